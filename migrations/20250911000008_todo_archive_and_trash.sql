@@ -0,0 +1,33 @@
+-- Soft-delete (trash) and archive support for todos
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS is_archived BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP;
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS is_deleted BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;
+
+-- Indexes for active list, archive and trash lookups
+CREATE INDEX IF NOT EXISTS idx_todos_user_id_active ON todos(user_id, created DESC)
+    WHERE is_deleted = FALSE AND is_archived = FALSE;
+CREATE INDEX IF NOT EXISTS idx_todos_deleted_at ON todos(deleted_at) WHERE is_deleted = TRUE;
+
+-- users and todos use `updated` rather than `updated_at`, so they need their own timestamp function
+CREATE OR REPLACE FUNCTION update_updated_timestamp()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated = NOW();
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS update_todos_updated ON todos;
+CREATE TRIGGER update_todos_updated
+    BEFORE UPDATE
+    ON todos
+    FOR EACH ROW
+EXECUTE PROCEDURE update_updated_timestamp();
+
+DROP TRIGGER IF EXISTS update_users_updated ON users;
+CREATE TRIGGER update_users_updated
+    BEFORE UPDATE
+    ON users
+    FOR EACH ROW
+EXECUTE PROCEDURE update_updated_timestamp();
@@ -0,0 +1,35 @@
+-- Audit trail of every change made to a todo
+CREATE TABLE IF NOT EXISTS todo_revisions (
+    id SERIAL PRIMARY KEY,
+    todo_id INTEGER NOT NULL REFERENCES todos(id) ON DELETE CASCADE,
+    -- The user who made the change
+    actor_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    action VARCHAR(20) NOT NULL,
+    -- Field-level diff: {"field": {"from": old, "to": new}}
+    changes JSONB NOT NULL DEFAULT '{}',
+    -- Set when the change reverted the todo to an earlier revision
+    reverted_to_revision_id INTEGER REFERENCES todo_revisions(id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT valid_todo_revision_action CHECK (
+        action IN ('create', 'update', 'delete', 'restore', 'archive', 'unarchive', 'revert')
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_todo_revisions_todo_id ON todo_revisions(todo_id, id DESC);
+CREATE INDEX IF NOT EXISTS idx_todo_revisions_actor_id ON todo_revisions(actor_id);
+
+-- Todos that existed before the audit trail get a 'create' revision holding
+-- their current values, so reverts always have a starting point to replay from
+INSERT INTO todo_revisions (todo_id, actor_id, action, changes, created_at)
+SELECT t.id, t.user_id, 'create',
+       jsonb_build_object(
+           'title', jsonb_build_object('from', NULL, 'to', t.title),
+           'description', jsonb_build_object('from', NULL, 'to', t.description),
+           'is_archived', jsonb_build_object('from', NULL, 'to', t.is_archived),
+           'is_deleted', jsonb_build_object('from', NULL, 'to', t.is_deleted)
+       ),
+       t.created
+FROM todos t
+WHERE NOT EXISTS (SELECT 1 FROM todo_revisions r WHERE r.todo_id = t.id)
+ORDER BY t.id;
@@ -0,0 +1,57 @@
+-- Delta sync support for todos.
+--
+-- Every insert/update stamps the row with the id of the writing transaction.
+-- A sync token is the oldest transaction still running when the client last
+-- synced (pg_snapshot_xmin), so changes committed after that point are never
+-- missed, even if their transaction started earlier.
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS created_xid BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS sync_xid BIGINT NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_todos_user_id_sync_xid ON todos(user_id, sync_xid);
+
+-- Todos removed for good (trash purge, account deletion) leave a tombstone so
+-- that clients syncing later still learn about the deletion
+CREATE TABLE IF NOT EXISTS todo_tombstones (
+    todo_id INTEGER PRIMARY KEY,
+    -- No foreign key: tombstones are written while the owner may be deleted
+    user_id INTEGER NOT NULL,
+    sync_xid BIGINT NOT NULL,
+    deleted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_todo_tombstones_user_id_sync_xid ON todo_tombstones(user_id, sync_xid);
+
+CREATE OR REPLACE FUNCTION stamp_todo_sync_xid()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.sync_xid = pg_current_xact_id()::text::bigint;
+    IF TG_OP = 'INSERT' THEN
+        NEW.created_xid = NEW.sync_xid;
+    END IF;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE OR REPLACE FUNCTION record_todo_tombstone()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO todo_tombstones (todo_id, user_id, sync_xid)
+    VALUES (OLD.id, OLD.user_id, pg_current_xact_id()::text::bigint)
+    ON CONFLICT (todo_id) DO UPDATE SET sync_xid = EXCLUDED.sync_xid, deleted_at = NOW();
+    RETURN OLD;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS stamp_todos_sync_xid ON todos;
+CREATE TRIGGER stamp_todos_sync_xid
+    BEFORE INSERT OR UPDATE
+    ON todos
+    FOR EACH ROW
+EXECUTE PROCEDURE stamp_todo_sync_xid();
+
+DROP TRIGGER IF EXISTS record_todos_tombstone ON todos;
+CREATE TRIGGER record_todos_tombstone
+    AFTER DELETE
+    ON todos
+    FOR EACH ROW
+EXECUTE PROCEDURE record_todo_tombstone();
@@ -0,0 +1,20 @@
+-- Conversation threads: every reply remembers the post that started its thread
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS root_post_id INTEGER REFERENCES posts(id) ON DELETE CASCADE;
+
+-- Backfill existing replies by walking up their reply chains
+WITH RECURSIVE chain AS (
+    SELECT id, id AS root_id
+    FROM posts
+    WHERE reply_to_post_id IS NULL
+    UNION ALL
+    SELECT p.id, chain.root_id
+    FROM posts p
+    INNER JOIN chain ON p.reply_to_post_id = chain.id
+)
+UPDATE posts
+SET root_post_id = chain.root_id
+FROM chain
+WHERE posts.id = chain.id AND posts.reply_to_post_id IS NOT NULL AND posts.root_post_id IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_posts_reply_to_created_at ON posts(reply_to_post_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_posts_root_post_id ON posts(root_post_id);
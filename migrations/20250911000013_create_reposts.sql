@@ -0,0 +1,40 @@
+-- Quote posts reference the post they quote
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS quote_of_post_id INTEGER REFERENCES posts(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_posts_quote_of_post_id ON posts(quote_of_post_id) WHERE quote_of_post_id IS NOT NULL;
+
+-- Create reposts table
+CREATE TABLE IF NOT EXISTS reposts (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    -- A post can only be reposted once per user
+    CONSTRAINT unique_repost UNIQUE (user_id, post_id)
+);
+
+-- Indexes for performance
+CREATE INDEX IF NOT EXISTS idx_reposts_user_id_created_at ON reposts(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_reposts_post_id ON reposts(post_id);
+
+-- Function to update post repost counts
+CREATE OR REPLACE FUNCTION update_repost_counts()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        UPDATE posts SET repost_count = COALESCE(repost_count, 0) + 1 WHERE id = NEW.post_id;
+        RETURN NEW;
+    ELSIF TG_OP = 'DELETE' THEN
+        UPDATE posts SET repost_count = GREATEST(0, COALESCE(repost_count, 0) - 1) WHERE id = OLD.post_id;
+        RETURN OLD;
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+-- Trigger to automatically update repost counts
+DROP TRIGGER IF EXISTS trigger_repost_counts ON reposts;
+CREATE TRIGGER trigger_repost_counts
+    AFTER INSERT OR DELETE ON reposts
+    FOR EACH ROW
+EXECUTE PROCEDURE update_repost_counts();
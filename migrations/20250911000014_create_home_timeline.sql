@@ -0,0 +1,96 @@
+-- Materialized home timelines (fan-out-on-write).
+-- One row per post per reader, pointing at the latest activity that put the
+-- post into the reader's timeline: the post itself or a repost.
+CREATE TABLE IF NOT EXISTS home_timeline (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    -- Set when the post is in the timeline because of a repost
+    reposter_id INTEGER REFERENCES users(id) ON DELETE CASCADE,
+    activity_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (user_id, post_id)
+);
+
+-- Keyset pagination index
+CREATE INDEX IF NOT EXISTS idx_home_timeline_user_activity ON home_timeline(user_id, activity_at DESC, post_id DESC);
+CREATE INDEX IF NOT EXISTS idx_home_timeline_post_id ON home_timeline(post_id);
+
+-- Queue of changes the fan-out worker still has to apply.
+-- No foreign keys: the referenced rows may be gone by the time the event is handled.
+CREATE TABLE IF NOT EXISTS timeline_events (
+    id BIGSERIAL PRIMARY KEY,
+    kind VARCHAR(20) NOT NULL,
+    actor_id INTEGER NOT NULL,
+    post_id INTEGER,
+    target_user_id INTEGER,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT valid_timeline_event_kind CHECK (
+        kind IN ('post_created', 'post_deleted', 'repost_created', 'repost_deleted',
+                 'follow_created', 'follow_deleted')
+    )
+);
+
+-- Function to enqueue timeline events
+CREATE OR REPLACE FUNCTION enqueue_timeline_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_TABLE_NAME = 'posts' THEN
+        IF TG_OP = 'INSERT' THEN
+            INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('post_created', NEW.user_id, NEW.id);
+        ELSIF NEW.is_deleted IS TRUE AND OLD.is_deleted IS NOT TRUE THEN
+            INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('post_deleted', NEW.user_id, NEW.id);
+        END IF;
+        RETURN NEW;
+    ELSIF TG_TABLE_NAME = 'reposts' THEN
+        IF TG_OP = 'INSERT' THEN
+            INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('repost_created', NEW.user_id, NEW.post_id);
+            RETURN NEW;
+        END IF;
+        INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('repost_deleted', OLD.user_id, OLD.post_id);
+        RETURN OLD;
+    ELSIF TG_TABLE_NAME = 'follows' THEN
+        IF TG_OP = 'INSERT' THEN
+            INSERT INTO timeline_events (kind, actor_id, target_user_id) VALUES ('follow_created', NEW.follower_id, NEW.following_id);
+            RETURN NEW;
+        END IF;
+        INSERT INTO timeline_events (kind, actor_id, target_user_id) VALUES ('follow_deleted', OLD.follower_id, OLD.following_id);
+        RETURN OLD;
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS trigger_posts_timeline ON posts;
+CREATE TRIGGER trigger_posts_timeline
+    AFTER INSERT OR UPDATE OF is_deleted ON posts
+    FOR EACH ROW
+EXECUTE PROCEDURE enqueue_timeline_event();
+
+DROP TRIGGER IF EXISTS trigger_reposts_timeline ON reposts;
+CREATE TRIGGER trigger_reposts_timeline
+    AFTER INSERT OR DELETE ON reposts
+    FOR EACH ROW
+EXECUTE PROCEDURE enqueue_timeline_event();
+
+DROP TRIGGER IF EXISTS trigger_follows_timeline ON follows;
+CREATE TRIGGER trigger_follows_timeline
+    AFTER INSERT OR DELETE ON follows
+    FOR EACH ROW
+EXECUTE PROCEDURE enqueue_timeline_event();
+
+-- Backfill timelines for existing follows, once
+INSERT INTO home_timeline (user_id, post_id, reposter_id, activity_at)
+SELECT DISTINCT ON (f.follower_id, a.post_id) f.follower_id, a.post_id, a.reposter_id, a.activity_at
+FROM follows f
+INNER JOIN (
+    SELECT p.user_id AS source_id, p.id AS post_id, NULL::INT4 AS reposter_id, p.created_at AS activity_at
+    FROM posts p
+    WHERE p.is_deleted IS NOT TRUE
+    UNION ALL
+    SELECT r.user_id, r.post_id, r.user_id, r.created_at
+    FROM reposts r
+    INNER JOIN posts p ON p.id = r.post_id
+    WHERE p.is_deleted IS NOT TRUE
+) a ON a.source_id = f.following_id
+WHERE NOT EXISTS (SELECT 1 FROM home_timeline)
+ORDER BY f.follower_id, a.post_id, a.activity_at DESC;
@@ -0,0 +1,14 @@
+-- Pending follow requests for private accounts
+CREATE TABLE IF NOT EXISTS follow_requests (
+    id SERIAL PRIMARY KEY,
+    requester_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    target_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    -- Constraints
+    CONSTRAINT no_self_follow_request CHECK (requester_id != target_id),
+    CONSTRAINT unique_follow_request UNIQUE (requester_id, target_id)
+);
+
+-- Inbox lookup
+CREATE INDEX IF NOT EXISTS idx_follow_requests_target_id_created_at ON follow_requests(target_id, created_at DESC);
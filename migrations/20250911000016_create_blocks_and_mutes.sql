@@ -0,0 +1,27 @@
+-- Create blocks table
+CREATE TABLE IF NOT EXISTS blocks (
+    id SERIAL PRIMARY KEY,
+    blocker_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    blocked_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    -- Constraints
+    CONSTRAINT no_self_block CHECK (blocker_id != blocked_id),
+    CONSTRAINT unique_block UNIQUE (blocker_id, blocked_id)
+);
+
+-- Create mutes table
+CREATE TABLE IF NOT EXISTS mutes (
+    id SERIAL PRIMARY KEY,
+    muter_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    muted_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    -- Constraints
+    CONSTRAINT no_self_mute CHECK (muter_id != muted_id),
+    CONSTRAINT unique_mute UNIQUE (muter_id, muted_id)
+);
+
+-- Blocks are checked in both directions, so index both sides
+CREATE INDEX IF NOT EXISTS idx_blocks_blocked_id ON blocks(blocked_id);
+CREATE INDEX IF NOT EXISTS idx_mutes_muter_id_created_at ON mutes(muter_id, created_at DESC);
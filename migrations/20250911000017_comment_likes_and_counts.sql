@@ -0,0 +1,79 @@
+-- Create comment likes table
+CREATE TABLE IF NOT EXISTS comment_likes (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    comment_id INTEGER NOT NULL REFERENCES comments(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    -- Prevent duplicate likes
+    CONSTRAINT unique_comment_like UNIQUE (user_id, comment_id)
+);
+
+-- Indexes for performance
+CREATE INDEX IF NOT EXISTS idx_comment_likes_comment_id ON comment_likes(comment_id);
+
+-- Function to update comment like counts
+CREATE OR REPLACE FUNCTION update_comment_like_counts()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        UPDATE comments SET like_count = COALESCE(like_count, 0) + 1 WHERE id = NEW.comment_id;
+        RETURN NEW;
+    ELSIF TG_OP = 'DELETE' THEN
+        UPDATE comments SET like_count = GREATEST(0, COALESCE(like_count, 0) - 1) WHERE id = OLD.comment_id;
+        RETURN OLD;
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+-- Trigger to automatically update comment like counts
+DROP TRIGGER IF EXISTS trigger_comment_like_counts ON comment_likes;
+CREATE TRIGGER trigger_comment_like_counts
+    AFTER INSERT OR DELETE ON comment_likes
+    FOR EACH ROW
+EXECUTE PROCEDURE update_comment_like_counts();
+
+-- Post comment counts only include live comments, so soft deletes and
+-- restores have to adjust them too
+CREATE OR REPLACE FUNCTION update_comment_counts()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        IF NEW.is_deleted IS NOT TRUE THEN
+            UPDATE posts SET comment_count = COALESCE(comment_count, 0) + 1 WHERE id = NEW.post_id;
+        END IF;
+        RETURN NEW;
+    ELSIF TG_OP = 'DELETE' THEN
+        IF OLD.is_deleted IS NOT TRUE THEN
+            UPDATE posts SET comment_count = GREATEST(0, COALESCE(comment_count, 0) - 1) WHERE id = OLD.post_id;
+        END IF;
+        RETURN OLD;
+    ELSIF TG_OP = 'UPDATE' THEN
+        IF NEW.is_deleted IS TRUE AND OLD.is_deleted IS NOT TRUE THEN
+            UPDATE posts SET comment_count = GREATEST(0, COALESCE(comment_count, 0) - 1) WHERE id = NEW.post_id;
+        ELSIF NEW.is_deleted IS NOT TRUE AND OLD.is_deleted IS TRUE THEN
+            UPDATE posts SET comment_count = COALESCE(comment_count, 0) + 1 WHERE id = NEW.post_id;
+        END IF;
+        RETURN NEW;
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS trigger_comment_counts ON comments;
+CREATE TRIGGER trigger_comment_counts
+    AFTER INSERT OR DELETE OR UPDATE OF is_deleted ON comments
+    FOR EACH ROW
+EXECUTE PROCEDURE update_comment_counts();
+
+-- Recount posts whose comment_count drifted from earlier soft deletes
+UPDATE posts p
+SET comment_count = c.total
+FROM (
+    SELECT p2.id, COUNT(c2.id) AS total
+    FROM posts p2
+    LEFT JOIN comments c2 ON c2.post_id = p2.id AND c2.is_deleted IS NOT TRUE
+    GROUP BY p2.id
+) c
+WHERE p.id = c.id AND p.comment_count IS DISTINCT FROM c.total;
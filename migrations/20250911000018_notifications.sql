@@ -0,0 +1,44 @@
+-- Create notifications table. Actions on the same target are grouped into a
+-- single unread row per recipient ("alice and 3 others liked your post").
+CREATE TABLE IF NOT EXISTS notifications (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(32) NOT NULL,
+    group_key VARCHAR(64) NOT NULL,
+    post_id INTEGER REFERENCES posts(id) ON DELETE CASCADE,
+    comment_id INTEGER REFERENCES comments(id) ON DELETE CASCADE,
+    actor_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    actor_count INTEGER NOT NULL DEFAULT 1,
+    is_read BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    -- Constraints
+    CONSTRAINT valid_notification_kind CHECK (
+        kind IN ('follow', 'follow_request', 'like', 'comment', 'reply', 'repost', 'mention')
+    )
+);
+
+-- Everyone who contributed to a grouped notification
+CREATE TABLE IF NOT EXISTS notification_actors (
+    notification_id INTEGER NOT NULL REFERENCES notifications(id) ON DELETE CASCADE,
+    actor_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (notification_id, actor_id)
+);
+
+-- Per-type opt-outs; a missing row means the type is enabled
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(32) NOT NULL,
+    enabled BOOLEAN NOT NULL,
+    PRIMARY KEY (user_id, kind)
+);
+
+-- At most one open group per recipient and target
+CREATE UNIQUE INDEX IF NOT EXISTS idx_notifications_unread_group
+    ON notifications(user_id, group_key) WHERE is_read = FALSE;
+CREATE INDEX IF NOT EXISTS idx_notifications_user_id_updated_at
+    ON notifications(user_id, updated_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_notification_actors_recent
+    ON notification_actors(notification_id, created_at DESC);
@@ -0,0 +1,86 @@
+-- Per-user log of realtime events, kept for a day so clients can resume a
+-- stream with Last-Event-ID. There is no foreign key on user_id: events are
+-- written while a user's rows are being cascade-deleted.
+CREATE TABLE IF NOT EXISTS stream_events (
+    id BIGSERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    kind VARCHAR(32) NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_stream_events_user_id_id ON stream_events(user_id, id);
+CREATE INDEX IF NOT EXISTS idx_stream_events_created_at ON stream_events(created_at);
+
+-- Records new notifications, new home timeline entries and todo changes
+CREATE OR REPLACE FUNCTION enqueue_stream_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_TABLE_NAME = 'notifications' THEN
+        INSERT INTO stream_events (user_id, kind, payload)
+        VALUES (NEW.user_id, 'notification', json_build_object(
+            'notification_id', NEW.id, 'kind', NEW.kind, 'post_id', NEW.post_id,
+            'comment_id', NEW.comment_id, 'actor_count', NEW.actor_count));
+        RETURN NEW;
+    ELSIF TG_TABLE_NAME = 'home_timeline' THEN
+        INSERT INTO stream_events (user_id, kind, payload)
+        VALUES (NEW.user_id, 'feed_item', json_build_object(
+            'post_id', NEW.post_id, 'reposter_id', NEW.reposter_id));
+        RETURN NEW;
+    ELSIF TG_TABLE_NAME = 'todos' THEN
+        IF TG_OP = 'DELETE' THEN
+            INSERT INTO stream_events (user_id, kind, payload)
+            VALUES (OLD.user_id, 'todo', json_build_object('todo_id', OLD.id, 'op', 'deleted'));
+            RETURN OLD;
+        END IF;
+        INSERT INTO stream_events (user_id, kind, payload)
+        VALUES (NEW.user_id, 'todo', json_build_object(
+            'todo_id', NEW.id, 'op', CASE WHEN TG_OP = 'INSERT' THEN 'created' ELSE 'updated' END));
+        RETURN NEW;
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+-- Wakes the listener on every instance
+CREATE OR REPLACE FUNCTION notify_stream_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('stream_events', json_build_object(
+        'id', NEW.id, 'user_id', NEW.user_id, 'kind', NEW.kind,
+        'payload', NEW.payload, 'created_at', NEW.created_at)::text);
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS trigger_notifications_stream ON notifications;
+CREATE TRIGGER trigger_notifications_stream
+    AFTER INSERT ON notifications
+    FOR EACH ROW
+EXECUTE PROCEDURE enqueue_stream_event();
+
+-- A grouped notification only changes for the user when another actor joins
+DROP TRIGGER IF EXISTS trigger_notifications_grouped_stream ON notifications;
+CREATE TRIGGER trigger_notifications_grouped_stream
+    AFTER UPDATE ON notifications
+    FOR EACH ROW
+    WHEN (OLD.actor_count IS DISTINCT FROM NEW.actor_count OR OLD.actor_id IS DISTINCT FROM NEW.actor_id)
+EXECUTE PROCEDURE enqueue_stream_event();
+
+DROP TRIGGER IF EXISTS trigger_home_timeline_stream ON home_timeline;
+CREATE TRIGGER trigger_home_timeline_stream
+    AFTER INSERT OR UPDATE OF activity_at ON home_timeline
+    FOR EACH ROW
+EXECUTE PROCEDURE enqueue_stream_event();
+
+DROP TRIGGER IF EXISTS trigger_todos_stream ON todos;
+CREATE TRIGGER trigger_todos_stream
+    AFTER INSERT OR UPDATE OR DELETE ON todos
+    FOR EACH ROW
+EXECUTE PROCEDURE enqueue_stream_event();
+
+DROP TRIGGER IF EXISTS trigger_stream_events_notify ON stream_events;
+CREATE TRIGGER trigger_stream_events_notify
+    AFTER INSERT ON stream_events
+    FOR EACH ROW
+EXECUTE PROCEDURE notify_stream_event();
@@ -0,0 +1,26 @@
+-- Users mentioned in a post
+CREATE TABLE IF NOT EXISTS post_mentions (
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (post_id, user_id)
+);
+
+-- Create hashtags table; tags are stored lowercased
+CREATE TABLE IF NOT EXISTS hashtags (
+    id SERIAL PRIMARY KEY,
+    tag VARCHAR(100) NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- created_at is copied from the post so trending windows follow post time
+CREATE TABLE IF NOT EXISTS post_hashtags (
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    hashtag_id INTEGER NOT NULL REFERENCES hashtags(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (post_id, hashtag_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_post_mentions_user_id ON post_mentions(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_post_hashtags_hashtag_id_created_at ON post_hashtags(hashtag_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_post_hashtags_created_at ON post_hashtags(created_at);
@@ -0,0 +1,18 @@
+-- Uploaded images; the files themselves live in the configured blob store
+CREATE TABLE IF NOT EXISTS media (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    content_type VARCHAR(50) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    storage_key VARCHAR(255) NOT NULL UNIQUE,
+    thumbnail_key VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- Posts and profiles reference uploads instead of arbitrary URLs
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS media_id INTEGER REFERENCES media(id) ON DELETE SET NULL;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS avatar_media_id INTEGER REFERENCES media(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_media_user_id ON media(user_id, created_at DESC);
@@ -0,0 +1,50 @@
+-- Placeholder shown while an image loads
+ALTER TABLE media ADD COLUMN IF NOT EXISTS blurhash VARCHAR(64);
+
+-- Up to four ordered attachments per post
+CREATE TABLE IF NOT EXISTS post_media (
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    media_id INTEGER NOT NULL REFERENCES media(id) ON DELETE CASCADE,
+    position SMALLINT NOT NULL CHECK (position BETWEEN 0 AND 3),
+    alt_text VARCHAR(1500),
+    PRIMARY KEY (post_id, position),
+    CONSTRAINT unique_post_media UNIQUE (post_id, media_id)
+);
+
+-- Files of deleted media rows, removed from the blob store by a worker
+CREATE TABLE IF NOT EXISTS media_deletions (
+    storage_key VARCHAR(255) PRIMARY KEY,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_post_media_media_id ON post_media(media_id);
+
+CREATE OR REPLACE FUNCTION enqueue_media_deletion()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO media_deletions (storage_key)
+    VALUES (OLD.storage_key), (OLD.thumbnail_key)
+    ON CONFLICT (storage_key) DO NOTHING;
+    RETURN OLD;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS trigger_media_deletion ON media;
+CREATE TRIGGER trigger_media_deletion
+    AFTER DELETE ON media
+    FOR EACH ROW
+EXECUTE PROCEDURE enqueue_media_deletion();
+
+-- Single attachments recorded in posts.media_id move to post_media
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'posts' AND column_name = 'media_id'
+    ) THEN
+        INSERT INTO post_media (post_id, media_id, position)
+        SELECT id, media_id, 0 FROM posts WHERE media_id IS NOT NULL
+        ON CONFLICT DO NOTHING;
+        ALTER TABLE posts DROP COLUMN media_id;
+    END IF;
+END $$;
@@ -0,0 +1,75 @@
+-- Moderators work through the report queue
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_admin BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Set by the content filter: 'held' waits for review, 'hidden' is shadow-hidden.
+-- Either way only the author sees the content.
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS moderation_state VARCHAR(16)
+    CHECK (moderation_state IN ('held', 'hidden'));
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS moderation_state VARCHAR(16)
+    CHECK (moderation_state IN ('held', 'hidden'));
+
+-- Reports filed by users, and by the content filter (reporter_id NULL).
+-- target_id points at posts, comments or users depending on target_type.
+CREATE TABLE IF NOT EXISTS reports (
+    id SERIAL PRIMARY KEY,
+    reporter_id INTEGER REFERENCES users(id) ON DELETE CASCADE,
+    target_type VARCHAR(16) NOT NULL CHECK (target_type IN ('post', 'comment', 'user')),
+    target_id INTEGER NOT NULL,
+    reason VARCHAR(32) NOT NULL CHECK (reason IN (
+        'spam', 'harassment', 'hate', 'violence', 'sexual', 'misinformation', 'other',
+        'content_filter'
+    )),
+    details TEXT,
+    status VARCHAR(16) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'resolved', 'dismissed')),
+    resolved_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    resolution_note TEXT,
+    resolved_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_reports_open_per_reporter
+    ON reports(reporter_id, target_type, target_id) WHERE status = 'open';
+CREATE INDEX IF NOT EXISTS idx_reports_status_created_at ON reports(status, created_at, id);
+CREATE INDEX IF NOT EXISTS idx_reports_target ON reports(target_type, target_id);
+
+-- Moderated posts leave home timelines, and come back when released
+CREATE OR REPLACE FUNCTION enqueue_timeline_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_TABLE_NAME = 'posts' THEN
+        IF TG_OP = 'INSERT' THEN
+            INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('post_created', NEW.user_id, NEW.id);
+        ELSIF NEW.is_deleted IS TRUE AND OLD.is_deleted IS NOT TRUE THEN
+            INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('post_deleted', NEW.user_id, NEW.id);
+        ELSIF NEW.moderation_state IS NOT NULL AND OLD.moderation_state IS NULL THEN
+            -- Flagged by the content filter after an edit
+            INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('post_deleted', NEW.user_id, NEW.id);
+        ELSIF NEW.moderation_state IS NULL AND OLD.moderation_state IS NOT NULL THEN
+            -- Released by a moderator
+            INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('post_created', NEW.user_id, NEW.id);
+        END IF;
+        RETURN NEW;
+    ELSIF TG_TABLE_NAME = 'reposts' THEN
+        IF TG_OP = 'INSERT' THEN
+            INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('repost_created', NEW.user_id, NEW.post_id);
+            RETURN NEW;
+        END IF;
+        INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('repost_deleted', OLD.user_id, OLD.post_id);
+        RETURN OLD;
+    ELSIF TG_TABLE_NAME = 'follows' THEN
+        IF TG_OP = 'INSERT' THEN
+            INSERT INTO timeline_events (kind, actor_id, target_user_id) VALUES ('follow_created', NEW.follower_id, NEW.following_id);
+            RETURN NEW;
+        END IF;
+        INSERT INTO timeline_events (kind, actor_id, target_user_id) VALUES ('follow_deleted', OLD.follower_id, OLD.following_id);
+        RETURN OLD;
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS trigger_posts_timeline ON posts;
+CREATE TRIGGER trigger_posts_timeline
+    AFTER INSERT OR UPDATE OF is_deleted, moderation_state ON posts
+    FOR EACH ROW
+EXECUTE PROCEDURE enqueue_timeline_event();
@@ -0,0 +1,24 @@
+-- Named groups a user files bookmarks into
+CREATE TABLE IF NOT EXISTS bookmark_collections (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- Private saved posts. Deleting a collection keeps its bookmarks unfiled.
+CREATE TABLE IF NOT EXISTS bookmarks (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    collection_id INTEGER REFERENCES bookmark_collections(id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, post_id)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_bookmark_collections_user_name
+    ON bookmark_collections(user_id, LOWER(name));
+CREATE INDEX IF NOT EXISTS idx_bookmarks_user_created_at
+    ON bookmarks(user_id, created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_bookmarks_collection_created_at
+    ON bookmarks(collection_id, created_at DESC, id DESC);
@@ -0,0 +1,32 @@
+-- Posts that are not published yet: drafts have no publish time, scheduled
+-- posts are published by a worker once publish_at passes, and failed ones
+-- could not be published and wait for the author to edit them. Reply and
+-- quote targets are checked again when the post is published.
+CREATE TABLE IF NOT EXISTS scheduled_posts (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    image_url TEXT,
+    reply_to_post_id INTEGER,
+    quote_of_post_id INTEGER,
+    status VARCHAR(16) NOT NULL CHECK (status IN ('draft', 'scheduled', 'failed')),
+    publish_at TIMESTAMP,
+    error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CHECK (status <> 'scheduled' OR publish_at IS NOT NULL)
+);
+
+CREATE TABLE IF NOT EXISTS scheduled_post_media (
+    scheduled_post_id INTEGER NOT NULL REFERENCES scheduled_posts(id) ON DELETE CASCADE,
+    media_id INTEGER NOT NULL REFERENCES media(id) ON DELETE CASCADE,
+    position SMALLINT NOT NULL CHECK (position BETWEEN 0 AND 3),
+    alt_text VARCHAR(1500),
+    PRIMARY KEY (scheduled_post_id, position),
+    UNIQUE (scheduled_post_id, media_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_scheduled_posts_user_id ON scheduled_posts(user_id);
+CREATE INDEX IF NOT EXISTS idx_scheduled_posts_due
+    ON scheduled_posts(publish_at) WHERE status = 'scheduled';
+CREATE INDEX IF NOT EXISTS idx_scheduled_post_media_media_id ON scheduled_post_media(media_id);
@@ -0,0 +1,65 @@
+-- A poll attached to a post; vote_count is the number of voters
+CREATE TABLE IF NOT EXISTS polls (
+    id SERIAL PRIMARY KEY,
+    post_id INTEGER NOT NULL UNIQUE REFERENCES posts(id) ON DELETE CASCADE,
+    closes_at TIMESTAMP NOT NULL,
+    vote_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS poll_options (
+    id SERIAL PRIMARY KEY,
+    poll_id INTEGER NOT NULL REFERENCES polls(id) ON DELETE CASCADE,
+    position SMALLINT NOT NULL CHECK (position BETWEEN 0 AND 3),
+    label VARCHAR(100) NOT NULL,
+    vote_count INTEGER NOT NULL DEFAULT 0,
+    UNIQUE (poll_id, position)
+);
+
+-- One vote per user and poll
+CREATE TABLE IF NOT EXISTS poll_votes (
+    poll_id INTEGER NOT NULL REFERENCES polls(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    option_id INTEGER NOT NULL REFERENCES poll_options(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (poll_id, user_id)
+);
+
+-- Drafts keep their poll until they are published
+ALTER TABLE scheduled_posts ADD COLUMN IF NOT EXISTS poll_options TEXT[];
+ALTER TABLE scheduled_posts ADD COLUMN IF NOT EXISTS poll_duration_minutes INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_poll_options_poll_id ON poll_options(poll_id, position);
+CREATE INDEX IF NOT EXISTS idx_poll_votes_option_id ON poll_votes(option_id);
+CREATE INDEX IF NOT EXISTS idx_poll_votes_user_id ON poll_votes(user_id);
+
+-- Tallies move with the votes; each vote locks its option and poll row, so
+-- concurrent votes are counted one after the other
+CREATE OR REPLACE FUNCTION update_poll_vote_counts()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        UPDATE poll_options SET vote_count = vote_count + 1 WHERE id = NEW.option_id;
+        UPDATE polls SET vote_count = vote_count + 1 WHERE id = NEW.poll_id;
+        RETURN NEW;
+    ELSIF TG_OP = 'DELETE' THEN
+        -- Votes deleted along with their post have no tallies left to fix,
+        -- and touching the dying poll rows would fail their foreign keys
+        IF EXISTS (
+            SELECT 1 FROM polls p INNER JOIN posts ON posts.id = p.post_id WHERE p.id = OLD.poll_id
+        ) THEN
+            UPDATE poll_options SET vote_count = GREATEST(0, vote_count - 1) WHERE id = OLD.option_id;
+            UPDATE polls SET vote_count = GREATEST(0, vote_count - 1) WHERE id = OLD.poll_id;
+        END IF;
+        RETURN OLD;
+    END IF;
+
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS trigger_poll_vote_counts ON poll_votes;
+CREATE TRIGGER trigger_poll_vote_counts
+    AFTER INSERT OR DELETE ON poll_votes
+    FOR EACH ROW
+EXECUTE PROCEDURE update_poll_vote_counts();
@@ -0,0 +1,79 @@
+-- Direct-message threads. One-to-one conversations carry a direct_key of
+-- "<lower id>:<higher id>" so each pair of users shares a single thread;
+-- group conversations leave it NULL.
+CREATE TABLE IF NOT EXISTS conversations (
+    id SERIAL PRIMARY KEY,
+    created_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    is_group BOOLEAN NOT NULL DEFAULT FALSE,
+    direct_key VARCHAR(32) UNIQUE,
+    last_message_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- last_read_message_id is the member's read receipt
+CREATE TABLE IF NOT EXISTS conversation_members (
+    conversation_id INTEGER NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    last_read_message_id INTEGER,
+    last_read_at TIMESTAMP,
+    joined_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (conversation_id, user_id)
+);
+
+-- Messages outlive their sender's account
+CREATE TABLE IF NOT EXISTS messages (
+    id SERIAL PRIMARY KEY,
+    conversation_id INTEGER NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    sender_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    content TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_conversation_members_user_id ON conversation_members(user_id);
+CREATE INDEX IF NOT EXISTS idx_messages_conversation_id_id ON messages(conversation_id, id DESC);
+
+-- Records new notifications, new home timeline entries, todo changes and new
+-- direct messages
+CREATE OR REPLACE FUNCTION enqueue_stream_event()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_TABLE_NAME = 'notifications' THEN
+        INSERT INTO stream_events (user_id, kind, payload)
+        VALUES (NEW.user_id, 'notification', json_build_object(
+            'notification_id', NEW.id, 'kind', NEW.kind, 'post_id', NEW.post_id,
+            'comment_id', NEW.comment_id, 'actor_count', NEW.actor_count));
+        RETURN NEW;
+    ELSIF TG_TABLE_NAME = 'home_timeline' THEN
+        INSERT INTO stream_events (user_id, kind, payload)
+        VALUES (NEW.user_id, 'feed_item', json_build_object(
+            'post_id', NEW.post_id, 'reposter_id', NEW.reposter_id));
+        RETURN NEW;
+    ELSIF TG_TABLE_NAME = 'todos' THEN
+        IF TG_OP = 'DELETE' THEN
+            INSERT INTO stream_events (user_id, kind, payload)
+            VALUES (OLD.user_id, 'todo', json_build_object('todo_id', OLD.id, 'op', 'deleted'));
+            RETURN OLD;
+        END IF;
+        INSERT INTO stream_events (user_id, kind, payload)
+        VALUES (NEW.user_id, 'todo', json_build_object(
+            'todo_id', NEW.id, 'op', CASE WHEN TG_OP = 'INSERT' THEN 'created' ELSE 'updated' END));
+        RETURN NEW;
+    ELSIF TG_TABLE_NAME = 'messages' THEN
+        INSERT INTO stream_events (user_id, kind, payload)
+        SELECT cm.user_id, 'message', json_build_object(
+            'conversation_id', NEW.conversation_id, 'message_id', NEW.id,
+            'sender_id', NEW.sender_id)
+        FROM conversation_members cm
+        WHERE cm.conversation_id = NEW.conversation_id
+          AND cm.user_id IS DISTINCT FROM NEW.sender_id;
+        RETURN NEW;
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS trigger_messages_stream ON messages;
+CREATE TRIGGER trigger_messages_stream
+    AFTER INSERT ON messages
+    FOR EACH ROW
+EXECUTE PROCEDURE enqueue_stream_event();
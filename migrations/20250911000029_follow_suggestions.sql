@@ -0,0 +1,7 @@
+-- Accounts a user dismissed from their who-to-follow suggestions
+CREATE TABLE IF NOT EXISTS dismissed_suggestions (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    dismissed_user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, dismissed_user_id)
+);
@@ -3,6 +3,11 @@ use google_cloud_secretmanager_v1::{
     client::SecretManagerService, model::AccessSecretVersionRequest,
 };
 
+use crate::service::blob_store::BlobStoreConfig;
+use crate::service::media::DEFAULT_MAX_UPLOAD_BYTES;
+use crate::service::moderation::RuleFilter;
+use crate::service::ranking::RankingWeights;
+
 #[derive(Clone, Debug)]
 pub struct Config {
     pub database_url: String,
@@ -10,6 +15,27 @@ pub struct Config {
     pub jwt_secret: String,
     pub server_port: u16,
     pub server_host: String,
+    /// Days a trashed todo is kept before the purge worker deletes it for good
+    pub todo_trash_retention_days: i32,
+    /// Strict mode: writes to versioned resources must send `If-Match`
+    pub require_if_match: bool,
+    /// Weights of the ranked "For You" timeline
+    pub ranking: RankingWeights,
+    /// Authors with more followers than this are merged into home timelines
+    /// at read time instead of being fanned out on write
+    pub timeline_fanout_max_followers: i32,
+    /// Backend uploaded media is stored in
+    pub media_storage: BlobStoreConfig,
+    /// Prefix of media URLs handed to clients; points at `/media/files` unless
+    /// the files are served straight from a bucket or CDN
+    pub media_public_base_url: String,
+    /// Largest accepted upload, enforced instead of the global body limit
+    pub media_max_upload_bytes: usize,
+    /// Rules checked against new and edited posts and comments
+    pub content_filter: RuleFilter,
+    /// Minutes after creation during which a post can be edited; 0 turns
+    /// editing off
+    pub post_edit_window_minutes: i32,
 }
 
 impl Config {
@@ -61,6 +87,42 @@ impl Config {
             .or_else(|_| std::env::var("SERVER_HOST"))
             .unwrap_or_else(|_| "0.0.0.0".to_string());
 
+        let todo_trash_retention_days = std::env::var("TODO_TRASH_RETENTION_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<i32>()
+            .context("Invalid TODO_TRASH_RETENTION_DAYS value")?;
+
+        let require_if_match = std::env::var("REQUIRE_IF_MATCH")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()
+            .context("Invalid REQUIRE_IF_MATCH value")?;
+
+        let ranking = RankingWeights::from_env()?;
+
+        let timeline_fanout_max_followers = std::env::var("TIMELINE_FANOUT_MAX_FOLLOWERS")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse::<i32>()
+            .context("Invalid TIMELINE_FANOUT_MAX_FOLLOWERS value")?;
+
+        let media_storage = BlobStoreConfig::from_env()?;
+
+        let media_public_base_url =
+            std::env::var("MEDIA_PUBLIC_BASE_URL").unwrap_or_else(|_| "/media/files".to_string());
+
+        let media_max_upload_bytes = std::env::var("MEDIA_MAX_UPLOAD_BYTES")
+            .unwrap_or_else(|_| DEFAULT_MAX_UPLOAD_BYTES.to_string())
+            .parse::<usize>()
+            .context("Invalid MEDIA_MAX_UPLOAD_BYTES value")?;
+
+        let content_filter = RuleFilter::from_env()?;
+
+        let post_edit_window_minutes = std::env::var("POST_EDIT_WINDOW_MINUTES")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse::<i32>()
+            .ok()
+            .filter(|minutes| *minutes >= 0)
+            .context("Invalid POST_EDIT_WINDOW_MINUTES value")?;
+
         // Validate required secrets
         if hashing_secret_key.len() < 16 {
             anyhow::bail!("HASHING_SECRET_KEY must be at least 16 characters");
@@ -76,6 +138,15 @@ impl Config {
             jwt_secret,
             server_port,
             server_host,
+            todo_trash_retention_days,
+            require_if_match,
+            ranking,
+            timeline_fanout_max_followers,
+            media_storage,
+            media_public_base_url,
+            media_max_upload_bytes,
+            content_filter,
+            post_edit_window_minutes,
         })
     }
 
@@ -124,6 +195,42 @@ impl Config {
 
         let server_host = std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
 
+        let todo_trash_retention_days = std::env::var("TODO_TRASH_RETENTION_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<i32>()
+            .context("Invalid TODO_TRASH_RETENTION_DAYS value")?;
+
+        let require_if_match = std::env::var("REQUIRE_IF_MATCH")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()
+            .context("Invalid REQUIRE_IF_MATCH value")?;
+
+        let ranking = RankingWeights::from_env()?;
+
+        let timeline_fanout_max_followers = std::env::var("TIMELINE_FANOUT_MAX_FOLLOWERS")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse::<i32>()
+            .context("Invalid TIMELINE_FANOUT_MAX_FOLLOWERS value")?;
+
+        let media_storage = BlobStoreConfig::from_env()?;
+
+        let media_public_base_url =
+            std::env::var("MEDIA_PUBLIC_BASE_URL").unwrap_or_else(|_| "/media/files".to_string());
+
+        let media_max_upload_bytes = std::env::var("MEDIA_MAX_UPLOAD_BYTES")
+            .unwrap_or_else(|_| DEFAULT_MAX_UPLOAD_BYTES.to_string())
+            .parse::<usize>()
+            .context("Invalid MEDIA_MAX_UPLOAD_BYTES value")?;
+
+        let content_filter = RuleFilter::from_env()?;
+
+        let post_edit_window_minutes = std::env::var("POST_EDIT_WINDOW_MINUTES")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse::<i32>()
+            .ok()
+            .filter(|minutes| *minutes >= 0)
+            .context("Invalid POST_EDIT_WINDOW_MINUTES value")?;
+
         // Validate secrets
         if hashing_secret_key.len() < 16 {
             anyhow::bail!("HASHING_SECRET_KEY must be at least 16 characters");
@@ -144,6 +251,15 @@ impl Config {
             hashing_secret_key,
             server_port,
             server_host,
+            todo_trash_retention_days,
+            require_if_match,
+            ranking,
+            timeline_fanout_max_followers,
+            media_storage,
+            media_public_base_url,
+            media_max_upload_bytes,
+            content_filter,
+            post_edit_window_minutes,
         })
     }
 }
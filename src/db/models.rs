@@ -36,6 +36,8 @@ pub struct UpdateUserProfile {
     pub display_name: Option<String>,
     pub bio: Option<String>,
     pub avatar_url: Option<String>,
+    /// Uploaded image to use as avatar; takes precedence over `avatar_url`
+    pub avatar_media_id: Option<i32>,
     pub location: Option<String>,
     pub website: Option<String>,
     pub is_private: Option<bool>,
@@ -49,6 +51,29 @@ pub struct TodoModel {
     pub created: NaiveDateTime,
     pub updated: NaiveDateTime,
     pub user_id: i32,
+    pub is_archived: bool,
+    pub archived_at: Option<NaiveDateTime>,
+    pub is_deleted: bool,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub version: i32,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TodoRevisionModel {
+    pub id: i32,
+    pub todo_id: i32,
+    pub actor_id: Option<i32>,
+    pub action: String,
+    pub changes: serde_json::Value,
+    pub reverted_to_revision_id: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+/// A todo that was removed from a user's list, as reported to sync clients
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TodoTombstoneModel {
+    pub todo_id: i32,
+    pub deleted_at: NaiveDateTime,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -84,15 +109,124 @@ pub struct Post {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub reply_to_post_id: Option<i32>,
+    pub quote_of_post_id: Option<i32>,
     pub is_deleted: Option<bool>,
     pub deleted_at: Option<NaiveDateTime>,
+    pub version: i32,
+    /// `held` or `hidden` when the content filter flagged it; only the
+    /// author ever sees such content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moderation_state: Option<String>,
+    /// Last edit, `None` for posts never edited
+    pub edited_at: Option<NaiveDateTime>,
+    /// Earlier versions kept in `post_revisions`
+    pub revision_count: i32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreatePost {
     pub content: String,
     pub image_url: Option<String>,
+    /// Uploaded images to attach, in display order
+    #[serde(default)]
+    pub media: Vec<NewPostMedia>,
     pub reply_to_post_id: Option<i32>,
+    pub quote_of_post_id: Option<i32>,
+    #[serde(default)]
+    pub poll: Option<NewPoll>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewPostMedia {
+    pub media_id: i32,
+    /// Description of the image for screen readers
+    pub alt_text: Option<String>,
+}
+
+/// Media attached to a post, as returned to clients
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostMedia {
+    pub media_id: i32,
+    pub url: String,
+    pub thumbnail_url: String,
+    pub content_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub blurhash: Option<String>,
+    pub alt_text: Option<String>,
+}
+
+/// Poll to attach to a new post
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewPoll {
+    /// Answers in display order
+    pub options: Vec<String>,
+    /// How long the poll stays open once the post is published
+    pub duration_minutes: i32,
+}
+
+/// A poll as seen by one viewer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Poll {
+    pub id: i32,
+    pub closes_at: NaiveDateTime,
+    pub closed: bool,
+    /// Number of voters
+    pub vote_count: i32,
+    pub options: Vec<PollOption>,
+    /// The viewer's answer, if they voted
+    pub voted_option_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollOption {
+    pub id: i32,
+    pub label: String,
+    /// `None` until the viewer votes or the poll closes
+    pub vote_count: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePollVote {
+    pub option_id: i32,
+}
+
+/// How replies are treated when listing posts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplyMode {
+    /// Replies are listed like any other post
+    #[default]
+    Include,
+    /// Only posts that start a conversation are listed
+    Exclude,
+    /// Each conversation shows up once, represented by its newest post
+    Collapse,
+}
+
+/// Ordering of the home feed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedMode {
+    /// Newest activity from followed accounts first
+    #[default]
+    Chronological,
+    /// Scored mix of followed, second-degree and trending posts
+    Ranked,
+}
+
+/// A version of a post that an edit replaced
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PostRevision {
+    pub id: i32,
+    pub post_id: i32,
+    pub version: i32,
+    pub content: String,
+    pub image_url: Option<String>,
+    /// When this version went live
+    pub published_at: NaiveDateTime,
+    /// When an edit replaced it
+    pub replaced_at: NaiveDateTime,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,11 +243,45 @@ pub struct Follow {
     pub created_at: NaiveDateTime,
 }
 
+/// A pending request to follow a private account
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct FollowRequest {
+    pub id: i32,
+    pub requester_id: i32,
+    pub target_id: i32,
+    pub created_at: NaiveDateTime,
+}
+
+/// Result of following a user: an immediate follow for public accounts,
+/// a pending request for private ones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FollowOutcome {
+    Followed(Follow),
+    Requested(FollowRequest),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateFollow {
     pub following_id: i32,
 }
 
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Block {
+    pub id: i32,
+    pub blocker_id: i32,
+    pub blocked_id: i32,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Mute {
+    pub id: i32,
+    pub muter_id: i32,
+    pub muted_id: i32,
+    pub created_at: NaiveDateTime,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Like {
     pub id: i32,
@@ -127,6 +295,14 @@ pub struct CreateLike {
     pub post_id: i32,
 }
 
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Repost {
+    pub id: i32,
+    pub user_id: i32,
+    pub post_id: i32,
+    pub created_at: NaiveDateTime,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Comment {
     pub id: i32,
@@ -139,6 +315,19 @@ pub struct Comment {
     pub reply_to_comment_id: Option<i32>,
     pub is_deleted: Option<bool>,
     pub deleted_at: Option<NaiveDateTime>,
+    pub version: i32,
+    /// `held` or `hidden` when the content filter flagged it; only the
+    /// author ever sees such content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moderation_state: Option<String>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct CommentLike {
+    pub id: i32,
+    pub user_id: i32,
+    pub comment_id: i32,
+    pub created_at: NaiveDateTime,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -164,6 +353,91 @@ pub struct PostWithUser {
     pub is_following_author: bool,
 }
 
+/// Interaction a notification reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    Follow,
+    FollowRequest,
+    Like,
+    Comment,
+    Reply,
+    Repost,
+    Mention,
+}
+
+impl NotificationKind {
+    pub const ALL: [NotificationKind; 7] = [
+        NotificationKind::Follow,
+        NotificationKind::FollowRequest,
+        NotificationKind::Like,
+        NotificationKind::Comment,
+        NotificationKind::Reply,
+        NotificationKind::Repost,
+        NotificationKind::Mention,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::Follow => "follow",
+            NotificationKind::FollowRequest => "follow_request",
+            NotificationKind::Like => "like",
+            NotificationKind::Comment => "comment",
+            NotificationKind::Reply => "reply",
+            NotificationKind::Repost => "repost",
+            NotificationKind::Mention => "mention",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == value)
+    }
+}
+
+/// A notification, possibly grouping several actors acting on the same target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: i32,
+    pub kind: NotificationKind,
+    pub post_id: Option<i32>,
+    pub comment_id: Option<i32>,
+    pub actor_count: i32,
+    pub is_read: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Notification with its most recent actors and a display summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationGroup {
+    #[serde(flatten)]
+    pub notification: Notification,
+    pub actors: Vec<UserProfile>,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationPreference {
+    pub kind: NotificationKind,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnreadCount {
+    pub count: i64,
+}
+
+/// Realtime event pushed to a user's `/events` stream
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize, Deserialize)]
+pub struct StreamEvent {
+    pub id: i64,
+    pub user_id: i32,
+    /// `notification`, `feed_item`, `todo` or `message`
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub created_at: NaiveDateTime,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserProfile {
     pub id: i32,
@@ -188,9 +462,468 @@ pub struct CommentWithUser {
     pub user: UserProfile,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowRequestWithUser {
+    #[serde(flatten)]
+    pub request: FollowRequest,
+    pub user: UserProfile,
+}
+
+/// Who put a post into the feed by reposting it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepostAttribution {
+    pub user_id: i32,
+    pub username: String,
+    pub reposted_at: NaiveDateTime,
+}
+
+/// A post as it appears in the home feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedEntry {
+    #[serde(flatten)]
+    pub post: Post,
+    /// Set when the post is in the feed because a followed account reposted it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reposted_by: Option<RepostAttribution>,
+}
+
+/// A reply within a thread, with its own replies nested below it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadReply {
+    #[serde(flatten)]
+    pub post: Post,
+    /// Distance from the post the thread was requested for
+    pub depth: i32,
+    pub replies: Vec<ThreadReply>,
+}
+
+/// A comment with its replies nested below it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentNode {
+    #[serde(flatten)]
+    pub comment: Comment,
+    /// 1 for comments on the post itself
+    pub depth: i32,
+    pub is_liked: bool,
+    pub replies: Vec<CommentNode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentTree {
+    pub comments: Vec<CommentNode>,
+    /// Whether more top-level comments exist beyond the requested page
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostThread {
+    /// Posts above the requested one, starting with the conversation root
+    pub ancestors: Vec<Post>,
+    pub post: Post,
+    pub replies: Vec<ThreadReply>,
+    /// Whether more direct replies exist beyond the requested page
+    pub has_more_replies: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityKind {
+    Mention,
+    Hashtag,
+}
+
+/// A mention or hashtag inside post content. `start` and `end` are character
+/// offsets into the content, `end` exclusive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostEntity {
+    pub kind: EntityKind,
+    pub start: usize,
+    pub end: usize,
+    /// Username for mentions, lowercased tag for hashtags
+    pub text: String,
+    /// Mentioned user, when the username belongs to someone
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Media {
+    pub id: i32,
+    pub user_id: i32,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub width: i32,
+    pub height: i32,
+    pub storage_key: String,
+    pub thumbnail_key: String,
+    pub blurhash: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadedMedia {
+    #[serde(flatten)]
+    pub media: Media,
+    pub url: String,
+    pub thumbnail_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendingHashtag {
+    pub tag: String,
+    /// Posts using the tag within the requested window
+    pub post_count: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeedPost {
     #[serde(flatten)]
     pub post: PostWithUser,
+    pub entities: Vec<PostEntity>,
+    pub media: Vec<PostMedia>,
     pub comments: Vec<CommentWithUser>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poll: Option<Poll>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reposted_by: Option<RepostAttribution>,
+}
+
+/// What a report points at; `target_id` is a post, comment or user id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportTarget {
+    Post,
+    Comment,
+    User,
+}
+
+impl ReportTarget {
+    pub const ALL: [ReportTarget; 3] = [
+        ReportTarget::Post,
+        ReportTarget::Comment,
+        ReportTarget::User,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportTarget::Post => "post",
+            ReportTarget::Comment => "comment",
+            ReportTarget::User => "user",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|target| target.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportReason {
+    Spam,
+    Harassment,
+    Hate,
+    Violence,
+    Sexual,
+    Misinformation,
+    Other,
+    /// Filed by the automatic content filter, never by users
+    ContentFilter,
+}
+
+impl ReportReason {
+    pub const ALL: [ReportReason; 8] = [
+        ReportReason::Spam,
+        ReportReason::Harassment,
+        ReportReason::Hate,
+        ReportReason::Violence,
+        ReportReason::Sexual,
+        ReportReason::Misinformation,
+        ReportReason::Other,
+        ReportReason::ContentFilter,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportReason::Spam => "spam",
+            ReportReason::Harassment => "harassment",
+            ReportReason::Hate => "hate",
+            ReportReason::Violence => "violence",
+            ReportReason::Sexual => "sexual",
+            ReportReason::Misinformation => "misinformation",
+            ReportReason::Other => "other",
+            ReportReason::ContentFilter => "content_filter",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|reason| reason.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportStatus {
+    Open,
+    /// Action was taken against the target
+    Resolved,
+    /// No action was needed
+    Dismissed,
+}
+
+impl ReportStatus {
+    pub const ALL: [ReportStatus; 3] = [
+        ReportStatus::Open,
+        ReportStatus::Resolved,
+        ReportStatus::Dismissed,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportStatus::Open => "open",
+            ReportStatus::Resolved => "resolved",
+            ReportStatus::Dismissed => "dismissed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub id: i32,
+    /// `None` for reports filed by the content filter
+    pub reporter_id: Option<i32>,
+    pub target_type: ReportTarget,
+    pub target_id: i32,
+    pub reason: ReportReason,
+    pub details: Option<String>,
+    pub status: ReportStatus,
+    pub resolved_by: Option<i32>,
+    pub resolution_note: Option<String>,
+    pub resolved_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateReport {
+    pub target_type: ReportTarget,
+    pub target_id: i32,
+    pub reason: ReportReason,
+    pub details: Option<String>,
+}
+
+/// Body of the resolve and dismiss actions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportDecision {
+    pub note: Option<String>,
+}
+
+/// A post saved by a user, optionally filed in one of their collections
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub id: i32,
+    pub user_id: i32,
+    pub post_id: i32,
+    pub collection_id: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Body of `POST /posts/{id}/bookmark`; bookmarking a saved post again
+/// moves it to `collection_id`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateBookmark {
+    pub collection_id: Option<i32>,
+}
+
+/// A bookmark with its post. Posts that were deleted or can no longer be
+/// seen by the user leave a tombstone: `post` is `null` and `tombstone` is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkEntry {
+    pub id: i32,
+    pub post_id: i32,
+    pub collection_id: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub tombstone: bool,
+    pub post: Option<FeedPost>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct BookmarkCollection {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub bookmark_count: i64,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBookmarkCollection {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateBookmarkCollection {
+    pub name: String,
+}
+
+/// Where a post that is not published yet stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledPostStatus {
+    /// Kept private until the author schedules or publishes it
+    Draft,
+    /// Published by a worker once `publish_at` passes
+    Scheduled,
+    /// Publishing failed, for example because the replied-to post was deleted
+    Failed,
+}
+
+impl ScheduledPostStatus {
+    pub const ALL: [ScheduledPostStatus; 3] = [
+        ScheduledPostStatus::Draft,
+        ScheduledPostStatus::Scheduled,
+        ScheduledPostStatus::Failed,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduledPostStatus::Draft => "draft",
+            ScheduledPostStatus::Scheduled => "scheduled",
+            ScheduledPostStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == value)
+    }
+}
+
+/// A draft or scheduled post, visible to its author only
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledPost {
+    pub id: i32,
+    pub user_id: i32,
+    pub content: String,
+    pub image_url: Option<String>,
+    pub media: Vec<PostMedia>,
+    pub reply_to_post_id: Option<i32>,
+    pub quote_of_post_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll: Option<NewPoll>,
+    pub status: ScheduledPostStatus,
+    pub publish_at: Option<NaiveDateTime>,
+    /// Why the last publishing attempt failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Body for creating or replacing a draft. With `publish_at` (UTC, in the
+/// future) the post is scheduled; without it, it stays a draft.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveScheduledPost {
+    #[serde(flatten)]
+    pub post: CreatePost,
+    pub publish_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize, Deserialize)]
+pub struct Message {
+    pub id: i32,
+    pub conversation_id: i32,
+    /// `None` once the sender deleted their account
+    pub sender_id: Option<i32>,
+    pub content: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// A conversation member with their read receipt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationMember {
+    #[serde(flatten)]
+    pub user: UserProfile,
+    /// Newest message the member has read
+    pub last_read_message_id: Option<i32>,
+}
+
+/// A direct-message conversation as seen by one of its members
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversation {
+    pub id: i32,
+    pub is_group: bool,
+    /// Everyone in the conversation, including the viewer
+    pub members: Vec<ConversationMember>,
+    pub last_message: Option<Message>,
+    /// Messages from other members the viewer has not read yet
+    pub unread_count: i64,
+    pub created_at: NaiveDateTime,
+}
+
+/// Body of `POST /conversations`. A single member starts (or reopens) the
+/// one-to-one conversation with them; more start a new group.
+#[derive(Debug, Deserialize)]
+pub struct CreateConversation {
+    pub member_ids: Vec<i32>,
+    /// Optional first message
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateMessage {
+    pub content: String,
+}
+
+/// Body of `POST /conversations/{id}/read`; without `message_id` everything
+/// up to the newest message is marked read
+#[derive(Debug, Default, Deserialize)]
+pub struct MarkConversationRead {
+    pub message_id: Option<i32>,
+}
+
+/// An account suggested to follow, with the signals it was ranked by
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowSuggestion {
+    #[serde(flatten)]
+    pub user: UserProfile,
+    /// Accounts the viewer follows that follow this one
+    pub mutual_follow_count: i64,
+    /// Hashtags both used recently
+    pub shared_hashtag_count: i64,
+    /// Likes and comments exchanged between the two
+    pub interaction_count: i64,
+}
+
+/// How the viewer and another user are connected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Relationship {
+    pub user_id: i32,
+    pub following: bool,
+    pub followed_by: bool,
+    /// The viewer blocked the user
+    pub blocking: bool,
+    /// The user blocked the viewer
+    pub blocked_by: bool,
+    pub muting: bool,
+    /// The viewer's follow request to this private account awaits approval
+    pub follow_requested: bool,
+    /// The user's follow request to the viewer awaits approval
+    pub follow_requested_by: bool,
+    /// A few accounts the viewer follows that follow the user. Left empty
+    /// for private accounts the viewer cannot see and across blocks.
+    pub mutual_followers: Vec<UserProfile>,
+    pub mutual_follower_count: i64,
+    /// Display text such as "Followed by alice, bob and 12 others"
+    pub mutual_followers_summary: Option<String>,
 }
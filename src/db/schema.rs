@@ -10,13 +10,22 @@ pub async fn initialize_schema(pool: &PgPool) -> Result<()> {
 pub async fn run_migrations(pool: &PgPool) -> Result<()> {
     // Create functions first
     create_update_function(pool).await?;
+    create_updated_timestamp_function(pool).await?;
     create_follow_count_function(pool).await?;
     create_like_count_function(pool).await?;
     create_comment_count_function(pool).await?;
+    create_comment_like_count_function(pool).await?;
+    create_repost_count_function(pool).await?;
+    create_timeline_event_function(pool).await?;
+    create_stream_event_functions(pool).await?;
+    create_todo_sync_functions(pool).await?;
+    create_media_deletion_function(pool).await?;
+    create_poll_vote_count_function(pool).await?;
 
     // Create tables
     create_users_table(pool).await?;
     create_todos_table(pool).await?;
+    extend_todos_for_trash(pool).await?;
 
     // Extend users table for social media
     extend_users_for_social_media(pool).await?;
@@ -24,24 +33,122 @@ pub async fn run_migrations(pool: &PgPool) -> Result<()> {
     // Create social media tables
     create_posts_table(pool).await?;
     create_follows_table(pool).await?;
+    create_follow_requests_table(pool).await?;
+    create_blocks_table(pool).await?;
+    create_mutes_table(pool).await?;
     create_likes_table(pool).await?;
     create_comments_table(pool).await?;
+    create_comment_likes_table(pool).await?;
+    extend_posts_for_threads(pool).await?;
+    extend_posts_for_quotes(pool).await?;
+    create_reposts_table(pool).await?;
+
+    // Notifications
+    create_notifications_table(pool).await?;
+    create_notification_actors_table(pool).await?;
+    create_notification_preferences_table(pool).await?;
+
+    // Mentions and hashtags
+    create_post_mentions_table(pool).await?;
+    create_hashtags_table(pool).await?;
+    create_post_hashtags_table(pool).await?;
+
+    // Realtime event log
+    create_stream_events_table(pool).await?;
+
+    // Uploaded media
+    create_media_table(pool).await?;
+    extend_for_media(pool).await?;
+    create_post_media_table(pool).await?;
+    create_media_deletions_table(pool).await?;
+
+    // Moderation
+    extend_for_moderation(pool).await?;
+    create_reports_table(pool).await?;
+
+    // Post edit history
+    extend_posts_for_revisions(pool).await?;
+    create_post_revisions_table(pool).await?;
+
+    // Bookmarks
+    create_bookmark_collections_table(pool).await?;
+    create_bookmarks_table(pool).await?;
+
+    // Drafts and scheduled posts
+    create_scheduled_posts_table(pool).await?;
+    create_scheduled_post_media_table(pool).await?;
+
+    // Polls
+    create_polls_table(pool).await?;
+    create_poll_options_table(pool).await?;
+    create_poll_votes_table(pool).await?;
+    extend_scheduled_posts_for_polls(pool).await?;
+
+    // Direct messages
+    create_conversations_table(pool).await?;
+    create_conversation_members_table(pool).await?;
+    create_messages_table(pool).await?;
+
+    // Who-to-follow suggestions
+    create_dismissed_suggestions_table(pool).await?;
+
+    // Materialized home timelines
+    create_home_timeline_table(pool).await?;
+    create_timeline_events_table(pool).await?;
+
+    // Version counters for optimistic concurrency control
+    add_version_columns(pool).await?;
+
+    // Todo audit trail
+    create_todo_revisions_table(pool).await?;
+    backfill_todo_revisions(pool).await?;
+
+    // Delta sync
+    extend_todos_for_sync(pool).await?;
+    create_todo_tombstones_table(pool).await?;
 
     // Create indexes
     create_users_indexes(pool).await?;
     create_todos_indexes(pool).await?;
+    create_todo_revisions_indexes(pool).await?;
+    create_todo_sync_indexes(pool).await?;
     create_posts_indexes(pool).await?;
     create_follows_indexes(pool).await?;
+    create_follow_requests_indexes(pool).await?;
+    create_blocks_and_mutes_indexes(pool).await?;
     create_likes_indexes(pool).await?;
+    create_reposts_indexes(pool).await?;
+    create_home_timeline_indexes(pool).await?;
     create_comments_indexes(pool).await?;
+    create_comment_likes_indexes(pool).await?;
+    create_notifications_indexes(pool).await?;
+    create_stream_events_indexes(pool).await?;
+    create_mentions_and_hashtags_indexes(pool).await?;
+    create_media_indexes(pool).await?;
+    create_reports_indexes(pool).await?;
+    create_bookmarks_indexes(pool).await?;
+    create_scheduled_posts_indexes(pool).await?;
+    create_polls_indexes(pool).await?;
+    create_direct_messages_indexes(pool).await?;
 
     // Create triggers
     create_users_trigger(pool).await?;
     create_todos_trigger(pool).await?;
+    create_todo_sync_triggers(pool).await?;
     create_posts_trigger(pool).await?;
     create_follows_trigger(pool).await?;
     create_likes_trigger(pool).await?;
+    create_reposts_trigger(pool).await?;
+    create_timeline_triggers(pool).await?;
     create_comments_trigger(pool).await?;
+    create_comment_likes_trigger(pool).await?;
+    create_stream_event_triggers(pool).await?;
+    create_media_deletion_trigger(pool).await?;
+    create_poll_votes_trigger(pool).await?;
+
+    backfill_home_timeline(pool).await?;
+    recount_post_comments(pool).await?;
+    migrate_post_media_ids(pool).await?;
 
     println!("All migrations applied successfully!");
     Ok(())
@@ -172,7 +279,7 @@ async fn create_users_trigger(pool: &PgPool) -> Result<()> {
             BEFORE UPDATE
             ON users
             FOR EACH ROW
-        EXECUTE PROCEDURE update_updated_column()
+        EXECUTE PROCEDURE update_updated_timestamp()
     "#,
     )
     .execute(pool)
@@ -220,6 +327,21 @@ async fn create_todos_indexes(pool: &PgPool) -> Result<()> {
     .execute(pool)
     .await?;
 
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_todos_user_id_active ON todos(user_id, created DESC)
+            WHERE is_deleted = FALSE AND is_archived = FALSE
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_todos_deleted_at ON todos(deleted_at) WHERE is_deleted = TRUE",
+    )
+    .execute(pool)
+    .await?;
+
     Ok(())
 }
 
@@ -234,7 +356,173 @@ async fn create_todos_trigger(pool: &PgPool) -> Result<()> {
             BEFORE UPDATE
             ON todos
             FOR EACH ROW
-        EXECUTE PROCEDURE update_updated_column()
+        EXECUTE PROCEDURE update_updated_timestamp()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn extend_todos_for_trash(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "ALTER TABLE todos ADD COLUMN IF NOT EXISTS is_archived BOOLEAN NOT NULL DEFAULT FALSE",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("ALTER TABLE todos ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        "ALTER TABLE todos ADD COLUMN IF NOT EXISTS is_deleted BOOLEAN NOT NULL DEFAULT FALSE",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("ALTER TABLE todos ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+async fn create_todo_revisions_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS todo_revisions (
+            id SERIAL PRIMARY KEY,
+            todo_id INTEGER NOT NULL REFERENCES todos(id) ON DELETE CASCADE,
+            actor_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+            action VARCHAR(20) NOT NULL,
+            changes JSONB NOT NULL DEFAULT '{}',
+            reverted_to_revision_id INTEGER REFERENCES todo_revisions(id) ON DELETE SET NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            CONSTRAINT valid_todo_revision_action CHECK (
+                action IN ('create', 'update', 'delete', 'restore', 'archive', 'unarchive', 'revert')
+            )
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Gives todos that predate the audit trail a 'create' revision with their
+/// current values, so `todo_history::replay` always has a starting point
+async fn backfill_todo_revisions(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO todo_revisions (todo_id, actor_id, action, changes, created_at)
+        SELECT t.id, t.user_id, 'create',
+               jsonb_build_object(
+                   'title', jsonb_build_object('from', NULL, 'to', t.title),
+                   'description', jsonb_build_object('from', NULL, 'to', t.description),
+                   'is_archived', jsonb_build_object('from', NULL, 'to', t.is_archived),
+                   'is_deleted', jsonb_build_object('from', NULL, 'to', t.is_deleted)
+               ),
+               t.created
+        FROM todos t
+        WHERE NOT EXISTS (SELECT 1 FROM todo_revisions r WHERE r.todo_id = t.id)
+        ORDER BY t.id
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_todo_revisions_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_todo_revisions_todo_id ON todo_revisions(todo_id, id DESC)",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_todo_revisions_actor_id ON todo_revisions(actor_id)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn extend_todos_for_sync(pool: &PgPool) -> Result<()> {
+    sqlx::query("ALTER TABLE todos ADD COLUMN IF NOT EXISTS created_xid BIGINT NOT NULL DEFAULT 0")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("ALTER TABLE todos ADD COLUMN IF NOT EXISTS sync_xid BIGINT NOT NULL DEFAULT 0")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+async fn create_todo_tombstones_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS todo_tombstones (
+            todo_id INTEGER PRIMARY KEY,
+            user_id INTEGER NOT NULL,
+            sync_xid BIGINT NOT NULL,
+            deleted_at TIMESTAMP NOT NULL DEFAULT NOW()
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_todo_sync_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_todos_user_id_sync_xid ON todos(user_id, sync_xid)",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_todo_tombstones_user_id_sync_xid ON todo_tombstones(user_id, sync_xid)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn create_todo_sync_triggers(pool: &PgPool) -> Result<()> {
+    sqlx::query("DROP TRIGGER IF EXISTS stamp_todos_sync_xid ON todos")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER stamp_todos_sync_xid
+            BEFORE INSERT OR UPDATE
+            ON todos
+            FOR EACH ROW
+        EXECUTE PROCEDURE stamp_todo_sync_xid()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("DROP TRIGGER IF EXISTS record_todos_tombstone ON todos")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER record_todos_tombstone
+            AFTER DELETE
+            ON todos
+            FOR EACH ROW
+        EXECUTE PROCEDURE record_todo_tombstone()
     "#,
     )
     .execute(pool)
@@ -337,6 +625,37 @@ async fn create_posts_table(pool: &PgPool) -> Result<()> {
     Ok(())
 }
 
+async fn extend_posts_for_threads(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "ALTER TABLE posts ADD COLUMN IF NOT EXISTS root_post_id INTEGER REFERENCES posts(id) ON DELETE CASCADE",
+    )
+    .execute(pool)
+    .await?;
+
+    // Backfill replies created before threads were tracked
+    sqlx::query(
+        r#"
+        WITH RECURSIVE chain AS (
+            SELECT id, id AS root_id
+            FROM posts
+            WHERE reply_to_post_id IS NULL
+            UNION ALL
+            SELECT p.id, chain.root_id
+            FROM posts p
+            INNER JOIN chain ON p.reply_to_post_id = chain.id
+        )
+        UPDATE posts
+        SET root_post_id = chain.root_id
+        FROM chain
+        WHERE posts.id = chain.id AND posts.reply_to_post_id IS NOT NULL AND posts.root_post_id IS NULL
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 async fn create_posts_indexes(pool: &PgPool) -> Result<()> {
     sqlx::query("CREATE INDEX IF NOT EXISTS idx_posts_user_id ON posts(user_id)")
         .execute(pool)
@@ -354,6 +673,14 @@ async fn create_posts_indexes(pool: &PgPool) -> Result<()> {
         .execute(pool)
         .await?;
 
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_posts_reply_to_created_at ON posts(reply_to_post_id, created_at)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_posts_root_post_id ON posts(root_post_id)")
+        .execute(pool)
+        .await?;
+
     sqlx::query("CREATE INDEX IF NOT EXISTS idx_posts_like_count ON posts(like_count DESC)")
         .execute(pool)
         .await?;
@@ -419,34 +746,44 @@ async fn create_follows_indexes(pool: &PgPool) -> Result<()> {
     Ok(())
 }
 
-async fn create_follows_trigger(pool: &PgPool) -> Result<()> {
-    sqlx::query("DROP TRIGGER IF EXISTS trigger_follow_counts ON follows")
-        .execute(pool)
-        .await?;
-
+async fn create_follow_requests_table(pool: &PgPool) -> Result<()> {
     sqlx::query(
         r#"
-        CREATE TRIGGER trigger_follow_counts
-            AFTER INSERT OR DELETE ON follows
-            FOR EACH ROW
-        EXECUTE PROCEDURE update_follow_counts()
+        CREATE TABLE IF NOT EXISTS follow_requests (
+            id SERIAL PRIMARY KEY,
+            requester_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            target_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            CONSTRAINT no_self_follow_request CHECK (requester_id != target_id),
+            CONSTRAINT unique_follow_request UNIQUE (requester_id, target_id)
+        )
     "#,
     )
     .execute(pool)
     .await?;
+    Ok(())
+}
+
+async fn create_follow_requests_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_follow_requests_target_id_created_at ON follow_requests(target_id, created_at DESC)",
+    )
+    .execute(pool)
+    .await?;
 
     Ok(())
 }
 
-async fn create_likes_table(pool: &PgPool) -> Result<()> {
+async fn create_blocks_table(pool: &PgPool) -> Result<()> {
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS likes (
+        CREATE TABLE IF NOT EXISTS blocks (
             id SERIAL PRIMARY KEY,
-            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-            post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+            blocker_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            blocked_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
             created_at TIMESTAMP NOT NULL DEFAULT NOW(),
-            CONSTRAINT unique_like UNIQUE (user_id, post_id)
+            CONSTRAINT no_self_block CHECK (blocker_id != blocked_id),
+            CONSTRAINT unique_block UNIQUE (blocker_id, blocked_id)
         )
     "#,
     )
@@ -455,34 +792,31 @@ async fn create_likes_table(pool: &PgPool) -> Result<()> {
     Ok(())
 }
 
-async fn create_likes_indexes(pool: &PgPool) -> Result<()> {
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_likes_user_id ON likes(user_id)")
-        .execute(pool)
-        .await?;
-
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_likes_post_id ON likes(post_id)")
-        .execute(pool)
-        .await?;
-
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_likes_created_at ON likes(created_at DESC)")
-        .execute(pool)
-        .await?;
-
+async fn create_mutes_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS mutes (
+            id SERIAL PRIMARY KEY,
+            muter_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            muted_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            CONSTRAINT no_self_mute CHECK (muter_id != muted_id),
+            CONSTRAINT unique_mute UNIQUE (muter_id, muted_id)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
     Ok(())
 }
 
-async fn create_likes_trigger(pool: &PgPool) -> Result<()> {
-    sqlx::query("DROP TRIGGER IF EXISTS trigger_like_counts ON likes")
+async fn create_blocks_and_mutes_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_blocks_blocked_id ON blocks(blocked_id)")
         .execute(pool)
         .await?;
 
     sqlx::query(
-        r#"
-        CREATE TRIGGER trigger_like_counts
-            AFTER INSERT OR DELETE ON likes
-            FOR EACH ROW
-        EXECUTE PROCEDURE update_like_counts()
-    "#,
+        "CREATE INDEX IF NOT EXISTS idx_mutes_muter_id_created_at ON mutes(muter_id, created_at DESC)",
     )
     .execute(pool)
     .await?;
@@ -490,20 +824,24 @@ async fn create_likes_trigger(pool: &PgPool) -> Result<()> {
     Ok(())
 }
 
-async fn create_comments_table(pool: &PgPool) -> Result<()> {
+async fn create_notifications_table(pool: &PgPool) -> Result<()> {
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS comments (
+        CREATE TABLE IF NOT EXISTS notifications (
             id SERIAL PRIMARY KEY,
             user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-            post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
-            content TEXT NOT NULL CHECK (char_length(content) <= 280),
-            like_count INTEGER DEFAULT 0,
+            kind VARCHAR(32) NOT NULL,
+            group_key VARCHAR(64) NOT NULL,
+            post_id INTEGER REFERENCES posts(id) ON DELETE CASCADE,
+            comment_id INTEGER REFERENCES comments(id) ON DELETE CASCADE,
+            actor_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            actor_count INTEGER NOT NULL DEFAULT 1,
+            is_read BOOLEAN NOT NULL DEFAULT FALSE,
             created_at TIMESTAMP NOT NULL DEFAULT NOW(),
             updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
-            reply_to_comment_id INTEGER REFERENCES comments(id) ON DELETE CASCADE,
-            is_deleted BOOLEAN DEFAULT FALSE,
-            deleted_at TIMESTAMP
+            CONSTRAINT valid_notification_kind CHECK (
+                kind IN ('follow', 'follow_request', 'like', 'comment', 'reply', 'repost', 'mention')
+            )
         )
     "#,
     )
@@ -512,60 +850,1279 @@ async fn create_comments_table(pool: &PgPool) -> Result<()> {
     Ok(())
 }
 
-async fn create_comments_indexes(pool: &PgPool) -> Result<()> {
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_comments_user_id ON comments(user_id)")
-        .execute(pool)
-        .await?;
-
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_comments_post_id ON comments(post_id)")
-        .execute(pool)
-        .await?;
-
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_comments_created_at ON comments(created_at DESC)")
-        .execute(pool)
-        .await?;
-
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_comments_post_id_created_at ON comments(post_id, created_at DESC)")
-        .execute(pool)
-        .await?;
-
+async fn create_notification_actors_table(pool: &PgPool) -> Result<()> {
     sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_comments_reply_to ON comments(reply_to_comment_id)",
+        r#"
+        CREATE TABLE IF NOT EXISTS notification_actors (
+            notification_id INTEGER NOT NULL REFERENCES notifications(id) ON DELETE CASCADE,
+            actor_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            PRIMARY KEY (notification_id, actor_id)
+        )
+    "#,
     )
     .execute(pool)
     .await?;
-
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_comments_is_deleted ON comments(is_deleted)")
-        .execute(pool)
-        .await?;
-
     Ok(())
 }
 
-async fn create_comments_trigger(pool: &PgPool) -> Result<()> {
-    sqlx::query("DROP TRIGGER IF EXISTS trigger_comment_counts ON comments")
-        .execute(pool)
-        .await?;
-
+async fn create_notification_preferences_table(pool: &PgPool) -> Result<()> {
     sqlx::query(
         r#"
-        CREATE TRIGGER trigger_comment_counts
-            AFTER INSERT OR DELETE ON comments
-            FOR EACH ROW
-        EXECUTE PROCEDURE update_comment_counts()
+        CREATE TABLE IF NOT EXISTS notification_preferences (
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            kind VARCHAR(32) NOT NULL,
+            enabled BOOLEAN NOT NULL,
+            PRIMARY KEY (user_id, kind)
+        )
     "#,
     )
     .execute(pool)
     .await?;
+    Ok(())
+}
 
-    sqlx::query("DROP TRIGGER IF EXISTS update_comments_updated_at ON comments")
-        .execute(pool)
-        .await?;
-
+async fn create_notifications_indexes(pool: &PgPool) -> Result<()> {
+    // At most one open group per recipient and target
     sqlx::query(
         r#"
-        CREATE TRIGGER update_comments_updated_at
-            BEFORE UPDATE
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_notifications_unread_group
+            ON notifications(user_id, group_key) WHERE is_read = FALSE
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_notifications_user_id_updated_at
+            ON notifications(user_id, updated_at DESC, id DESC)
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_notification_actors_recent
+            ON notification_actors(notification_id, created_at DESC)
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn create_post_mentions_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS post_mentions (
+            post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            PRIMARY KEY (post_id, user_id)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Tags are stored lowercased
+async fn create_hashtags_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS hashtags (
+            id SERIAL PRIMARY KEY,
+            tag VARCHAR(100) NOT NULL UNIQUE,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW()
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// created_at is copied from the post so trending windows follow post time
+async fn create_post_hashtags_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS post_hashtags (
+            post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+            hashtag_id INTEGER NOT NULL REFERENCES hashtags(id) ON DELETE CASCADE,
+            created_at TIMESTAMP NOT NULL,
+            PRIMARY KEY (post_id, hashtag_id)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_mentions_and_hashtags_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_post_mentions_user_id ON post_mentions(user_id, created_at DESC)",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_post_hashtags_hashtag_id_created_at
+            ON post_hashtags(hashtag_id, created_at DESC)
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_post_hashtags_created_at ON post_hashtags(created_at)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Uploaded images; the files themselves live in the configured blob store
+async fn create_media_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS media (
+            id SERIAL PRIMARY KEY,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            content_type VARCHAR(50) NOT NULL,
+            size_bytes BIGINT NOT NULL,
+            width INTEGER NOT NULL,
+            height INTEGER NOT NULL,
+            storage_key VARCHAR(255) NOT NULL UNIQUE,
+            thumbnail_key VARCHAR(255) NOT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW()
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn extend_for_media(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "ALTER TABLE users ADD COLUMN IF NOT EXISTS avatar_media_id INTEGER REFERENCES media(id) ON DELETE SET NULL",
+    )
+    .execute(pool)
+    .await?;
+
+    // Placeholder shown while an image loads
+    sqlx::query("ALTER TABLE media ADD COLUMN IF NOT EXISTS blurhash VARCHAR(64)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// Up to four ordered attachments per post
+async fn create_post_media_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS post_media (
+            post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+            media_id INTEGER NOT NULL REFERENCES media(id) ON DELETE CASCADE,
+            position SMALLINT NOT NULL CHECK (position BETWEEN 0 AND 3),
+            alt_text VARCHAR(1500),
+            PRIMARY KEY (post_id, position),
+            CONSTRAINT unique_post_media UNIQUE (post_id, media_id)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Files of deleted media rows, removed from the blob store by a worker
+async fn create_media_deletions_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS media_deletions (
+            storage_key VARCHAR(255) PRIMARY KEY,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW()
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_media_deletion_function(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE OR REPLACE FUNCTION enqueue_media_deletion()
+        RETURNS TRIGGER AS $$
+        BEGIN
+            INSERT INTO media_deletions (storage_key)
+            VALUES (OLD.storage_key), (OLD.thumbnail_key)
+            ON CONFLICT (storage_key) DO NOTHING;
+            RETURN OLD;
+        END;
+        $$ language 'plpgsql'
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_media_deletion_trigger(pool: &PgPool) -> Result<()> {
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_media_deletion ON media")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_media_deletion
+            AFTER DELETE ON media
+            FOR EACH ROW
+        EXECUTE PROCEDURE enqueue_media_deletion()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Single attachments recorded in the former posts.media_id move to post_media
+async fn migrate_post_media_ids(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        DO $$
+        BEGIN
+            IF EXISTS (
+                SELECT 1 FROM information_schema.columns
+                WHERE table_name = 'posts' AND column_name = 'media_id'
+            ) THEN
+                INSERT INTO post_media (post_id, media_id, position)
+                SELECT id, media_id, 0 FROM posts WHERE media_id IS NOT NULL
+                ON CONFLICT DO NOTHING;
+                ALTER TABLE posts DROP COLUMN media_id;
+            END IF;
+        END $$
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_media_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_media_user_id ON media(user_id, created_at DESC)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_post_media_media_id ON post_media(media_id)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+async fn extend_for_moderation(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "ALTER TABLE users ADD COLUMN IF NOT EXISTS is_admin BOOLEAN NOT NULL DEFAULT FALSE",
+    )
+    .execute(pool)
+    .await?;
+
+    // Set by the content filter: 'held' waits for review, 'hidden' is
+    // shadow-hidden. Either way only the author sees the content.
+    for table in ["posts", "comments"] {
+        sqlx::query(&format!(
+            "ALTER TABLE {} ADD COLUMN IF NOT EXISTS moderation_state VARCHAR(16) CHECK (moderation_state IN ('held', 'hidden'))",
+            table
+        ))
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+// Reports filed by users, and by the content filter (reporter_id NULL).
+// target_id points at posts, comments or users depending on target_type.
+async fn create_reports_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS reports (
+            id SERIAL PRIMARY KEY,
+            reporter_id INTEGER REFERENCES users(id) ON DELETE CASCADE,
+            target_type VARCHAR(16) NOT NULL CHECK (target_type IN ('post', 'comment', 'user')),
+            target_id INTEGER NOT NULL,
+            reason VARCHAR(32) NOT NULL CHECK (reason IN (
+                'spam', 'harassment', 'hate', 'violence', 'sexual', 'misinformation', 'other',
+                'content_filter'
+            )),
+            details TEXT,
+            status VARCHAR(16) NOT NULL DEFAULT 'open'
+                CHECK (status IN ('open', 'resolved', 'dismissed')),
+            resolved_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+            resolution_note TEXT,
+            resolved_at TIMESTAMP,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW()
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn extend_posts_for_revisions(pool: &PgPool) -> Result<()> {
+    sqlx::query("ALTER TABLE posts ADD COLUMN IF NOT EXISTS edited_at TIMESTAMP")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        "ALTER TABLE posts ADD COLUMN IF NOT EXISTS revision_count INTEGER NOT NULL DEFAULT 0",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Every version of a post that an edit replaced
+async fn create_post_revisions_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS post_revisions (
+            id SERIAL PRIMARY KEY,
+            post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+            version INTEGER NOT NULL,
+            content TEXT NOT NULL,
+            image_url TEXT,
+            -- When this version went live and when an edit replaced it
+            published_at TIMESTAMP NOT NULL,
+            replaced_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            UNIQUE (post_id, version)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Named groups a user files bookmarks into
+async fn create_bookmark_collections_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS bookmark_collections (
+            id SERIAL PRIMARY KEY,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            name VARCHAR(100) NOT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW()
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Private saved posts. Deleting a collection keeps its bookmarks unfiled.
+async fn create_bookmarks_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS bookmarks (
+            id SERIAL PRIMARY KEY,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+            collection_id INTEGER REFERENCES bookmark_collections(id) ON DELETE SET NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            UNIQUE (user_id, post_id)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Posts that are not published yet: drafts have no publish time, scheduled
+// posts are published by a worker once publish_at passes, and failed ones
+// could not be published and wait for the author to edit them. Reply and
+// quote targets are checked again when the post is published.
+async fn create_scheduled_posts_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS scheduled_posts (
+            id SERIAL PRIMARY KEY,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            content TEXT NOT NULL,
+            image_url TEXT,
+            reply_to_post_id INTEGER,
+            quote_of_post_id INTEGER,
+            status VARCHAR(16) NOT NULL CHECK (status IN ('draft', 'scheduled', 'failed')),
+            publish_at TIMESTAMP,
+            error TEXT,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            CHECK (status <> 'scheduled' OR publish_at IS NOT NULL)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_scheduled_post_media_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS scheduled_post_media (
+            scheduled_post_id INTEGER NOT NULL REFERENCES scheduled_posts(id) ON DELETE CASCADE,
+            media_id INTEGER NOT NULL REFERENCES media(id) ON DELETE CASCADE,
+            position SMALLINT NOT NULL CHECK (position BETWEEN 0 AND 3),
+            alt_text VARCHAR(1500),
+            PRIMARY KEY (scheduled_post_id, position),
+            UNIQUE (scheduled_post_id, media_id)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// A poll attached to a post; vote_count is the number of voters
+async fn create_polls_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS polls (
+            id SERIAL PRIMARY KEY,
+            post_id INTEGER NOT NULL UNIQUE REFERENCES posts(id) ON DELETE CASCADE,
+            closes_at TIMESTAMP NOT NULL,
+            vote_count INTEGER NOT NULL DEFAULT 0,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW()
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_poll_options_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS poll_options (
+            id SERIAL PRIMARY KEY,
+            poll_id INTEGER NOT NULL REFERENCES polls(id) ON DELETE CASCADE,
+            position SMALLINT NOT NULL CHECK (position BETWEEN 0 AND 3),
+            label VARCHAR(100) NOT NULL,
+            vote_count INTEGER NOT NULL DEFAULT 0,
+            UNIQUE (poll_id, position)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// One vote per user and poll
+async fn create_poll_votes_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS poll_votes (
+            poll_id INTEGER NOT NULL REFERENCES polls(id) ON DELETE CASCADE,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            option_id INTEGER NOT NULL REFERENCES poll_options(id) ON DELETE CASCADE,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            PRIMARY KEY (poll_id, user_id)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Drafts keep their poll until they are published
+async fn extend_scheduled_posts_for_polls(pool: &PgPool) -> Result<()> {
+    sqlx::query("ALTER TABLE scheduled_posts ADD COLUMN IF NOT EXISTS poll_options TEXT[]")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        "ALTER TABLE scheduled_posts ADD COLUMN IF NOT EXISTS poll_duration_minutes INTEGER",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Direct-message threads. One-to-one conversations carry a direct_key of
+// "<lower id>:<higher id>" so each pair of users shares a single thread;
+// group conversations leave it NULL.
+async fn create_conversations_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS conversations (
+            id SERIAL PRIMARY KEY,
+            created_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+            is_group BOOLEAN NOT NULL DEFAULT FALSE,
+            direct_key VARCHAR(32) UNIQUE,
+            last_message_at TIMESTAMP,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW()
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// last_read_message_id is the member's read receipt
+async fn create_conversation_members_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS conversation_members (
+            conversation_id INTEGER NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            last_read_message_id INTEGER,
+            last_read_at TIMESTAMP,
+            joined_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            PRIMARY KEY (conversation_id, user_id)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Messages outlive their sender's account
+async fn create_messages_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS messages (
+            id SERIAL PRIMARY KEY,
+            conversation_id INTEGER NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+            sender_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+            content TEXT NOT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW()
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Accounts a user dismissed from their who-to-follow suggestions
+async fn create_dismissed_suggestions_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS dismissed_suggestions (
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            dismissed_user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            PRIMARY KEY (user_id, dismissed_user_id)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_reports_indexes(pool: &PgPool) -> Result<()> {
+    // One open report per reporter and target
+    sqlx::query(
+        r#"
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_reports_open_per_reporter
+            ON reports(reporter_id, target_type, target_id) WHERE status = 'open'
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_reports_status_created_at ON reports(status, created_at, id)",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_reports_target ON reports(target_type, target_id)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+async fn create_bookmarks_indexes(pool: &PgPool) -> Result<()> {
+    // Collection names are unique per user regardless of case
+    sqlx::query(
+        r#"
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_bookmark_collections_user_name
+            ON bookmark_collections(user_id, LOWER(name))
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_bookmarks_user_created_at
+            ON bookmarks(user_id, created_at DESC, id DESC)
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_bookmarks_collection_created_at
+            ON bookmarks(collection_id, created_at DESC, id DESC)
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_scheduled_posts_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_scheduled_posts_user_id ON scheduled_posts(user_id)",
+    )
+    .execute(pool)
+    .await?;
+
+    // Due posts, polled by the publishing worker
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_scheduled_posts_due
+            ON scheduled_posts(publish_at) WHERE status = 'scheduled'
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_scheduled_post_media_media_id
+            ON scheduled_post_media(media_id)
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_polls_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_poll_options_poll_id ON poll_options(poll_id, position)",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_poll_votes_option_id ON poll_votes(option_id)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_poll_votes_user_id ON poll_votes(user_id)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+async fn create_direct_messages_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_conversation_members_user_id ON conversation_members(user_id)",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_messages_conversation_id_id ON messages(conversation_id, id DESC)",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Per-user log of realtime events, kept for a day so clients can resume a
+// stream with Last-Event-ID. There is no foreign key on user_id: events are
+// written while a user's rows are being cascade-deleted.
+async fn create_stream_events_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS stream_events (
+            id BIGSERIAL PRIMARY KEY,
+            user_id INTEGER NOT NULL,
+            kind VARCHAR(32) NOT NULL,
+            payload JSONB NOT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW()
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_stream_events_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_stream_events_user_id_id ON stream_events(user_id, id)",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_stream_events_created_at ON stream_events(created_at)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn create_stream_event_triggers(pool: &PgPool) -> Result<()> {
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_notifications_stream ON notifications")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_notifications_stream
+            AFTER INSERT ON notifications
+            FOR EACH ROW
+        EXECUTE PROCEDURE enqueue_stream_event()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // A grouped notification only changes for the user when another actor joins
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_notifications_grouped_stream ON notifications")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_notifications_grouped_stream
+            AFTER UPDATE ON notifications
+            FOR EACH ROW
+            WHEN (OLD.actor_count IS DISTINCT FROM NEW.actor_count OR OLD.actor_id IS DISTINCT FROM NEW.actor_id)
+        EXECUTE PROCEDURE enqueue_stream_event()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_home_timeline_stream ON home_timeline")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_home_timeline_stream
+            AFTER INSERT OR UPDATE OF activity_at ON home_timeline
+            FOR EACH ROW
+        EXECUTE PROCEDURE enqueue_stream_event()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_todos_stream ON todos")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_todos_stream
+            AFTER INSERT OR UPDATE OR DELETE ON todos
+            FOR EACH ROW
+        EXECUTE PROCEDURE enqueue_stream_event()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_messages_stream ON messages")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_messages_stream
+            AFTER INSERT ON messages
+            FOR EACH ROW
+        EXECUTE PROCEDURE enqueue_stream_event()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_stream_events_notify ON stream_events")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_stream_events_notify
+            AFTER INSERT ON stream_events
+            FOR EACH ROW
+        EXECUTE PROCEDURE notify_stream_event()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn create_follows_trigger(pool: &PgPool) -> Result<()> {
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_follow_counts ON follows")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_follow_counts
+            AFTER INSERT OR DELETE ON follows
+            FOR EACH ROW
+        EXECUTE PROCEDURE update_follow_counts()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn create_likes_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS likes (
+            id SERIAL PRIMARY KEY,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            CONSTRAINT unique_like UNIQUE (user_id, post_id)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_likes_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_likes_user_id ON likes(user_id)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_likes_post_id ON likes(post_id)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_likes_created_at ON likes(created_at DESC)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+async fn create_likes_trigger(pool: &PgPool) -> Result<()> {
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_like_counts ON likes")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_like_counts
+            AFTER INSERT OR DELETE ON likes
+            FOR EACH ROW
+        EXECUTE PROCEDURE update_like_counts()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn create_poll_votes_trigger(pool: &PgPool) -> Result<()> {
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_poll_vote_counts ON poll_votes")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_poll_vote_counts
+            AFTER INSERT OR DELETE ON poll_votes
+            FOR EACH ROW
+        EXECUTE PROCEDURE update_poll_vote_counts()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn extend_posts_for_quotes(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "ALTER TABLE posts ADD COLUMN IF NOT EXISTS quote_of_post_id INTEGER REFERENCES posts(id) ON DELETE SET NULL",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_reposts_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS reposts (
+            id SERIAL PRIMARY KEY,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            CONSTRAINT unique_repost UNIQUE (user_id, post_id)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_reposts_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_reposts_user_id_created_at ON reposts(user_id, created_at DESC)",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_reposts_post_id ON reposts(post_id)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_posts_quote_of_post_id ON posts(quote_of_post_id) WHERE quote_of_post_id IS NOT NULL",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn create_reposts_trigger(pool: &PgPool) -> Result<()> {
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_repost_counts ON reposts")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_repost_counts
+            AFTER INSERT OR DELETE ON reposts
+            FOR EACH ROW
+        EXECUTE PROCEDURE update_repost_counts()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn create_home_timeline_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS home_timeline (
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+            reposter_id INTEGER REFERENCES users(id) ON DELETE CASCADE,
+            activity_at TIMESTAMP NOT NULL,
+            PRIMARY KEY (user_id, post_id)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_timeline_events_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS timeline_events (
+            id BIGSERIAL PRIMARY KEY,
+            kind VARCHAR(20) NOT NULL,
+            actor_id INTEGER NOT NULL,
+            post_id INTEGER,
+            target_user_id INTEGER,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            CONSTRAINT valid_timeline_event_kind CHECK (
+                kind IN ('post_created', 'post_deleted', 'repost_created', 'repost_deleted',
+                         'follow_created', 'follow_deleted')
+            )
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_home_timeline_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_home_timeline_user_activity ON home_timeline(user_id, activity_at DESC, post_id DESC)",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_home_timeline_post_id ON home_timeline(post_id)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+async fn create_timeline_triggers(pool: &PgPool) -> Result<()> {
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_posts_timeline ON posts")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_posts_timeline
+            AFTER INSERT OR UPDATE OF is_deleted, moderation_state ON posts
+            FOR EACH ROW
+        EXECUTE PROCEDURE enqueue_timeline_event()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_reposts_timeline ON reposts")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_reposts_timeline
+            AFTER INSERT OR DELETE ON reposts
+            FOR EACH ROW
+        EXECUTE PROCEDURE enqueue_timeline_event()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_follows_timeline ON follows")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_follows_timeline
+            AFTER INSERT OR DELETE ON follows
+            FOR EACH ROW
+        EXECUTE PROCEDURE enqueue_timeline_event()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Fills timelines from existing follows the first time the table is created
+async fn backfill_home_timeline(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO home_timeline (user_id, post_id, reposter_id, activity_at)
+        SELECT DISTINCT ON (f.follower_id, a.post_id) f.follower_id, a.post_id, a.reposter_id, a.activity_at
+        FROM follows f
+        INNER JOIN (
+            SELECT p.user_id AS source_id, p.id AS post_id, NULL::INT4 AS reposter_id, p.created_at AS activity_at
+            FROM posts p
+            WHERE p.is_deleted IS NOT TRUE
+            UNION ALL
+            SELECT r.user_id, r.post_id, r.user_id, r.created_at
+            FROM reposts r
+            INNER JOIN posts p ON p.id = r.post_id
+            WHERE p.is_deleted IS NOT TRUE
+        ) a ON a.source_id = f.following_id
+        WHERE NOT EXISTS (SELECT 1 FROM home_timeline)
+        ORDER BY f.follower_id, a.post_id, a.activity_at DESC
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_comments_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS comments (
+            id SERIAL PRIMARY KEY,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+            content TEXT NOT NULL CHECK (char_length(content) <= 280),
+            like_count INTEGER DEFAULT 0,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            reply_to_comment_id INTEGER REFERENCES comments(id) ON DELETE CASCADE,
+            is_deleted BOOLEAN DEFAULT FALSE,
+            deleted_at TIMESTAMP
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn add_version_columns(pool: &PgPool) -> Result<()> {
+    for table in ["todos", "posts", "comments"] {
+        sqlx::query(&format!(
+            "ALTER TABLE {} ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1",
+            table
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn create_comment_likes_table(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS comment_likes (
+            id SERIAL PRIMARY KEY,
+            user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            comment_id INTEGER NOT NULL REFERENCES comments(id) ON DELETE CASCADE,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            CONSTRAINT unique_comment_like UNIQUE (user_id, comment_id)
+        )
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_comment_likes_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_comment_likes_comment_id ON comment_likes(comment_id)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn create_comment_likes_trigger(pool: &PgPool) -> Result<()> {
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_comment_like_counts ON comment_likes")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_comment_like_counts
+            AFTER INSERT OR DELETE ON comment_likes
+            FOR EACH ROW
+        EXECUTE PROCEDURE update_comment_like_counts()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Fixes comment counts that drifted while soft deletes were not counted
+async fn recount_post_comments(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE posts p
+        SET comment_count = c.total
+        FROM (
+            SELECT p2.id, COUNT(c2.id) AS total
+            FROM posts p2
+            LEFT JOIN comments c2 ON c2.post_id = p2.id AND c2.is_deleted IS NOT TRUE
+            GROUP BY p2.id
+        ) c
+        WHERE p.id = c.id AND p.comment_count IS DISTINCT FROM c.total
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_comments_indexes(pool: &PgPool) -> Result<()> {
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_comments_user_id ON comments(user_id)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_comments_post_id ON comments(post_id)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_comments_created_at ON comments(created_at DESC)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_comments_post_id_created_at ON comments(post_id, created_at DESC)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_comments_reply_to ON comments(reply_to_comment_id)",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_comments_is_deleted ON comments(is_deleted)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+async fn create_comments_trigger(pool: &PgPool) -> Result<()> {
+    sqlx::query("DROP TRIGGER IF EXISTS trigger_comment_counts ON comments")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER trigger_comment_counts
+            AFTER INSERT OR DELETE OR UPDATE OF is_deleted ON comments
+            FOR EACH ROW
+        EXECUTE PROCEDURE update_comment_counts()
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("DROP TRIGGER IF EXISTS update_comments_updated_at ON comments")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TRIGGER update_comments_updated_at
+            BEFORE UPDATE
             ON comments
             FOR EACH ROW
         EXECUTE PROCEDURE update_updated_column()
@@ -596,6 +2153,24 @@ async fn create_update_function(pool: &PgPool) -> Result<()> {
     Ok(())
 }
 
+// users and todos use `updated` rather than `updated_at`, so they need their own timestamp function
+async fn create_updated_timestamp_function(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE OR REPLACE FUNCTION update_updated_timestamp()
+        RETURNS TRIGGER AS $$
+        BEGIN
+            NEW.updated = NOW();
+            RETURN NEW;
+        END;
+        $$ language 'plpgsql'
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 async fn create_follow_count_function(pool: &PgPool) -> Result<()> {
     sqlx::query(
         r#"
@@ -660,22 +2235,236 @@ async fn create_like_count_function(pool: &PgPool) -> Result<()> {
     Ok(())
 }
 
-async fn create_comment_count_function(pool: &PgPool) -> Result<()> {
+// Tallies move with the votes; each vote locks its option and poll row, so
+// concurrent votes are counted one after the other
+async fn create_poll_vote_count_function(pool: &PgPool) -> Result<()> {
     sqlx::query(
         r#"
-        CREATE OR REPLACE FUNCTION update_comment_counts()
+        CREATE OR REPLACE FUNCTION update_poll_vote_counts()
+        RETURNS TRIGGER AS $$
+        BEGIN
+            IF TG_OP = 'INSERT' THEN
+                UPDATE poll_options SET vote_count = vote_count + 1 WHERE id = NEW.option_id;
+                UPDATE polls SET vote_count = vote_count + 1 WHERE id = NEW.poll_id;
+                RETURN NEW;
+            ELSIF TG_OP = 'DELETE' THEN
+                -- Votes deleted along with their post have no tallies left to
+                -- fix, and touching the dying poll rows would fail their keys
+                IF EXISTS (
+                    SELECT 1 FROM polls p INNER JOIN posts ON posts.id = p.post_id
+                    WHERE p.id = OLD.poll_id
+                ) THEN
+                    UPDATE poll_options SET vote_count = GREATEST(0, vote_count - 1)
+                    WHERE id = OLD.option_id;
+                    UPDATE polls SET vote_count = GREATEST(0, vote_count - 1)
+                    WHERE id = OLD.poll_id;
+                END IF;
+                RETURN OLD;
+            END IF;
+
+            RETURN NULL;
+        END;
+        $$ language 'plpgsql'
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_repost_count_function(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE OR REPLACE FUNCTION update_repost_counts()
         RETURNS TRIGGER AS $$
         BEGIN
             IF TG_OP = 'INSERT' THEN
-                UPDATE posts SET comment_count = comment_count + 1
+                UPDATE posts SET repost_count = COALESCE(repost_count, 0) + 1
                 WHERE id = NEW.post_id;
                 RETURN NEW;
             ELSIF TG_OP = 'DELETE' THEN
-                UPDATE posts SET comment_count = GREATEST(0, comment_count - 1)
+                UPDATE posts SET repost_count = GREATEST(0, COALESCE(repost_count, 0) - 1)
                 WHERE id = OLD.post_id;
                 RETURN OLD;
             END IF;
-            
+
+            RETURN NULL;
+        END;
+        $$ language 'plpgsql'
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Queues timeline work for the fan-out worker
+async fn create_timeline_event_function(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE OR REPLACE FUNCTION enqueue_timeline_event()
+        RETURNS TRIGGER AS $$
+        BEGIN
+            IF TG_TABLE_NAME = 'posts' THEN
+                IF TG_OP = 'INSERT' THEN
+                    INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('post_created', NEW.user_id, NEW.id);
+                ELSIF NEW.is_deleted IS TRUE AND OLD.is_deleted IS NOT TRUE THEN
+                    INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('post_deleted', NEW.user_id, NEW.id);
+                ELSIF NEW.moderation_state IS NOT NULL AND OLD.moderation_state IS NULL THEN
+                    -- Flagged by the content filter after an edit
+                    INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('post_deleted', NEW.user_id, NEW.id);
+                ELSIF NEW.moderation_state IS NULL AND OLD.moderation_state IS NOT NULL THEN
+                    -- Released by a moderator
+                    INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('post_created', NEW.user_id, NEW.id);
+                END IF;
+                RETURN NEW;
+            ELSIF TG_TABLE_NAME = 'reposts' THEN
+                IF TG_OP = 'INSERT' THEN
+                    INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('repost_created', NEW.user_id, NEW.post_id);
+                    RETURN NEW;
+                END IF;
+                INSERT INTO timeline_events (kind, actor_id, post_id) VALUES ('repost_deleted', OLD.user_id, OLD.post_id);
+                RETURN OLD;
+            ELSIF TG_TABLE_NAME = 'follows' THEN
+                IF TG_OP = 'INSERT' THEN
+                    INSERT INTO timeline_events (kind, actor_id, target_user_id) VALUES ('follow_created', NEW.follower_id, NEW.following_id);
+                    RETURN NEW;
+                END IF;
+                INSERT INTO timeline_events (kind, actor_id, target_user_id) VALUES ('follow_deleted', OLD.follower_id, OLD.following_id);
+                RETURN OLD;
+            END IF;
+            RETURN NULL;
+        END;
+        $$ language 'plpgsql'
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Records new notifications, new home timeline entries, todo changes and new
+// direct messages in stream_events, and wakes the listener on every instance
+async fn create_stream_event_functions(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE OR REPLACE FUNCTION enqueue_stream_event()
+        RETURNS TRIGGER AS $$
+        BEGIN
+            IF TG_TABLE_NAME = 'notifications' THEN
+                INSERT INTO stream_events (user_id, kind, payload)
+                VALUES (NEW.user_id, 'notification', json_build_object(
+                    'notification_id', NEW.id, 'kind', NEW.kind, 'post_id', NEW.post_id,
+                    'comment_id', NEW.comment_id, 'actor_count', NEW.actor_count));
+                RETURN NEW;
+            ELSIF TG_TABLE_NAME = 'home_timeline' THEN
+                INSERT INTO stream_events (user_id, kind, payload)
+                VALUES (NEW.user_id, 'feed_item', json_build_object(
+                    'post_id', NEW.post_id, 'reposter_id', NEW.reposter_id));
+                RETURN NEW;
+            ELSIF TG_TABLE_NAME = 'todos' THEN
+                IF TG_OP = 'DELETE' THEN
+                    INSERT INTO stream_events (user_id, kind, payload)
+                    VALUES (OLD.user_id, 'todo', json_build_object('todo_id', OLD.id, 'op', 'deleted'));
+                    RETURN OLD;
+                END IF;
+                INSERT INTO stream_events (user_id, kind, payload)
+                VALUES (NEW.user_id, 'todo', json_build_object(
+                    'todo_id', NEW.id, 'op', CASE WHEN TG_OP = 'INSERT' THEN 'created' ELSE 'updated' END));
+                RETURN NEW;
+            ELSIF TG_TABLE_NAME = 'messages' THEN
+                INSERT INTO stream_events (user_id, kind, payload)
+                SELECT cm.user_id, 'message', json_build_object(
+                    'conversation_id', NEW.conversation_id, 'message_id', NEW.id,
+                    'sender_id', NEW.sender_id)
+                FROM conversation_members cm
+                WHERE cm.conversation_id = NEW.conversation_id
+                  AND cm.user_id IS DISTINCT FROM NEW.sender_id;
+                RETURN NEW;
+            END IF;
+            RETURN NULL;
+        END;
+        $$ language 'plpgsql'
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE OR REPLACE FUNCTION notify_stream_event()
+        RETURNS TRIGGER AS $$
+        BEGIN
+            PERFORM pg_notify('stream_events', json_build_object(
+                'id', NEW.id, 'user_id', NEW.user_id, 'kind', NEW.kind,
+                'payload', NEW.payload, 'created_at', NEW.created_at)::text);
+            RETURN NEW;
+        END;
+        $$ language 'plpgsql'
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Only live comments are counted, so soft deletes and restores adjust the count too
+async fn create_comment_count_function(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE OR REPLACE FUNCTION update_comment_counts()
+        RETURNS TRIGGER AS $$
+        BEGIN
+            IF TG_OP = 'INSERT' THEN
+                IF NEW.is_deleted IS NOT TRUE THEN
+                    UPDATE posts SET comment_count = COALESCE(comment_count, 0) + 1
+                    WHERE id = NEW.post_id;
+                END IF;
+                RETURN NEW;
+            ELSIF TG_OP = 'DELETE' THEN
+                IF OLD.is_deleted IS NOT TRUE THEN
+                    UPDATE posts SET comment_count = GREATEST(0, COALESCE(comment_count, 0) - 1)
+                    WHERE id = OLD.post_id;
+                END IF;
+                RETURN OLD;
+            ELSIF TG_OP = 'UPDATE' THEN
+                IF NEW.is_deleted IS TRUE AND OLD.is_deleted IS NOT TRUE THEN
+                    UPDATE posts SET comment_count = GREATEST(0, COALESCE(comment_count, 0) - 1)
+                    WHERE id = NEW.post_id;
+                ELSIF NEW.is_deleted IS NOT TRUE AND OLD.is_deleted IS TRUE THEN
+                    UPDATE posts SET comment_count = COALESCE(comment_count, 0) + 1
+                    WHERE id = NEW.post_id;
+                END IF;
+                RETURN NEW;
+            END IF;
+
+            RETURN NULL;
+        END;
+        $$ language 'plpgsql'
+    "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn create_comment_like_count_function(pool: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE OR REPLACE FUNCTION update_comment_like_counts()
+        RETURNS TRIGGER AS $$
+        BEGIN
+            IF TG_OP = 'INSERT' THEN
+                UPDATE comments SET like_count = COALESCE(like_count, 0) + 1
+                WHERE id = NEW.comment_id;
+                RETURN NEW;
+            ELSIF TG_OP = 'DELETE' THEN
+                UPDATE comments SET like_count = GREATEST(0, COALESCE(like_count, 0) - 1)
+                WHERE id = OLD.comment_id;
+                RETURN OLD;
+            END IF;
+
             RETURN NULL;
         END;
         $$ language 'plpgsql'
@@ -685,3 +2474,42 @@ async fn create_comment_count_function(pool: &PgPool) -> Result<()> {
     .await?;
     Ok(())
 }
+
+async fn create_todo_sync_functions(pool: &PgPool) -> Result<()> {
+    // Stamps todos with the id of the transaction that last wrote them
+    sqlx::query(
+        r#"
+        CREATE OR REPLACE FUNCTION stamp_todo_sync_xid()
+        RETURNS TRIGGER AS $$
+        BEGIN
+            NEW.sync_xid = pg_current_xact_id()::text::bigint;
+            IF TG_OP = 'INSERT' THEN
+                NEW.created_xid = NEW.sync_xid;
+            END IF;
+            RETURN NEW;
+        END;
+        $$ language 'plpgsql'
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Leaves a tombstone behind when a todo is removed for good
+    sqlx::query(
+        r#"
+        CREATE OR REPLACE FUNCTION record_todo_tombstone()
+        RETURNS TRIGGER AS $$
+        BEGIN
+            INSERT INTO todo_tombstones (todo_id, user_id, sync_xid)
+            VALUES (OLD.id, OLD.user_id, pg_current_xact_id()::text::bigint)
+            ON CONFLICT (todo_id) DO UPDATE SET sync_xid = EXCLUDED.sync_xid, deleted_at = NOW();
+            RETURN OLD;
+        END;
+        $$ language 'plpgsql'
+    "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -521,11 +521,13 @@ pub async fn error_correlation_middleware(mut request: Request, next: Next) -> R
 }
 
 /// Helper trait for adding context to errors
+#[allow(clippy::result_large_err)]
 pub trait WithErrorContext<T> {
     fn with_context(self, context: ErrorContext) -> Result<T, AppError>;
     fn with_user_context(self, user_id: i32, path: &str) -> Result<T, AppError>;
 }
 
+#[allow(clippy::result_large_err)]
 impl<T, E> WithErrorContext<T> for Result<T, E>
 where
     E: Into<AppError>,
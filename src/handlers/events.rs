@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    Extension,
+};
+use futures::stream::{self, Stream, StreamExt};
+use serde::Deserialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{
+    db::models::StreamEvent,
+    handlers::models::Claims,
+    service::events::{self, EventService, HEARTBEAT_INTERVAL, REPLAY_LIMIT},
+    AppState,
+};
+
+#[derive(Deserialize)]
+pub struct EventsQuery {
+    /// Fallback for clients that cannot set the Last-Event-ID header
+    pub last_event_id: Option<i64>,
+}
+
+fn to_sse(event: &StreamEvent) -> Event {
+    Event::default()
+        .id(event.id.to_string())
+        .event(&event.kind)
+        .data(event.payload.to_string())
+}
+
+/// Where a stream stands: draining the backlog page by page, then following
+/// the live bus
+struct StreamState {
+    event_service: Arc<EventService>,
+    receiver: broadcast::Receiver<Arc<StreamEvent>>,
+    user_id: i32,
+    /// Id of the last event sent, or the one the client resumed after
+    last_sent: Option<i64>,
+    pending: VecDeque<StreamEvent>,
+    backlog_done: bool,
+}
+
+impl StreamState {
+    async fn next(mut self) -> Option<(Event, Self)> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                self.last_sent = Some(event.id);
+                return Some((to_sse(&event), self));
+            }
+
+            if !self.backlog_done {
+                let after_id = self.last_sent?;
+                match self.event_service.replay(self.user_id, after_id).await {
+                    Ok(page) => self.fill(page),
+                    // Ending the stream makes the client resume from the last event
+                    Err(e) => {
+                        eprintln!("Failed to replay events: {}", e);
+                        return None;
+                    }
+                }
+                continue;
+            }
+
+            match self.receiver.recv().await {
+                Ok(event) if events::is_deliverable(&event, self.user_id, self.last_sent) => {
+                    self.last_sent = Some(event.id);
+                    return Some((to_sse(&event), self));
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(_)) | Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    fn fill(&mut self, page: Vec<StreamEvent>) {
+        self.backlog_done = (page.len() as i64) < REPLAY_LIMIT;
+        self.pending.extend(page);
+    }
+}
+
+/// Streams the caller's notifications, feed items and todo changes as
+/// server-sent events. A client that reconnects with `Last-Event-ID` first
+/// receives everything it missed, read from the log one page at a time.
+/// When a client falls too far behind the stream ends, so that it
+/// reconnects and catches up from the log.
+pub async fn stream(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
+    Query(query): Query<EventsQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    let header = headers
+        .get("last-event-id")
+        .map(|value| value.to_str().map_err(|_| StatusCode::BAD_REQUEST))
+        .transpose()?;
+    let resume_after =
+        events::resume_after(header, query.last_event_id).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    // Subscribe before reading the backlog so nothing falls in between
+    let mut state = StreamState {
+        receiver: app_state.event_service.subscribe(),
+        event_service: app_state.event_service.clone(),
+        user_id: claims.sub,
+        last_sent: resume_after,
+        pending: VecDeque::new(),
+        backlog_done: resume_after.is_none(),
+    };
+    if let Some(after_id) = resume_after {
+        let page = app_state
+            .event_service
+            .replay(claims.sub, after_id)
+            .await
+            .map_err(|e| {
+                eprintln!("Failed to replay events: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        state.fill(page);
+    }
+
+    let events = stream::unfold(state, StreamState::next).map(Ok);
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::new().interval(HEARTBEAT_INTERVAL)))
+}
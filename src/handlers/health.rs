@@ -12,7 +12,7 @@ use crate::{
 
 pub async fn handler(State(app_state): State<AppState>) -> AppResult<impl IntoResponse> {
     // Check database connectivity with proper error handling
-    match crate::db::health_check(&app_state.todo_service.get_pool()).await {
+    match crate::db::health_check(app_state.todo_service.get_pool()).await {
         Ok(_) => {
             // Return healthy status
             Ok(Json(json!({
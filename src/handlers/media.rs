@@ -0,0 +1,102 @@
+use axum::{
+    extract::{Multipart, Path, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
+    Extension,
+};
+
+use crate::{
+    db::models::UploadedMedia,
+    handlers::models::Claims,
+    service::{
+        blob_store::validate_key,
+        media::{content_type_for_key, MediaError},
+    },
+    AppState,
+};
+
+/// Multipart field carrying the file
+const FILE_FIELD: &str = "file";
+
+/// Bytes allowed on top of the file itself for multipart boundaries and
+/// part headers
+pub const MULTIPART_OVERHEAD_BYTES: usize = 64 * 1024;
+
+pub(crate) fn error_status(error: &anyhow::Error) -> StatusCode {
+    match error.downcast_ref::<MediaError>() {
+        Some(MediaError::UnsupportedType) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        Some(MediaError::TooLarge) => StatusCode::PAYLOAD_TOO_LARGE,
+        Some(MediaError::InvalidImage(_))
+        | Some(MediaError::NotFound)
+        | Some(MediaError::TooManyAttachments)
+        | Some(MediaError::DuplicateAttachment)
+        | Some(MediaError::AltTextTooLong)
+        | Some(MediaError::ExternalUrl) => StatusCode::UNPROCESSABLE_ENTITY,
+        None => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Accepts a `multipart/form-data` upload with a single `file` field
+pub async fn upload(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<UploadedMedia>), StatusCode> {
+    let mut data = None;
+    while let Some(field) = multipart.next_field().await.map_err(|e| e.status())? {
+        if field.name() == Some(FILE_FIELD) {
+            data = Some(field.bytes().await.map_err(|e| e.status())?);
+            break;
+        }
+    }
+    let data = data.ok_or(StatusCode::BAD_REQUEST)?;
+
+    if data.len() > app_state.media_service.max_upload_bytes() {
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    match app_state
+        .media_service
+        .upload(claims.sub, data.to_vec())
+        .await
+    {
+        Ok(media) => Ok((StatusCode::CREATED, Json(media))),
+        Err(e) => {
+            eprintln!("Failed to upload media: {}", e);
+            Err(error_status(&e))
+        }
+    }
+}
+
+/// Serves stored files for the local and proxied storage setups. Keys are
+/// unguessable, so files are public like they would be on a CDN.
+pub async fn get_file(
+    State(app_state): State<AppState>,
+    Path(key): Path<String>,
+) -> Result<Response, StatusCode> {
+    validate_key(&key).map_err(|_| StatusCode::NOT_FOUND)?;
+    let content_type = content_type_for_key(&key).ok_or(StatusCode::NOT_FOUND)?;
+
+    match app_state.media_service.read_file(&key).await {
+        Ok(Some(data)) => Ok((
+            [
+                (header::CONTENT_TYPE, HeaderValue::from_static(content_type)),
+                (
+                    header::CACHE_CONTROL,
+                    HeaderValue::from_static("public, max-age=31536000, immutable"),
+                ),
+                (
+                    header::X_CONTENT_TYPE_OPTIONS,
+                    HeaderValue::from_static("nosniff"),
+                ),
+            ],
+            data,
+        )
+            .into_response()),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to read media file: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -1,5 +1,8 @@
 pub mod auth;
+pub mod events;
 pub mod health;
+pub mod media;
 pub mod models;
+pub mod preconditions;
 pub mod social;
 pub mod todo;
@@ -0,0 +1,60 @@
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+
+/// Builds the strong `ETag` header value for a resource version
+pub fn etag(version: i32) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{}\"", version))
+        .unwrap_or_else(|_| HeaderValue::from_static("\"0\""))
+}
+
+/// Reads the `If-Match` header of a write request.
+///
+/// Returns `Ok(None)` when the write is unconditional (`If-Match: *`, or no
+/// header outside strict mode) and `Ok(Some(versions))` with the versions the
+/// client is willing to overwrite otherwise. A missing header in strict mode
+/// yields `428 Precondition Required`; a header that can never match (weak or
+/// malformed tags only) yields `412 Precondition Failed`.
+pub fn if_match_versions(
+    headers: &HeaderMap,
+    strict: bool,
+) -> Result<Option<Vec<i32>>, StatusCode> {
+    let values: Vec<&HeaderValue> = headers.get_all(header::IF_MATCH).iter().collect();
+    if values.is_empty() {
+        return if strict {
+            Err(StatusCode::PRECONDITION_REQUIRED)
+        } else {
+            Ok(None)
+        };
+    }
+
+    let mut versions = Vec::new();
+    for value in values {
+        let value = value
+            .to_str()
+            .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+
+        for tag in value.split(',').map(str::trim) {
+            if tag == "*" {
+                return Ok(None);
+            }
+
+            // If-Match uses strong comparison, so weak tags never match
+            if tag.starts_with("W/") {
+                continue;
+            }
+
+            if let Some(version) = tag
+                .strip_prefix('"')
+                .and_then(|tag| tag.strip_suffix('"'))
+                .and_then(|tag| tag.parse::<i32>().ok())
+            {
+                versions.push(version);
+            }
+        }
+    }
+
+    if versions.is_empty() {
+        return Err(StatusCode::PRECONDITION_FAILED);
+    }
+
+    Ok(Some(versions))
+}
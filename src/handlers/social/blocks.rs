@@ -0,0 +1,145 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    Extension,
+};
+use serde::Deserialize;
+
+use crate::{
+    db::models::{Block, Mute, UserProfile},
+    handlers::models::Claims,
+    AppState,
+};
+
+#[derive(Deserialize)]
+pub struct BlockQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+pub async fn block_user(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(blocked_id): Path<i32>,
+) -> Result<(StatusCode, Json<Block>), StatusCode> {
+    if claims.sub == blocked_id {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match app_state
+        .social_service
+        .block_user(claims.sub, blocked_id)
+        .await
+    {
+        Ok(Some(block)) => Ok((StatusCode::CREATED, Json(block))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to block user: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn unblock_user(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(blocked_id): Path<i32>,
+) -> Result<StatusCode, StatusCode> {
+    match app_state
+        .social_service
+        .unblock_user(claims.sub, blocked_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to unblock user: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_blocked_users(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<BlockQuery>,
+) -> Result<Json<Vec<UserProfile>>, StatusCode> {
+    match app_state
+        .social_service
+        .get_blocked_users(claims.sub, query.limit, query.offset)
+        .await
+    {
+        Ok(users) => Ok(Json(users)),
+        Err(e) => {
+            eprintln!("Failed to get blocked users: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn mute_user(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(muted_id): Path<i32>,
+) -> Result<(StatusCode, Json<Mute>), StatusCode> {
+    if claims.sub == muted_id {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match app_state
+        .social_service
+        .mute_user(claims.sub, muted_id)
+        .await
+    {
+        Ok(Some(mute)) => Ok((StatusCode::CREATED, Json(mute))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to mute user: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn unmute_user(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(muted_id): Path<i32>,
+) -> Result<StatusCode, StatusCode> {
+    match app_state
+        .social_service
+        .unmute_user(claims.sub, muted_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to unmute user: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_muted_users(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<BlockQuery>,
+) -> Result<Json<Vec<UserProfile>>, StatusCode> {
+    match app_state
+        .social_service
+        .get_muted_users(claims.sub, query.limit, query.offset)
+        .await
+    {
+        Ok(users) => Ok(Json(users)),
+        Err(e) => {
+            eprintln!("Failed to get muted users: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::Json,
+    Extension,
+};
+use serde::Deserialize;
+
+use crate::{
+    db::models::{
+        BookmarkCollection, BookmarkEntry, CreateBookmark, CreateBookmarkCollection,
+        UpdateBookmarkCollection,
+    },
+    handlers::{models::Claims, social::posts::NEXT_CURSOR_HEADER},
+    service::bookmarks::{BookmarkCursor, DEFAULT_BOOKMARK_PAGE_SIZE},
+    AppState,
+};
+
+#[derive(Deserialize)]
+pub struct BookmarkQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    pub cursor: Option<String>,
+    /// Only list bookmarks filed in this collection
+    pub collection_id: Option<i32>,
+}
+
+fn default_limit() -> i64 {
+    DEFAULT_BOOKMARK_PAGE_SIZE
+}
+
+pub async fn bookmark_post(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(post_id): Path<i32>,
+    bookmark: Option<Json<CreateBookmark>>,
+) -> Result<(StatusCode, Json<BookmarkEntry>), StatusCode> {
+    let Json(bookmark) = bookmark.unwrap_or_default();
+
+    match app_state
+        .social_service
+        .bookmark_post(claims.sub, post_id, bookmark.collection_id)
+        .await
+    {
+        Ok(Some((entry, true))) => Ok((StatusCode::CREATED, Json(entry))),
+        Ok(Some((entry, false))) => Ok((StatusCode::OK, Json(entry))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to bookmark post: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
+
+pub async fn remove_bookmark(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(post_id): Path<i32>,
+) -> Result<StatusCode, StatusCode> {
+    match app_state
+        .social_service
+        .remove_bookmark(claims.sub, post_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to remove bookmark: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_bookmarks(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<BookmarkQuery>,
+) -> Result<(HeaderMap, Json<Vec<BookmarkEntry>>), StatusCode> {
+    let cursor = match query.cursor.as_deref().map(str::parse::<BookmarkCursor>) {
+        Some(Ok(cursor)) => Some(cursor),
+        Some(Err(_)) => return Err(StatusCode::BAD_REQUEST),
+        None => None,
+    };
+
+    match app_state
+        .social_service
+        .get_bookmarks(claims.sub, query.collection_id, cursor, query.limit)
+        .await
+    {
+        Ok(Some((bookmarks, next_cursor))) => {
+            let mut headers = HeaderMap::new();
+            if let Some(value) =
+                next_cursor.and_then(|cursor| HeaderValue::from_str(&cursor.to_string()).ok())
+            {
+                headers.insert(NEXT_CURSOR_HEADER, value);
+            }
+            Ok((headers, Json(bookmarks)))
+        }
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to get bookmarks: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_collections(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<BookmarkCollection>>, StatusCode> {
+    match app_state
+        .social_service
+        .get_bookmark_collections(claims.sub)
+        .await
+    {
+        Ok(collections) => Ok(Json(collections)),
+        Err(e) => {
+            eprintln!("Failed to get bookmark collections: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn create_collection(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(collection): Json<CreateBookmarkCollection>,
+) -> Result<(StatusCode, Json<BookmarkCollection>), StatusCode> {
+    match app_state
+        .social_service
+        .create_bookmark_collection(claims.sub, &collection.name)
+        .await
+    {
+        Ok(collection) => Ok((StatusCode::CREATED, Json(collection))),
+        Err(e) => {
+            eprintln!("Failed to create bookmark collection: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
+
+pub async fn update_collection(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(collection_id): Path<i32>,
+    Json(collection): Json<UpdateBookmarkCollection>,
+) -> Result<Json<BookmarkCollection>, StatusCode> {
+    match app_state
+        .social_service
+        .rename_bookmark_collection(claims.sub, collection_id, &collection.name)
+        .await
+    {
+        Ok(Some(collection)) => Ok(Json(collection)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to update bookmark collection: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
+
+pub async fn delete_collection(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(collection_id): Path<i32>,
+) -> Result<StatusCode, StatusCode> {
+    match app_state
+        .social_service
+        .delete_bookmark_collection(claims.sub, collection_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to delete bookmark collection: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
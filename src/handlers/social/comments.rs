@@ -1,14 +1,14 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
     response::Json,
     Extension,
 };
 use serde::Deserialize;
 
 use crate::{
-    db::models::{Comment, CreateComment, UpdateComment},
-    handlers::models::Claims,
+    db::models::{Comment, CommentLike, CommentTree, CreateComment, UpdateComment},
+    handlers::{models::Claims, preconditions},
     AppState,
 };
 
@@ -20,10 +20,24 @@ pub struct CommentQuery {
     pub offset: i64,
 }
 
+#[derive(Deserialize)]
+pub struct CommentTreeQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+    #[serde(default = "default_depth")]
+    pub depth: i32,
+}
+
 fn default_limit() -> i64 {
     20
 }
 
+fn default_depth() -> i32 {
+    3
+}
+
 pub async fn create_comment(
     State(app_state): State<AppState>,
     Extension(claims): Extension<Claims>,
@@ -34,25 +48,28 @@ pub async fn create_comment(
         .create_comment(claims.sub, create_comment)
         .await
     {
-        Ok(comment) => Ok((StatusCode::CREATED, Json(comment))),
+        Ok(Some(comment)) => Ok((StatusCode::CREATED, Json(comment))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             eprintln!("Failed to create comment: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(super::error_status(&e))
         }
     }
 }
 
 pub async fn get_post_comments(
     State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(post_id): Path<i32>,
     Query(query): Query<CommentQuery>,
 ) -> Result<Json<Vec<Comment>>, StatusCode> {
     match app_state
         .social_service
-        .get_post_comments(post_id, query.limit, query.offset)
+        .get_post_comments(claims.sub, post_id, query.limit, query.offset)
         .await
     {
-        Ok(comments) => Ok(Json(comments)),
+        Ok(Some(comments)) => Ok(Json(comments)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             eprintln!("Failed to get comments: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -60,22 +77,48 @@ pub async fn get_post_comments(
     }
 }
 
+pub async fn get_comment_tree(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(post_id): Path<i32>,
+    Query(query): Query<CommentTreeQuery>,
+) -> Result<Json<CommentTree>, StatusCode> {
+    match app_state
+        .social_service
+        .get_comment_tree(claims.sub, post_id, query.limit, query.offset, query.depth)
+        .await
+    {
+        Ok(Some(tree)) => Ok(Json(tree)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to get comment tree: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn update_comment(
     State(app_state): State<AppState>,
     Extension(claims): Extension<Claims>,
     Path(comment_id): Path<i32>,
+    headers: HeaderMap,
     Json(update_comment): Json<UpdateComment>,
-) -> Result<Json<Comment>, StatusCode> {
+) -> Result<([(HeaderName, HeaderValue); 1], Json<Comment>), StatusCode> {
+    let if_match = preconditions::if_match_versions(&headers, app_state.config.require_if_match)?;
+
     match app_state
         .social_service
-        .update_comment(comment_id, claims.sub, update_comment)
+        .update_comment(comment_id, claims.sub, update_comment, if_match)
         .await
     {
-        Ok(Some(comment)) => Ok(Json(comment)),
+        Ok(Some(comment)) => Ok((
+            [(header::ETAG, preconditions::etag(comment.version))],
+            Json(comment),
+        )),
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             eprintln!("Failed to update comment: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(super::error_status(&e))
         }
     }
 }
@@ -98,3 +141,41 @@ pub async fn delete_comment(
         }
     }
 }
+
+pub async fn like_comment(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(comment_id): Path<i32>,
+) -> Result<(StatusCode, Json<CommentLike>), StatusCode> {
+    match app_state
+        .social_service
+        .like_comment(claims.sub, comment_id)
+        .await
+    {
+        Ok(Some(like)) => Ok((StatusCode::CREATED, Json(like))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to like comment: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
+
+pub async fn unlike_comment(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(comment_id): Path<i32>,
+) -> Result<StatusCode, StatusCode> {
+    match app_state
+        .social_service
+        .unlike_comment(claims.sub, comment_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to unlike comment: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    Extension,
+};
+use serde::Deserialize;
+
+use crate::{
+    db::models::{Follow, FollowRequestWithUser},
+    handlers::models::Claims,
+    AppState,
+};
+
+#[derive(Deserialize)]
+pub struct FollowRequestQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+pub async fn get_follow_requests(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<FollowRequestQuery>,
+) -> Result<Json<Vec<FollowRequestWithUser>>, StatusCode> {
+    match app_state
+        .social_service
+        .get_follow_requests(claims.sub, query.limit, query.offset)
+        .await
+    {
+        Ok(requests) => Ok(Json(requests)),
+        Err(e) => {
+            eprintln!("Failed to get follow requests: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn approve_follow_request(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(request_id): Path<i32>,
+) -> Result<(StatusCode, Json<Follow>), StatusCode> {
+    match app_state
+        .social_service
+        .approve_follow_request(claims.sub, request_id)
+        .await
+    {
+        Ok(Some(follow)) => Ok((StatusCode::CREATED, Json(follow))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to approve follow request: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn deny_follow_request(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(request_id): Path<i32>,
+) -> Result<StatusCode, StatusCode> {
+    match app_state
+        .social_service
+        .deny_follow_request(claims.sub, request_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to deny follow request: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
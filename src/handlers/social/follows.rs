@@ -7,8 +7,9 @@ use axum::{
 use serde::Deserialize;
 
 use crate::{
-    db::models::{Follow, UserProfile},
+    db::models::{FollowOutcome, Relationship, UserProfile},
     handlers::models::Claims,
+    service::relationships,
     AppState,
 };
 
@@ -28,7 +29,7 @@ pub async fn follow_user(
     State(app_state): State<AppState>,
     Extension(claims): Extension<Claims>,
     Path(following_id): Path<i32>,
-) -> Result<(StatusCode, Json<Follow>), StatusCode> {
+) -> Result<(StatusCode, Json<FollowOutcome>), StatusCode> {
     // Check if trying to follow themselves
     if claims.sub == following_id {
         return Err(StatusCode::BAD_REQUEST);
@@ -39,10 +40,15 @@ pub async fn follow_user(
         .follow_user(claims.sub, following_id)
         .await
     {
-        Ok(follow) => Ok((StatusCode::CREATED, Json(follow))),
+        Ok(Some(outcome @ FollowOutcome::Followed(_))) => Ok((StatusCode::CREATED, Json(outcome))),
+        // Private accounts have to approve the request first
+        Ok(Some(outcome @ FollowOutcome::Requested(_))) => {
+            Ok((StatusCode::ACCEPTED, Json(outcome)))
+        }
+        Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             eprintln!("Failed to follow user: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(super::error_status(&e))
         }
     }
 }
@@ -84,38 +90,85 @@ pub async fn check_following(
     }
 }
 
+pub async fn get_relationship(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(user_id): Path<i32>,
+) -> Result<Json<Relationship>, StatusCode> {
+    match app_state
+        .social_service
+        .get_relationship(claims.sub, user_id)
+        .await
+    {
+        Ok(Some(relationship)) => Ok(Json(relationship)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to get relationship: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RelationshipQuery {
+    /// Comma-separated user ids
+    pub ids: String,
+}
+
+pub async fn get_relationships(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<RelationshipQuery>,
+) -> Result<Json<Vec<Relationship>>, StatusCode> {
+    let user_ids = relationships::parse_user_ids(&query.ids).ok_or(StatusCode::BAD_REQUEST)?;
+
+    match app_state
+        .social_service
+        .get_relationships(claims.sub, &user_ids)
+        .await
+    {
+        Ok(relationships) => Ok(Json(relationships)),
+        Err(e) => {
+            eprintln!("Failed to get relationships: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub async fn get_followers(
     State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(user_id): Path<i32>,
     Query(query): Query<FollowQuery>,
 ) -> Result<Json<Vec<UserProfile>>, StatusCode> {
     match app_state
         .social_service
-        .get_followers(user_id, query.limit, query.offset)
+        .get_followers(claims.sub, user_id, query.limit, query.offset)
         .await
     {
         Ok(followers) => Ok(Json(followers)),
         Err(e) => {
             eprintln!("Failed to get followers: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(super::error_status(&e))
         }
     }
 }
 
 pub async fn get_following(
     State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(user_id): Path<i32>,
     Query(query): Query<FollowQuery>,
 ) -> Result<Json<Vec<UserProfile>>, StatusCode> {
     match app_state
         .social_service
-        .get_following(user_id, query.limit, query.offset)
+        .get_following(claims.sub, user_id, query.limit, query.offset)
         .await
     {
         Ok(following) => Ok(Json(following)),
         Err(e) => {
             eprintln!("Failed to get following: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(super::error_status(&e))
         }
     }
 }
@@ -0,0 +1,98 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    Extension,
+};
+use serde::Deserialize;
+
+use crate::{
+    db::models::{FeedEntry, FeedPost, TrendingHashtag},
+    handlers::models::Claims,
+    service::entities,
+    AppState,
+};
+
+#[derive(Deserialize)]
+pub struct HashtagPostsQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+    #[serde(default = "default_comment_preview")]
+    pub comments: i64,
+}
+
+#[derive(Deserialize)]
+pub struct TrendingQuery {
+    #[serde(default = "default_window_hours")]
+    pub window_hours: i32,
+    #[serde(default = "default_trending_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    20
+}
+
+fn default_comment_preview() -> i64 {
+    3
+}
+
+fn default_window_hours() -> i32 {
+    entities::DEFAULT_TRENDING_WINDOW_HOURS
+}
+
+fn default_trending_limit() -> i64 {
+    10
+}
+
+pub async fn get_hashtag_posts(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(tag): Path<String>,
+    Query(query): Query<HashtagPostsQuery>,
+) -> Result<Json<Vec<FeedPost>>, StatusCode> {
+    let social_service = &app_state.social_service;
+    let result = async {
+        let posts = social_service
+            .get_hashtag_posts(claims.sub, &tag, query.limit, query.offset)
+            .await?;
+        let entries = posts
+            .into_iter()
+            .map(|post| FeedEntry {
+                post,
+                reposted_by: None,
+            })
+            .collect();
+        social_service
+            .hydrate_posts(claims.sub, entries, query.comments)
+            .await
+    }
+    .await;
+
+    match result {
+        Ok(posts) => Ok(Json(posts)),
+        Err(e) => {
+            eprintln!("Failed to get hashtag posts: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_trending_hashtags(
+    State(app_state): State<AppState>,
+    Query(query): Query<TrendingQuery>,
+) -> Result<Json<Vec<TrendingHashtag>>, StatusCode> {
+    match app_state
+        .social_service
+        .get_trending_hashtags(query.window_hours, query.limit)
+        .await
+    {
+        Ok(hashtags) => Ok(Json(hashtags)),
+        Err(e) => {
+            eprintln!("Failed to get trending hashtags: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -17,10 +17,11 @@ pub async fn like_post(
         .like_post(claims.sub, post_id)
         .await
     {
-        Ok(like) => Ok((StatusCode::CREATED, Json(like))),
+        Ok(Some(like)) => Ok((StatusCode::CREATED, Json(like))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             eprintln!("Failed to like post: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(super::error_status(&e))
         }
     }
 }
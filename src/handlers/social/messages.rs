@@ -0,0 +1,183 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::Json,
+    Extension,
+};
+use serde::Deserialize;
+
+use crate::{
+    db::models::{
+        Conversation, CreateConversation, CreateMessage, MarkConversationRead, Message, UnreadCount,
+    },
+    handlers::{models::Claims, social::posts::NEXT_CURSOR_HEADER},
+    service::messages::{DEFAULT_CONVERSATION_PAGE_SIZE, DEFAULT_MESSAGE_PAGE_SIZE},
+    AppState,
+};
+
+#[derive(Deserialize)]
+pub struct ConversationQuery {
+    #[serde(default = "default_conversation_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_conversation_limit() -> i64 {
+    DEFAULT_CONVERSATION_PAGE_SIZE
+}
+
+#[derive(Deserialize)]
+pub struct MessageQuery {
+    #[serde(default = "default_message_limit")]
+    pub limit: i64,
+    /// Only list messages older than this message id
+    pub cursor: Option<i32>,
+}
+
+fn default_message_limit() -> i64 {
+    DEFAULT_MESSAGE_PAGE_SIZE
+}
+
+pub async fn start_conversation(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(create): Json<CreateConversation>,
+) -> Result<(StatusCode, Json<Conversation>), StatusCode> {
+    match app_state
+        .social_service
+        .start_conversation(claims.sub, create)
+        .await
+    {
+        Ok((conversation, true)) => Ok((StatusCode::CREATED, Json(conversation))),
+        Ok((conversation, false)) => Ok((StatusCode::OK, Json(conversation))),
+        Err(e) => {
+            eprintln!("Failed to start conversation: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
+
+pub async fn get_conversations(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ConversationQuery>,
+) -> Result<Json<Vec<Conversation>>, StatusCode> {
+    match app_state
+        .social_service
+        .get_conversations(claims.sub, query.limit, query.offset)
+        .await
+    {
+        Ok(conversations) => Ok(Json(conversations)),
+        Err(e) => {
+            eprintln!("Failed to get conversations: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_conversation(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(conversation_id): Path<i32>,
+) -> Result<Json<Conversation>, StatusCode> {
+    match app_state
+        .social_service
+        .get_conversation(claims.sub, conversation_id)
+        .await
+    {
+        Ok(Some(conversation)) => Ok(Json(conversation)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to get conversation: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_messages(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(conversation_id): Path<i32>,
+    Query(query): Query<MessageQuery>,
+) -> Result<(HeaderMap, Json<Vec<Message>>), StatusCode> {
+    match app_state
+        .social_service
+        .get_messages(claims.sub, conversation_id, query.cursor, query.limit)
+        .await
+    {
+        Ok(Some(messages)) => {
+            let mut headers = HeaderMap::new();
+            if messages.len() as i64 == query.limit {
+                if let Some(last) = messages.last() {
+                    headers.insert(NEXT_CURSOR_HEADER, HeaderValue::from(last.id));
+                }
+            }
+            Ok((headers, Json(messages)))
+        }
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to get messages: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn send_message(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(conversation_id): Path<i32>,
+    Json(message): Json<CreateMessage>,
+) -> Result<(StatusCode, Json<Message>), StatusCode> {
+    match app_state
+        .social_service
+        .send_message(claims.sub, conversation_id, &message.content)
+        .await
+    {
+        Ok(Some(message)) => Ok((StatusCode::CREATED, Json(message))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to send message: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
+
+pub async fn mark_read(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(conversation_id): Path<i32>,
+    read: Option<Json<MarkConversationRead>>,
+) -> Result<StatusCode, StatusCode> {
+    let Json(read) = read.unwrap_or_default();
+
+    match app_state
+        .social_service
+        .mark_conversation_read(claims.sub, conversation_id, read.message_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to mark conversation read: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
+
+pub async fn get_unread_count(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<UnreadCount>, StatusCode> {
+    match app_state
+        .social_service
+        .get_unread_message_count(claims.sub)
+        .await
+    {
+        Ok(count) => Ok(Json(UnreadCount { count })),
+        Err(e) => {
+            eprintln!("Failed to count unread messages: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -1,5 +1,53 @@
+use axum::http::StatusCode;
+
+use crate::service::social::SocialError;
+
+pub mod blocks;
+pub mod bookmarks;
 pub mod comments;
+pub mod follow_requests;
 pub mod follows;
+pub mod hashtags;
 pub mod likes;
+pub mod messages;
+pub mod moderation;
+pub mod notifications;
+pub mod polls;
 pub mod posts;
 pub mod profile;
+pub mod reposts;
+pub mod scheduled_posts;
+pub mod suggestions;
+
+/// Maps an error returned by `SocialService` to the response status
+pub(crate) fn error_status(error: &anyhow::Error) -> StatusCode {
+    match error.downcast_ref::<SocialError>() {
+        Some(SocialError::PreconditionFailed) => StatusCode::PRECONDITION_FAILED,
+        Some(SocialError::ReplyTargetNotFound)
+        | Some(SocialError::QuoteTargetNotFound)
+        | Some(SocialError::ParentCommentNotFound)
+        | Some(SocialError::ContentRejected)
+        | Some(SocialError::ReportTargetNotFound)
+        | Some(SocialError::ReportDetailsTooLong)
+        | Some(SocialError::InvalidReportReason)
+        | Some(SocialError::CollectionNotFound)
+        | Some(SocialError::InvalidCollectionName)
+        | Some(SocialError::PublishTimeInPast)
+        | Some(SocialError::InvalidPoll)
+        | Some(SocialError::InvalidPollOption)
+        | Some(SocialError::InvalidConversationMembers)
+        | Some(SocialError::InvalidMessage)
+        | Some(SocialError::MessageNotFound) => StatusCode::UNPROCESSABLE_ENTITY,
+        Some(SocialError::AlreadyReposted)
+        | Some(SocialError::AlreadyLiked)
+        | Some(SocialError::AlreadyReported)
+        | Some(SocialError::ReportClosed)
+        | Some(SocialError::CollectionNameTaken)
+        | Some(SocialError::AlreadyVoted) => StatusCode::CONFLICT,
+        Some(SocialError::PrivateAccount)
+        | Some(SocialError::Blocked)
+        | Some(SocialError::EditWindowClosed)
+        | Some(SocialError::PollClosed) => StatusCode::FORBIDDEN,
+        None => crate::handlers::media::error_status(error),
+    }
+}
@@ -0,0 +1,112 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    Extension,
+};
+use serde::Deserialize;
+
+use crate::{
+    db::models::{CreateReport, Report, ReportDecision, ReportStatus},
+    handlers::models::Claims,
+    service::moderation::DEFAULT_REPORT_PAGE_SIZE,
+    AppState,
+};
+
+#[derive(Deserialize)]
+pub struct ReportQuery {
+    #[serde(default = "default_status")]
+    pub status: ReportStatus,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_status() -> ReportStatus {
+    ReportStatus::Open
+}
+
+fn default_limit() -> i64 {
+    DEFAULT_REPORT_PAGE_SIZE
+}
+
+pub async fn create_report(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(report): Json<CreateReport>,
+) -> Result<(StatusCode, Json<Report>), StatusCode> {
+    match app_state
+        .social_service
+        .create_report(claims.sub, report)
+        .await
+    {
+        Ok(report) => Ok((StatusCode::CREATED, Json(report))),
+        Err(e) => {
+            eprintln!("Failed to create report: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
+
+/// Moderation queue; admins only
+pub async fn get_reports(
+    State(app_state): State<AppState>,
+    Query(query): Query<ReportQuery>,
+) -> Result<Json<Vec<Report>>, StatusCode> {
+    match app_state
+        .social_service
+        .get_reports(query.status, query.limit, query.offset)
+        .await
+    {
+        Ok(reports) => Ok(Json(reports)),
+        Err(e) => {
+            eprintln!("Failed to get reports: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn resolve_report(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(report_id): Path<i32>,
+    decision: Option<Json<ReportDecision>>,
+) -> Result<Json<Report>, StatusCode> {
+    let Json(decision) = decision.unwrap_or_default();
+
+    match app_state
+        .social_service
+        .resolve_report(claims.sub, report_id, decision.note)
+        .await
+    {
+        Ok(Some(report)) => Ok(Json(report)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to resolve report: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
+
+pub async fn dismiss_report(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(report_id): Path<i32>,
+    decision: Option<Json<ReportDecision>>,
+) -> Result<Json<Report>, StatusCode> {
+    let Json(decision) = decision.unwrap_or_default();
+
+    match app_state
+        .social_service
+        .dismiss_report(claims.sub, report_id, decision.note)
+        .await
+    {
+        Ok(Some(report)) => Ok(Json(report)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to dismiss report: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::Json,
+    Extension,
+};
+use serde::Deserialize;
+
+use crate::{
+    db::models::{NotificationGroup, NotificationPreference, UnreadCount},
+    handlers::{models::Claims, social::posts::NEXT_CURSOR_HEADER},
+    service::notifications::NotificationCursor,
+    AppState,
+};
+
+#[derive(Deserialize)]
+pub struct NotificationQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub unread_only: bool,
+}
+
+fn default_limit() -> i64 {
+    20
+}
+
+pub async fn get_notifications(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<NotificationQuery>,
+) -> Result<(HeaderMap, Json<Vec<NotificationGroup>>), StatusCode> {
+    let cursor = match query
+        .cursor
+        .as_deref()
+        .map(str::parse::<NotificationCursor>)
+    {
+        Some(Ok(cursor)) => Some(cursor),
+        Some(Err(_)) => return Err(StatusCode::BAD_REQUEST),
+        None => None,
+    };
+
+    match app_state
+        .social_service
+        .get_notifications(claims.sub, cursor, query.limit, query.unread_only)
+        .await
+    {
+        Ok((notifications, next_cursor)) => {
+            let mut headers = HeaderMap::new();
+            if let Some(value) =
+                next_cursor.and_then(|cursor| HeaderValue::from_str(&cursor.to_string()).ok())
+            {
+                headers.insert(NEXT_CURSOR_HEADER, value);
+            }
+            Ok((headers, Json(notifications)))
+        }
+        Err(e) => {
+            eprintln!("Failed to get notifications: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_unread_count(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<UnreadCount>, StatusCode> {
+    match app_state
+        .social_service
+        .get_unread_notification_count(claims.sub)
+        .await
+    {
+        Ok(count) => Ok(Json(UnreadCount { count })),
+        Err(e) => {
+            eprintln!("Failed to count unread notifications: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn mark_read(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(notification_id): Path<i32>,
+) -> Result<StatusCode, StatusCode> {
+    match app_state
+        .social_service
+        .mark_notification_read(claims.sub, notification_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to mark notification read: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn mark_all_read(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<StatusCode, StatusCode> {
+    match app_state
+        .social_service
+        .mark_all_notifications_read(claims.sub)
+        .await
+    {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            eprintln!("Failed to mark notifications read: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_preferences(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<NotificationPreference>>, StatusCode> {
+    match app_state
+        .social_service
+        .get_notification_preferences(claims.sub)
+        .await
+    {
+        Ok(preferences) => Ok(Json(preferences)),
+        Err(e) => {
+            eprintln!("Failed to get notification preferences: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn update_preferences(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(preferences): Json<Vec<NotificationPreference>>,
+) -> Result<Json<Vec<NotificationPreference>>, StatusCode> {
+    match app_state
+        .social_service
+        .update_notification_preferences(claims.sub, preferences)
+        .await
+    {
+        Ok(preferences) => Ok(Json(preferences)),
+        Err(e) => {
+            eprintln!("Failed to update notification preferences: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
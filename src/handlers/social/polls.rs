@@ -0,0 +1,33 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    Extension,
+};
+
+use crate::{
+    db::models::{CreatePollVote, Poll},
+    handlers::models::Claims,
+    AppState,
+};
+
+/// Casts the caller's vote and returns the poll with its results
+pub async fn vote(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(post_id): Path<i32>,
+    Json(vote): Json<CreatePollVote>,
+) -> Result<(StatusCode, Json<Poll>), StatusCode> {
+    match app_state
+        .social_service
+        .vote_in_poll(claims.sub, post_id, vote.option_id)
+        .await
+    {
+        Ok(Some(poll)) => Ok((StatusCode::CREATED, Json(poll))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to vote in poll: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
@@ -1,53 +1,160 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
     response::Json,
     Extension,
 };
 use serde::Deserialize;
 
 use crate::{
-    db::models::{CreatePost, Post, UpdatePost},
-    handlers::models::Claims,
+    db::models::{
+        CreatePost, FeedEntry, FeedMode, FeedPost, Post, PostRevision, PostThread, ReplyMode,
+        UpdatePost,
+    },
+    handlers::{models::Claims, preconditions},
+    service::timeline::TimelineCursor,
     AppState,
 };
 
+/// Response header carrying the cursor of the next home timeline page
+pub const NEXT_CURSOR_HEADER: HeaderName = HeaderName::from_static("x-next-cursor");
+
 #[derive(Deserialize)]
 pub struct PostQuery {
     #[serde(default = "default_limit")]
     pub limit: i64,
     #[serde(default)]
     pub offset: i64,
+    #[serde(default)]
+    pub replies: ReplyMode,
+    /// Number of top comments to include with each post
+    #[serde(default = "default_comment_preview")]
+    pub comments: i64,
+}
+
+#[derive(Deserialize)]
+pub struct FeedQuery {
+    #[serde(default)]
+    pub mode: FeedMode,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+    /// Keyset cursor from a previous page's `X-Next-Cursor` header.
+    /// Chronological mode only; takes precedence over `offset`.
+    pub cursor: Option<String>,
+    /// Ranked mode only distinguishes between including and excluding replies
+    #[serde(default)]
+    pub replies: ReplyMode,
+    #[serde(default = "default_comment_preview")]
+    pub comments: i64,
+}
+
+#[derive(Deserialize)]
+pub struct PostViewQuery {
+    #[serde(default = "default_comment_preview")]
+    pub comments: i64,
+}
+
+#[derive(Deserialize)]
+pub struct ThreadQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+    #[serde(default = "default_thread_depth")]
+    pub depth: i32,
+}
+
+#[derive(Deserialize)]
+pub struct RevisionQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
 }
 
 fn default_limit() -> i64 {
     20
 }
 
+fn default_comment_preview() -> i64 {
+    3
+}
+
+fn default_thread_depth() -> i32 {
+    3
+}
+
+/// Hydrates a post the caller just wrote, without comment previews
+pub(super) async fn hydrate_own_post(
+    app_state: &AppState,
+    user_id: i32,
+    post: Post,
+) -> anyhow::Result<FeedPost> {
+    let entry = FeedEntry {
+        post,
+        reposted_by: None,
+    };
+    app_state
+        .social_service
+        .hydrate_posts(user_id, vec![entry], 0)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Author of a new post could not be loaded"))
+}
+
 pub async fn create_post(
     State(app_state): State<AppState>,
     Extension(claims): Extension<Claims>,
     Json(create_post): Json<CreatePost>,
-) -> Result<(StatusCode, Json<Post>), StatusCode> {
-    match app_state
-        .social_service
-        .create_post(claims.sub, create_post)
-        .await
-    {
+) -> Result<(StatusCode, Json<FeedPost>), StatusCode> {
+    let result = async {
+        let post = app_state
+            .social_service
+            .create_post(claims.sub, create_post)
+            .await?;
+        hydrate_own_post(&app_state, claims.sub, post).await
+    }
+    .await;
+
+    match result {
         Ok(post) => Ok((StatusCode::CREATED, Json(post))),
         Err(e) => {
             eprintln!("Failed to create post: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(super::error_status(&e))
         }
     }
 }
 
 pub async fn get_post(
     State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(post_id): Path<i32>,
-) -> Result<Json<Post>, StatusCode> {
-    match app_state.social_service.get_post(post_id).await {
-        Ok(Some(post)) => Ok(Json(post)),
+    Query(query): Query<PostViewQuery>,
+) -> Result<([(HeaderName, HeaderValue); 1], Json<FeedPost>), StatusCode> {
+    let social_service = &app_state.social_service;
+    let result = async {
+        let Some(post) = social_service.get_post(claims.sub, post_id).await? else {
+            return Ok(None);
+        };
+        let entry = FeedEntry {
+            post,
+            reposted_by: None,
+        };
+        let posts = social_service
+            .hydrate_posts(claims.sub, vec![entry], query.comments)
+            .await?;
+        anyhow::Ok(posts.into_iter().next())
+    }
+    .await;
+
+    match result {
+        Ok(Some(post)) => Ok((
+            [(header::ETAG, preconditions::etag(post.post.post.version))],
+            Json(post),
+        )),
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             eprintln!("Failed to get post: {}", e);
@@ -56,35 +163,152 @@ pub async fn get_post(
     }
 }
 
-pub async fn get_user_posts(
+pub async fn get_post_thread(
     State(app_state): State<AppState>,
-    Path(user_id): Path<i32>,
-    Query(query): Query<PostQuery>,
-) -> Result<Json<Vec<Post>>, StatusCode> {
+    Extension(claims): Extension<Claims>,
+    Path(post_id): Path<i32>,
+    Query(query): Query<ThreadQuery>,
+) -> Result<Json<PostThread>, StatusCode> {
     match app_state
         .social_service
-        .get_user_posts(user_id, query.limit, query.offset)
+        .get_post_thread(claims.sub, post_id, query.limit, query.offset, query.depth)
         .await
     {
-        Ok(posts) => Ok(Json(posts)),
+        Ok(Some(thread)) => Ok(Json(thread)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
-            eprintln!("Failed to get user posts: {}", e);
+            eprintln!("Failed to get post thread: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-pub async fn get_feed(
+pub async fn get_post_revisions(
     State(app_state): State<AppState>,
     Extension(claims): Extension<Claims>,
-    Query(query): Query<PostQuery>,
-) -> Result<Json<Vec<Post>>, StatusCode> {
+    Path(post_id): Path<i32>,
+    Query(query): Query<RevisionQuery>,
+) -> Result<Json<Vec<PostRevision>>, StatusCode> {
     match app_state
         .social_service
-        .get_feed_posts(claims.sub, query.limit, query.offset)
+        .get_post_revisions(claims.sub, post_id, query.limit, query.offset)
         .await
     {
+        Ok(Some(revisions)) => Ok(Json(revisions)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to get post revisions: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_user_posts(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(user_id): Path<i32>,
+    Query(query): Query<PostQuery>,
+) -> Result<Json<Vec<FeedPost>>, StatusCode> {
+    let social_service = &app_state.social_service;
+    let result = async {
+        let posts = social_service
+            .get_user_posts(
+                claims.sub,
+                user_id,
+                query.limit,
+                query.offset,
+                query.replies,
+            )
+            .await?;
+        let entries = posts
+            .into_iter()
+            .map(|post| FeedEntry {
+                post,
+                reposted_by: None,
+            })
+            .collect();
+        social_service
+            .hydrate_posts(claims.sub, entries, query.comments)
+            .await
+    }
+    .await;
+
+    match result {
         Ok(posts) => Ok(Json(posts)),
+        Err(e) => {
+            eprintln!("Failed to get user posts: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
+
+pub async fn get_feed(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<FeedQuery>,
+) -> Result<(HeaderMap, Json<Vec<FeedPost>>), StatusCode> {
+    let cursor = match query.cursor.as_deref().map(str::parse::<TimelineCursor>) {
+        Some(Ok(cursor)) => Some(cursor),
+        Some(Err(_)) => return Err(StatusCode::BAD_REQUEST),
+        None => None,
+    };
+
+    let social_service = &app_state.social_service;
+    let result = async {
+        let mut next_cursor = None;
+        let entries = match query.mode {
+            // The materialized timeline keeps one row per post, so collapsed
+            // conversations and offset paging still go through the live query
+            FeedMode::Chronological
+                if query.replies == ReplyMode::Collapse
+                    || (cursor.is_none() && query.offset > 0) =>
+            {
+                social_service
+                    .get_feed_posts(claims.sub, query.limit, query.offset, query.replies)
+                    .await?
+            }
+            FeedMode::Chronological => {
+                let (entries, next) = social_service
+                    .get_home_timeline(
+                        claims.sub,
+                        cursor,
+                        query.limit,
+                        query.replies == ReplyMode::Exclude,
+                        app_state.config.timeline_fanout_max_followers,
+                    )
+                    .await?;
+                next_cursor = next;
+                entries
+            }
+            FeedMode::Ranked => {
+                social_service
+                    .get_ranked_feed_posts(
+                        claims.sub,
+                        &app_state.config.ranking,
+                        query.limit,
+                        query.offset,
+                        query.replies == ReplyMode::Exclude,
+                    )
+                    .await?
+            }
+        };
+        let posts = social_service
+            .hydrate_posts(claims.sub, entries, query.comments)
+            .await?;
+        anyhow::Ok((posts, next_cursor))
+    }
+    .await;
+
+    match result {
+        Ok((posts, next_cursor)) => {
+            let mut headers = HeaderMap::new();
+            if let Some(value) =
+                next_cursor.and_then(|cursor| HeaderValue::from_str(&cursor.to_string()).ok())
+            {
+                headers.insert(NEXT_CURSOR_HEADER, value);
+            }
+            Ok((headers, Json(posts)))
+        }
         Err(e) => {
             eprintln!("Failed to get feed: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -96,18 +320,40 @@ pub async fn update_post(
     State(app_state): State<AppState>,
     Extension(claims): Extension<Claims>,
     Path(post_id): Path<i32>,
+    headers: HeaderMap,
     Json(update_post): Json<UpdatePost>,
-) -> Result<Json<Post>, StatusCode> {
-    match app_state
-        .social_service
-        .update_post(post_id, claims.sub, update_post)
-        .await
-    {
-        Ok(Some(post)) => Ok(Json(post)),
+) -> Result<([(HeaderName, HeaderValue); 1], Json<FeedPost>), StatusCode> {
+    let if_match = preconditions::if_match_versions(&headers, app_state.config.require_if_match)?;
+
+    let result = async {
+        let Some(post) = app_state
+            .social_service
+            .update_post(
+                post_id,
+                claims.sub,
+                update_post,
+                if_match,
+                app_state.config.post_edit_window_minutes,
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        hydrate_own_post(&app_state, claims.sub, post)
+            .await
+            .map(Some)
+    }
+    .await;
+
+    match result {
+        Ok(Some(post)) => Ok((
+            [(header::ETAG, preconditions::etag(post.post.post.version))],
+            Json(post),
+        )),
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             eprintln!("Failed to update post: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(super::error_status(&e))
         }
     }
 }
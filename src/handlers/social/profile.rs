@@ -56,29 +56,41 @@ pub async fn get_my_profile(
 pub async fn update_profile(
     State(app_state): State<AppState>,
     Extension(claims): Extension<Claims>,
-    Json(update_profile): Json<UpdateUserProfile>,
+    Json(mut update_profile): Json<UpdateUserProfile>,
 ) -> Result<Json<UserProfile>, StatusCode> {
-    match app_state
-        .social_service
-        .update_user_profile(claims.sub, update_profile)
-        .await
-    {
+    let result = async {
+        if let Some(media_id) = update_profile.avatar_media_id {
+            let media = app_state
+                .media_service
+                .get_owned_media(claims.sub, media_id)
+                .await?;
+            update_profile.avatar_url = Some(app_state.media_service.url(&media.storage_key));
+        }
+        app_state
+            .social_service
+            .update_user_profile(claims.sub, update_profile)
+            .await
+    }
+    .await;
+
+    match result {
         Ok(Some(profile)) => Ok(Json(profile)),
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             eprintln!("Failed to update user profile: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(super::error_status(&e))
         }
     }
 }
 
 pub async fn search_users(
     State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Query(query): Query<SearchQuery>,
 ) -> Result<Json<Vec<UserProfile>>, StatusCode> {
     match app_state
         .social_service
-        .search_users(&query.q, query.limit, query.offset)
+        .search_users(claims.sub, &query.q, query.limit, query.offset)
         .await
     {
         Ok(users) => Ok(Json(users)),
@@ -0,0 +1,46 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    Extension,
+};
+
+use crate::{db::models::Repost, handlers::models::Claims, AppState};
+
+pub async fn repost_post(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(post_id): Path<i32>,
+) -> Result<(StatusCode, Json<Repost>), StatusCode> {
+    match app_state
+        .social_service
+        .repost_post(claims.sub, post_id)
+        .await
+    {
+        Ok(Some(repost)) => Ok((StatusCode::CREATED, Json(repost))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to repost post: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
+
+pub async fn unrepost_post(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(post_id): Path<i32>,
+) -> Result<StatusCode, StatusCode> {
+    match app_state
+        .social_service
+        .unrepost_post(claims.sub, post_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to remove repost: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    Extension,
+};
+use serde::Deserialize;
+
+use crate::{
+    db::models::{FeedPost, SaveScheduledPost, ScheduledPost, ScheduledPostStatus},
+    handlers::models::Claims,
+    service::scheduled_posts::DEFAULT_SCHEDULED_PAGE_SIZE,
+    AppState,
+};
+
+#[derive(Deserialize)]
+pub struct ScheduledPostQuery {
+    pub status: Option<ScheduledPostStatus>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_limit() -> i64 {
+    DEFAULT_SCHEDULED_PAGE_SIZE
+}
+
+pub async fn create_scheduled_post(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(draft): Json<SaveScheduledPost>,
+) -> Result<(StatusCode, Json<ScheduledPost>), StatusCode> {
+    match app_state
+        .social_service
+        .create_scheduled_post(claims.sub, draft)
+        .await
+    {
+        Ok(scheduled_post) => Ok((StatusCode::CREATED, Json(scheduled_post))),
+        Err(e) => {
+            eprintln!("Failed to create scheduled post: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
+
+pub async fn get_scheduled_posts(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ScheduledPostQuery>,
+) -> Result<Json<Vec<ScheduledPost>>, StatusCode> {
+    match app_state
+        .social_service
+        .get_scheduled_posts(claims.sub, query.status, query.limit, query.offset)
+        .await
+    {
+        Ok(scheduled_posts) => Ok(Json(scheduled_posts)),
+        Err(e) => {
+            eprintln!("Failed to get scheduled posts: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_scheduled_post(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(scheduled_post_id): Path<i32>,
+) -> Result<Json<ScheduledPost>, StatusCode> {
+    match app_state
+        .social_service
+        .get_scheduled_post(claims.sub, scheduled_post_id)
+        .await
+    {
+        Ok(Some(scheduled_post)) => Ok(Json(scheduled_post)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to get scheduled post: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn update_scheduled_post(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(scheduled_post_id): Path<i32>,
+    Json(draft): Json<SaveScheduledPost>,
+) -> Result<Json<ScheduledPost>, StatusCode> {
+    match app_state
+        .social_service
+        .update_scheduled_post(claims.sub, scheduled_post_id, draft)
+        .await
+    {
+        Ok(Some(scheduled_post)) => Ok(Json(scheduled_post)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to update scheduled post: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
+
+pub async fn delete_scheduled_post(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(scheduled_post_id): Path<i32>,
+) -> Result<StatusCode, StatusCode> {
+    match app_state
+        .social_service
+        .delete_scheduled_post(claims.sub, scheduled_post_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to delete scheduled post: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Publishes a draft or scheduled post now instead of at its publish time
+pub async fn publish_scheduled_post(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(scheduled_post_id): Path<i32>,
+) -> Result<(StatusCode, Json<FeedPost>), StatusCode> {
+    let result = async {
+        let Some(post) = app_state
+            .social_service
+            .publish_scheduled_post(claims.sub, scheduled_post_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        super::posts::hydrate_own_post(&app_state, claims.sub, post)
+            .await
+            .map(Some)
+    }
+    .await;
+
+    match result {
+        Ok(Some(post)) => Ok((StatusCode::CREATED, Json(post))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to publish scheduled post: {}", e);
+            Err(super::error_status(&e))
+        }
+    }
+}
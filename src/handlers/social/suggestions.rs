@@ -0,0 +1,59 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    Extension,
+};
+use serde::Deserialize;
+
+use crate::{
+    db::models::FollowSuggestion, handlers::models::Claims,
+    service::suggestions::DEFAULT_SUGGESTION_PAGE_SIZE, AppState,
+};
+
+#[derive(Deserialize)]
+pub struct SuggestionQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    DEFAULT_SUGGESTION_PAGE_SIZE
+}
+
+pub async fn get_suggestions(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<SuggestionQuery>,
+) -> Result<Json<Vec<FollowSuggestion>>, StatusCode> {
+    match app_state
+        .social_service
+        .get_follow_suggestions(claims.sub, query.limit)
+        .await
+    {
+        Ok(suggestions) => Ok(Json(suggestions)),
+        Err(e) => {
+            eprintln!("Failed to get follow suggestions: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn dismiss_suggestion(
+    State(app_state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(user_id): Path<i32>,
+) -> Result<StatusCode, StatusCode> {
+    match app_state
+        .social_service
+        .dismiss_suggestion(claims.sub, user_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Failed to dismiss suggestion: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
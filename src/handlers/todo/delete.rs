@@ -16,7 +16,7 @@ pub async fn handler(
     Extension(user): Extension<ContextUser>,
     Path(id): Path<u64>,
 ) -> impl IntoResponse {
-    match todo_service.delete(user.user_id, id as i32).await {
+    match todo_service.delete(user.user_id, id as i32, None).await {
         Ok(_) => (StatusCode::OK, Json(JsonResponse::Success(true))),
         Err(error) => {
             if matches!(error, service::todo::Error::TodoNotFound) {
@@ -1,13 +1,14 @@
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     Extension, Json,
 };
 
 use crate::{
     handlers::{
         models::{ErrorResponse, JsonResponse},
+        preconditions,
         todo::models::Todo,
     },
     service::{self, jwt::ContextUser},
@@ -18,26 +19,30 @@ pub async fn handler(
     State(AppState { todo_service, .. }): State<AppState>,
     Extension(user): Extension<ContextUser>,
     Path(id): Path<u64>,
-) -> impl IntoResponse {
+) -> Response {
     tracing::info!("TODO'yu getiriliyor: {}", id);
     match todo_service.get(user.user_id, id as i32).await {
         Ok(result) => (
             StatusCode::OK,
+            [(header::ETAG, preconditions::etag(result.version))],
             Json(JsonResponse::Success(Todo::from(result))),
-        ),
+        )
+            .into_response(),
         Err(error) => {
             if matches!(error, service::todo::Error::TodoNotFound) {
                 return (
                     StatusCode::NOT_FOUND,
-                    Json(JsonResponse::Error(ErrorResponse::new_from_str(
+                    Json(JsonResponse::<()>::Error(ErrorResponse::new_from_str(
                         "TODO not found!",
                     ))),
-                );
+                )
+                    .into_response();
             }
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(JsonResponse::Error(ErrorResponse::from_error(error))),
+                Json(JsonResponse::<()>::Error(ErrorResponse::from_error(error))),
             )
+                .into_response()
         }
     }
 }
@@ -1,9 +1,14 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
 
 use crate::{
     handlers::{
         models::{ErrorResponse, JsonResponse},
-        todo::models::Todo,
+        todo::models::{ListTodosQuery, Todo},
     },
     service::jwt::ContextUser,
     AppState,
@@ -12,8 +17,15 @@ use crate::{
 pub async fn handler(
     State(AppState { todo_service, .. }): State<AppState>,
     Extension(user): Extension<ContextUser>,
+    Query(query): Query<ListTodosQuery>,
 ) -> impl IntoResponse {
-    match todo_service.list(user.user_id).await {
+    let result = if query.archived {
+        todo_service.list_archived(user.user_id).await
+    } else {
+        todo_service.list(user.user_id).await
+    };
+
+    match result {
         Ok(result) => (
             StatusCode::OK,
             Json(JsonResponse::Success(
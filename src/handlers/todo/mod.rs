@@ -1,7 +1,15 @@
+pub mod archive;
 pub mod create;
 pub mod delete;
 pub mod get;
+pub mod history;
 pub mod list;
 pub mod models;
 pub mod partial_update;
+pub mod restore;
+pub mod revert;
+pub mod sync;
+pub mod sync_push;
+pub mod trash;
+pub mod unarchive;
 pub mod update;
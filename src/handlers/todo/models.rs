@@ -1,13 +1,20 @@
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-use crate::db::models::{TodoModel, UpdateTodo, UpdateTodoPartial};
+use crate::db::models::{
+    TodoModel, TodoRevisionModel, TodoTombstoneModel, UpdateTodo, UpdateTodoPartial,
+};
+use crate::service::todo_sync::TodoChanges;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Todo {
     pub id: u64,
     pub title: String,
     pub description: String,
+    pub is_archived: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<NaiveDateTime>,
 }
 
 impl From<TodoModel> for Todo {
@@ -16,6 +23,8 @@ impl From<TodoModel> for Todo {
             id: model.id as u64,
             title: model.title,
             description: model.description,
+            is_archived: model.is_archived,
+            deleted_at: model.deleted_at,
         }
     }
 }
@@ -26,10 +35,47 @@ impl From<&TodoModel> for Todo {
             id: model.id as u64,
             title: model.title.clone(),
             description: model.description.clone(),
+            is_archived: model.is_archived,
+            deleted_at: model.deleted_at,
         }
     }
 }
 
+/// A single entry of a todo's change history
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TodoRevision {
+    pub id: u64,
+    pub todo_id: u64,
+    pub actor_id: Option<u64>,
+    pub action: String,
+    /// Changed fields as `{"field": {"from": old, "to": new}}`
+    pub changes: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reverted_to_revision_id: Option<u64>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<TodoRevisionModel> for TodoRevision {
+    fn from(model: TodoRevisionModel) -> Self {
+        Self {
+            id: model.id as u64,
+            todo_id: model.todo_id as u64,
+            actor_id: model.actor_id.map(|id| id as u64),
+            action: model.action,
+            changes: model.changes,
+            reverted_to_revision_id: model.reverted_to_revision_id.map(|id| id as u64),
+            created_at: model.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListTodosQuery {
+    /// Lists archived todos instead of active ones
+    #[serde(default)]
+    pub archived: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateTodoRequest {
     #[validate(length(
@@ -95,3 +141,145 @@ impl From<UpdateTodoRequest> for UpdateTodo {
         }
     }
 }
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SyncQuery {
+    /// Token from a previous sync. Omit it to fetch every todo.
+    pub since: Option<String>,
+}
+
+/// A todo as seen by sync clients, including the version to send back as
+/// `base_version` when pushing changes
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncedTodo {
+    pub id: u64,
+    pub title: String,
+    pub description: String,
+    pub is_archived: bool,
+    pub version: i32,
+    pub updated: NaiveDateTime,
+}
+
+impl From<TodoModel> for SyncedTodo {
+    fn from(model: TodoModel) -> Self {
+        Self {
+            id: model.id as u64,
+            title: model.title,
+            description: model.description,
+            is_archived: model.is_archived,
+            version: model.version,
+            updated: model.updated,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TodoTombstone {
+    pub id: u64,
+    pub deleted_at: NaiveDateTime,
+}
+
+impl From<TodoTombstoneModel> for TodoTombstone {
+    fn from(model: TodoTombstoneModel) -> Self {
+        Self {
+            id: model.todo_id as u64,
+            deleted_at: model.deleted_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncResponse {
+    pub created: Vec<SyncedTodo>,
+    pub updated: Vec<SyncedTodo>,
+    pub deleted: Vec<TodoTombstone>,
+    pub sync_token: String,
+}
+
+impl From<TodoChanges> for SyncResponse {
+    fn from(changes: TodoChanges) -> Self {
+        Self {
+            created: changes.created.into_iter().map(Into::into).collect(),
+            updated: changes.updated.into_iter().map(Into::into).collect(),
+            deleted: changes.deleted.into_iter().map(Into::into).collect(),
+            sync_token: changes.sync_token.to_string(),
+        }
+    }
+}
+
+/// A change made by a client while offline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum SyncChange {
+    Create {
+        /// Client-side identifier, echoed back so the client can map it to the server id
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        client_id: Option<String>,
+        title: String,
+        description: String,
+    },
+    Update {
+        id: u64,
+        /// Version of the todo the client based its change on
+        base_version: i32,
+        title: Option<String>,
+        description: Option<String>,
+    },
+    Delete {
+        id: u64,
+        base_version: i32,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SyncPushRequest {
+    /// Token from the client's last sync. The response carries everything
+    /// that changed since then; omit it to receive every todo.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[validate(length(max = 500, message = "At most 500 changes can be pushed at once"))]
+    pub changes: Vec<SyncChange>,
+}
+
+/// Result of a single pushed change, in the same order as the request
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SyncChangeResult {
+    Applied {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        client_id: Option<String>,
+        id: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        todo: Option<SyncedTodo>,
+    },
+    Conflict {
+        id: u64,
+        server: SyncedTodo,
+    },
+    NotFound {
+        id: u64,
+    },
+    Invalid {
+        message: String,
+    },
+    /// The server failed to apply the change; the client should push it again
+    Failed {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        client_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<u64>,
+        message: String,
+    },
+}
+
+/// Results of the pushed changes, followed by everything that changed since
+/// the request's `since` token (the pushed changes and those of other
+/// devices) and the token for the next sync, as `GET /todos/sync` returns
+/// them. The changes are left out if they could not be read; the client then
+/// syncs with its previous token.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncPushResponse {
+    pub results: Vec<SyncChangeResult>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub changes: Option<SyncResponse>,
+}
@@ -1,7 +1,7 @@
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Extension, Json,
 };
 use validator::Validate;
@@ -9,6 +9,7 @@ use validator::Validate;
 use crate::{
     handlers::{
         models::{ErrorResponse, JsonResponse},
+        preconditions,
         todo::models::PartialUpdateTodoRequest,
     },
     service::{self, jwt::ContextUser},
@@ -16,39 +17,71 @@ use crate::{
 };
 
 pub async fn handler(
-    State(AppState { todo_service, .. }): State<AppState>,
+    State(AppState {
+        todo_service,
+        config,
+        ..
+    }): State<AppState>,
     Extension(user): Extension<ContextUser>,
     Path(id): Path<u64>,
+    headers: HeaderMap,
     Json(request): Json<PartialUpdateTodoRequest>,
-) -> impl IntoResponse {
+) -> Response {
     if let Err(validation_errors) = request.validate() {
         return (
             StatusCode::BAD_REQUEST,
-            Json(JsonResponse::Error(ErrorResponse::new_from_str(&format!(
-                "Validation error: {}",
-                validation_errors
-            )))),
-        );
+            Json(JsonResponse::<()>::Error(ErrorResponse::new_from_str(
+                &format!("Validation error: {}", validation_errors),
+            ))),
+        )
+            .into_response();
     }
 
+    let if_match = match preconditions::if_match_versions(&headers, config.require_if_match) {
+        Ok(if_match) => if_match,
+        Err(status) => {
+            return (
+                status,
+                Json(JsonResponse::<()>::Error(ErrorResponse::new_from_str(
+                    status.canonical_reason().unwrap_or("Precondition failed"),
+                ))),
+            )
+                .into_response();
+        }
+    };
+
     match todo_service
-        .partial_update(user.user_id, id as i32, request.into())
+        .partial_update(user.user_id, id as i32, request.into(), if_match)
         .await
     {
-        Ok(_) => (StatusCode::OK, Json(JsonResponse::Success(true))),
+        Ok(version) => (
+            StatusCode::OK,
+            [(header::ETAG, preconditions::etag(version))],
+            Json(JsonResponse::Success(true)),
+        )
+            .into_response(),
         Err(error) => {
             if matches!(error, service::todo::Error::TodoNotFound) {
                 return (
                     StatusCode::NOT_FOUND,
-                    Json(JsonResponse::Error(ErrorResponse::new_from_str(
+                    Json(JsonResponse::<()>::Error(ErrorResponse::new_from_str(
                         "TODO not found!",
                     ))),
-                );
+                )
+                    .into_response();
+            }
+            if matches!(error, service::todo::Error::PreconditionFailed) {
+                return (
+                    StatusCode::PRECONDITION_FAILED,
+                    Json(JsonResponse::<()>::Error(ErrorResponse::from_error(error))),
+                )
+                    .into_response();
             }
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(JsonResponse::Error(ErrorResponse::from_error(error))),
+                Json(JsonResponse::<()>::Error(ErrorResponse::from_error(error))),
             )
+                .into_response()
         }
     }
 }
@@ -0,0 +1,47 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+
+use crate::{
+    handlers::{
+        models::{ErrorResponse, JsonResponse},
+        todo::models::Todo,
+    },
+    service::{self, jwt::ContextUser},
+    AppState,
+};
+
+pub async fn handler(
+    State(AppState { todo_service, .. }): State<AppState>,
+    Extension(user): Extension<ContextUser>,
+    Path((id, revision_id)): Path<(u64, u64)>,
+) -> impl IntoResponse {
+    match todo_service
+        .revert(user.user_id, id as i32, revision_id as i32)
+        .await
+    {
+        Ok(result) => (
+            StatusCode::OK,
+            Json(JsonResponse::Success(Todo::from(result))),
+        ),
+        Err(service::todo::Error::TodoNotFound) => (
+            StatusCode::NOT_FOUND,
+            Json(JsonResponse::Error(ErrorResponse::new_from_str(
+                "TODO not found!",
+            ))),
+        ),
+        Err(service::todo::Error::RevisionNotFound) => (
+            StatusCode::NOT_FOUND,
+            Json(JsonResponse::Error(ErrorResponse::new_from_str(
+                "Revision not found!",
+            ))),
+        ),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(JsonResponse::Error(ErrorResponse::from_error(error))),
+        ),
+    }
+}
@@ -0,0 +1,43 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+
+use crate::{
+    handlers::{
+        models::{ErrorResponse, JsonResponse},
+        todo::models::{SyncQuery, SyncResponse},
+    },
+    service::{jwt::ContextUser, todo_sync::SyncToken},
+    AppState,
+};
+
+pub async fn handler(
+    State(AppState { todo_service, .. }): State<AppState>,
+    Extension(user): Extension<ContextUser>,
+    Query(query): Query<SyncQuery>,
+) -> impl IntoResponse {
+    let since = match query.since.as_deref().map(str::parse::<SyncToken>) {
+        None => None,
+        Some(Ok(token)) => Some(token),
+        Some(Err(error)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(JsonResponse::Error(ErrorResponse::from_error(error))),
+            );
+        }
+    };
+
+    match todo_service.sync_changes(user.user_id, since).await {
+        Ok(changes) => (
+            StatusCode::OK,
+            Json(JsonResponse::Success(SyncResponse::from(changes))),
+        ),
+        Err(error) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(JsonResponse::Error(ErrorResponse::from_error(error))),
+        ),
+    }
+}
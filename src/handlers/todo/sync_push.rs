@@ -0,0 +1,100 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+use validator::Validate;
+
+use crate::{
+    handlers::{
+        models::{ErrorResponse, JsonResponse},
+        todo::models::{SyncChange, SyncChangeResult, SyncPushRequest, SyncPushResponse},
+    },
+    service::{
+        jwt::ContextUser,
+        todo_sync::{SyncOutcome, SyncToken},
+    },
+    AppState,
+};
+
+pub async fn handler(
+    State(AppState { todo_service, .. }): State<AppState>,
+    Extension(user): Extension<ContextUser>,
+    Json(request): Json<SyncPushRequest>,
+) -> impl IntoResponse {
+    if let Err(validation_errors) = request.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(JsonResponse::Error(ErrorResponse::new_from_str(&format!(
+                "Validation error: {}",
+                validation_errors
+            )))),
+        );
+    }
+
+    let since = match request.since.as_deref().map(str::parse::<SyncToken>) {
+        None => None,
+        Some(Ok(token)) => Some(token),
+        Some(Err(error)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(JsonResponse::Error(ErrorResponse::from_error(error))),
+            );
+        }
+    };
+
+    // Every change commits on its own, so a failure is reported for that
+    // change alone and the rest of the batch still goes through
+    let mut results = Vec::with_capacity(request.changes.len());
+    for change in request.changes {
+        let (id, client_id) = match &change {
+            SyncChange::Create { client_id, .. } => (None, client_id.clone()),
+            SyncChange::Update { id, .. } | SyncChange::Delete { id, .. } => (Some(*id), None),
+        };
+
+        let outcome = match todo_service.apply_sync_change(user.user_id, change).await {
+            Ok(outcome) => outcome,
+            Err(error) => {
+                tracing::error!(error = %error, todo_id = ?id, "Failed to apply sync change");
+                results.push(SyncChangeResult::Failed {
+                    client_id,
+                    id,
+                    message: "The change could not be applied, push it again later".to_string(),
+                });
+                continue;
+            }
+        };
+
+        results.push(match outcome {
+            SyncOutcome::Applied(todo) => SyncChangeResult::Applied {
+                client_id,
+                id: todo
+                    .as_ref()
+                    .map(|todo| todo.id as u64)
+                    .or(id)
+                    .unwrap_or_default(),
+                todo: todo.map(Into::into),
+            },
+            SyncOutcome::Conflict(server) => SyncChangeResult::Conflict {
+                id: server.id as u64,
+                server: server.into(),
+            },
+            SyncOutcome::NotFound => SyncChangeResult::NotFound {
+                id: id.unwrap_or_default(),
+            },
+            SyncOutcome::Invalid(message) => SyncChangeResult::Invalid { message },
+        });
+    }
+
+    // Read after the changes are applied, so the token covers them; the
+    // token is the snapshot's oldest in-flight transaction, so changes other
+    // devices are still committing are delivered on the next sync
+    let changes = match todo_service.sync_changes(user.user_id, since).await {
+        Ok(changes) => Some(changes.into()),
+        Err(error) => {
+            tracing::error!(error = %error, "Failed to read todo changes after a push");
+            None
+        }
+    };
+
+    (
+        StatusCode::OK,
+        Json(JsonResponse::Success(SyncPushResponse { results, changes })),
+    )
+}
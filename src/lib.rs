@@ -5,11 +5,15 @@ use crate::error::error_correlation_middleware;
 use crate::monitoring::{error_tracking_middleware, request_metrics_middleware};
 use crate::rate_limiter::{auth_rate_limit_middleware, global_rate_limit_middleware};
 use axum::{
-    extract::{Request, State},
-    http::{self, header::CONTENT_TYPE, HeaderValue, StatusCode},
+    extract::{DefaultBodyLimit, Request, State},
+    http::{
+        self,
+        header::{CONTENT_TYPE, ETAG, IF_MATCH},
+        HeaderValue, StatusCode,
+    },
     middleware::{self, Next},
     response::Response,
-    routing::{get, post},
+    routing::{get, post, put},
     Router,
 };
 use tower_http::{
@@ -24,13 +28,17 @@ pub mod handlers;
 pub mod monitoring;
 pub mod rate_limiter;
 pub mod service;
+pub mod workers;
 
 #[derive(Clone)]
 pub struct AppState {
+    pub config: Arc<config::Config>,
     pub todo_service: Arc<service::todo::Service>,
     pub auth_service: Arc<service::auth::Service>,
     pub jwt_service: Arc<service::jwt::Service>,
     pub social_service: Arc<service::social::SocialService>,
+    pub event_service: Arc<service::events::EventService>,
+    pub media_service: Arc<service::media::MediaService>,
 }
 
 async fn auth_middleware(
@@ -60,10 +68,11 @@ async fn auth_middleware(
 
         let context_user = service::jwt::ContextUser {
             user_id: claims.sub,
-            username: claims.username,
+            username: claims.username.clone(),
         };
 
         req.extensions_mut().insert(context_user);
+        req.extensions_mut().insert(claims);
 
         return Ok(next.run(req).await);
     }
@@ -71,6 +80,29 @@ async fn auth_middleware(
     Err(StatusCode::UNAUTHORIZED)
 }
 
+/// Lets only admins through. Runs after `auth_middleware`, which provides
+/// the claims.
+async fn admin_middleware(
+    State(AppState { social_service, .. }): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let user_id = req
+        .extensions()
+        .get::<handlers::models::Claims>()
+        .map(|claims| claims.sub)
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    match social_service.is_admin(user_id).await {
+        Ok(true) => Ok(next.run(req).await),
+        Ok(false) => Err(StatusCode::FORBIDDEN),
+        Err(e) => {
+            eprintln!("Failed to check admin status: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub fn create_app_router(app_state: AppState) -> Router {
     let origin = std::env::var("FRONTEND_URL").unwrap_or_else(|_| "*".to_string());
     let allowed_origin = match origin.parse::<HeaderValue>() {
@@ -97,16 +129,58 @@ pub fn create_app_router(app_state: AppState) -> Router {
                 .patch(handlers::todo::partial_update::handler)
                 .delete(handlers::todo::delete::handler),
         )
+        .route("/todos/trash", get(handlers::todo::trash::handler))
+        .route(
+            "/todos/sync",
+            get(handlers::todo::sync::handler).post(handlers::todo::sync_push::handler),
+        )
+        .route(
+            "/todos/{id}/restore",
+            post(handlers::todo::restore::handler),
+        )
+        .route(
+            "/todos/{id}/archive",
+            post(handlers::todo::archive::handler).delete(handlers::todo::unarchive::handler),
+        )
+        .route("/todos/{id}/history", get(handlers::todo::history::handler))
+        .route(
+            "/todos/{id}/history/{revision_id}/revert",
+            post(handlers::todo::revert::handler),
+        )
         .route(
             "/posts",
             get(handlers::social::posts::get_feed).post(handlers::social::posts::create_post),
         )
+        .route("/posts/feed", get(handlers::social::posts::get_feed))
+        .route(
+            "/posts/scheduled",
+            get(handlers::social::scheduled_posts::get_scheduled_posts)
+                .post(handlers::social::scheduled_posts::create_scheduled_post),
+        )
+        .route(
+            "/posts/scheduled/{id}",
+            get(handlers::social::scheduled_posts::get_scheduled_post)
+                .put(handlers::social::scheduled_posts::update_scheduled_post)
+                .delete(handlers::social::scheduled_posts::delete_scheduled_post),
+        )
+        .route(
+            "/posts/scheduled/{id}/publish",
+            post(handlers::social::scheduled_posts::publish_scheduled_post),
+        )
         .route(
             "/posts/{id}",
             get(handlers::social::posts::get_post)
                 .put(handlers::social::posts::update_post)
                 .delete(handlers::social::posts::delete_post),
         )
+        .route(
+            "/posts/{id}/thread",
+            get(handlers::social::posts::get_post_thread),
+        )
+        .route(
+            "/posts/{id}/revisions",
+            get(handlers::social::posts::get_post_revisions),
+        )
         .route(
             "/users/{id}/posts",
             get(handlers::social::posts::get_user_posts),
@@ -116,10 +190,41 @@ pub fn create_app_router(app_state: AppState) -> Router {
             post(handlers::social::follows::follow_user)
                 .delete(handlers::social::follows::unfollow_user),
         )
+        .route(
+            "/follow-requests",
+            get(handlers::social::follow_requests::get_follow_requests),
+        )
+        .route(
+            "/follow-requests/{id}/approve",
+            post(handlers::social::follow_requests::approve_follow_request),
+        )
+        .route(
+            "/follow-requests/{id}/deny",
+            post(handlers::social::follow_requests::deny_follow_request),
+        )
+        .route(
+            "/users/{id}/block",
+            post(handlers::social::blocks::block_user)
+                .delete(handlers::social::blocks::unblock_user),
+        )
+        .route(
+            "/users/{id}/mute",
+            post(handlers::social::blocks::mute_user).delete(handlers::social::blocks::unmute_user),
+        )
+        .route("/blocks", get(handlers::social::blocks::get_blocked_users))
+        .route("/mutes", get(handlers::social::blocks::get_muted_users))
         .route(
             "/users/{id}/following-status",
             get(handlers::social::follows::check_following),
         )
+        .route(
+            "/users/{id}/relationship",
+            get(handlers::social::follows::get_relationship),
+        )
+        .route(
+            "/users/relationships",
+            get(handlers::social::follows::get_relationships),
+        )
         .route(
             "/users/{id}/followers",
             get(handlers::social::follows::get_followers),
@@ -128,10 +233,46 @@ pub fn create_app_router(app_state: AppState) -> Router {
             "/users/{id}/following",
             get(handlers::social::follows::get_following),
         )
+        .route(
+            "/users/suggestions",
+            get(handlers::social::suggestions::get_suggestions),
+        )
+        .route(
+            "/users/suggestions/{id}/dismiss",
+            post(handlers::social::suggestions::dismiss_suggestion),
+        )
         .route(
             "/posts/{id}/like",
             post(handlers::social::likes::like_post).delete(handlers::social::likes::unlike_post),
         )
+        .route(
+            "/posts/{id}/repost",
+            post(handlers::social::reposts::repost_post)
+                .delete(handlers::social::reposts::unrepost_post),
+        )
+        .route(
+            "/posts/{id}/poll/votes",
+            post(handlers::social::polls::vote),
+        )
+        .route(
+            "/posts/{id}/bookmark",
+            post(handlers::social::bookmarks::bookmark_post)
+                .delete(handlers::social::bookmarks::remove_bookmark),
+        )
+        .route(
+            "/bookmarks",
+            get(handlers::social::bookmarks::get_bookmarks),
+        )
+        .route(
+            "/bookmarks/collections",
+            get(handlers::social::bookmarks::get_collections)
+                .post(handlers::social::bookmarks::create_collection),
+        )
+        .route(
+            "/bookmarks/collections/{id}",
+            put(handlers::social::bookmarks::update_collection)
+                .delete(handlers::social::bookmarks::delete_collection),
+        )
         .route(
             "/posts/{id}/liked",
             get(handlers::social::likes::check_liked),
@@ -141,16 +282,124 @@ pub fn create_app_router(app_state: AppState) -> Router {
             get(handlers::social::comments::get_post_comments)
                 .post(handlers::social::comments::create_comment),
         )
-        .route("/profile", get(handlers::social::profile::get_my_profile))
+        .route(
+            "/posts/{id}/comments/tree",
+            get(handlers::social::comments::get_comment_tree),
+        )
+        .route(
+            "/comments/{id}",
+            put(handlers::social::comments::update_comment)
+                .delete(handlers::social::comments::delete_comment),
+        )
+        .route(
+            "/comments/{id}/like",
+            post(handlers::social::comments::like_comment)
+                .delete(handlers::social::comments::unlike_comment),
+        )
+        .route(
+            "/hashtags/trending",
+            get(handlers::social::hashtags::get_trending_hashtags),
+        )
+        .route(
+            "/hashtags/{tag}/posts",
+            get(handlers::social::hashtags::get_hashtag_posts),
+        )
+        .route(
+            "/notifications",
+            get(handlers::social::notifications::get_notifications),
+        )
+        .route("/events", get(handlers::events::stream))
+        .route(
+            "/notifications/unread-count",
+            get(handlers::social::notifications::get_unread_count),
+        )
+        .route(
+            "/notifications/read-all",
+            post(handlers::social::notifications::mark_all_read),
+        )
+        .route(
+            "/notifications/{id}/read",
+            post(handlers::social::notifications::mark_read),
+        )
+        .route(
+            "/notifications/preferences",
+            get(handlers::social::notifications::get_preferences)
+                .put(handlers::social::notifications::update_preferences),
+        )
+        .route(
+            "/conversations",
+            get(handlers::social::messages::get_conversations)
+                .post(handlers::social::messages::start_conversation),
+        )
+        .route(
+            "/conversations/unread-count",
+            get(handlers::social::messages::get_unread_count),
+        )
+        .route(
+            "/conversations/{id}",
+            get(handlers::social::messages::get_conversation),
+        )
+        .route(
+            "/conversations/{id}/messages",
+            get(handlers::social::messages::get_messages)
+                .post(handlers::social::messages::send_message),
+        )
+        .route(
+            "/conversations/{id}/read",
+            post(handlers::social::messages::mark_read),
+        )
+        .route(
+            "/profile",
+            get(handlers::social::profile::get_my_profile)
+                .put(handlers::social::profile::update_profile),
+        )
         .route(
             "/users/{id}/profile",
             get(handlers::social::profile::get_profile),
         )
+        .route(
+            "/reports",
+            post(handlers::social::moderation::create_report),
+        )
         .route_layer(middleware::from_fn_with_state(
             app_state.clone(),
             auth_middleware,
         ));
 
+    let admin_routes = Router::new()
+        .route(
+            "/admin/reports",
+            get(handlers::social::moderation::get_reports),
+        )
+        .route(
+            "/admin/reports/{id}/resolve",
+            post(handlers::social::moderation::resolve_report),
+        )
+        .route(
+            "/admin/reports/{id}/dismiss",
+            post(handlers::social::moderation::dismiss_report),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            admin_middleware,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_middleware,
+        ));
+
+    // Uploads get their own body limit instead of the global 1 MiB one
+    let media_upload_limit =
+        app_state.config.media_max_upload_bytes + handlers::media::MULTIPART_OVERHEAD_BYTES;
+    let media_routes = Router::new()
+        .route("/media", post(handlers::media::upload))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_middleware,
+        ))
+        .layer(DefaultBodyLimit::max(media_upload_limit))
+        .layer(RequestBodyLimitLayer::new(media_upload_limit));
+
     let auth_routes = Router::new()
         .route(
             "/auth/register",
@@ -161,18 +410,21 @@ pub fn create_app_router(app_state: AppState) -> Router {
 
     let public_routes = Router::new()
         .merge(auth_routes)
-        .route("/health", get(handlers::health::handler));
+        .route("/health", get(handlers::health::handler))
+        .route("/media/files/{*key}", get(handlers::media::get_file));
 
     Router::new()
         .merge(public_routes)
         .merge(protected_routes)
+        .merge(admin_routes)
+        .layer(RequestBodyLimitLayer::new(1024 * 1024))
+        .merge(media_routes)
         .layer(middleware::from_fn(error_correlation_middleware)) // Add correlation IDs
         .layer(middleware::from_fn(error_tracking_middleware)) // Track error patterns
         .layer(middleware::from_fn(request_metrics_middleware)) // Request metrics and tracing
         .layer(middleware::from_fn(global_rate_limit_middleware)) // Global rate limiting - 100 req/min per IP
         .layer(CompressionLayer::new())
         .layer(TimeoutLayer::new(Duration::from_secs(30)))
-        .layer(RequestBodyLimitLayer::new(1024 * 1024))
         .layer(
             CorsLayer::new()
                 .allow_origin(allowed_origin)
@@ -183,7 +435,8 @@ pub fn create_app_router(app_state: AppState) -> Router {
                     axum::http::Method::PATCH,
                     axum::http::Method::DELETE,
                 ])
-                .allow_headers([CONTENT_TYPE]),
+                .allow_headers([CONTENT_TYPE, IF_MATCH])
+                .expose_headers([ETAG, handlers::social::posts::NEXT_CURSOR_HEADER]),
         )
         .with_state(app_state)
 }
@@ -1,5 +1,5 @@
 use std::sync::Arc;
-use todo_api::{config::Config, create_app_router, db, service, AppState};
+use todo_api::{config::Config, create_app_router, db, service, workers, AppState};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -32,16 +32,41 @@ async fn main() -> anyhow::Result<()> {
         config.hashing_secret_key.clone(),
     )?);
 
-    let social_service = Arc::new(service::social::SocialService::new(db_pool.clone()));
+    let social_service = Arc::new(service::social::SocialService::new(
+        db_pool.clone(),
+        config.media_public_base_url.clone(),
+        Arc::new(config.content_filter.clone()),
+    ));
+    let event_service = Arc::new(service::events::EventService::new(db_pool.clone()));
+    let media_service = Arc::new(service::media::MediaService::new(
+        db_pool.clone(),
+        config.media_storage.build()?,
+        config.media_public_base_url.clone(),
+        config.media_max_upload_bytes,
+    ));
 
     tracing::info!("Tüm servisler başarıyla oluşturuldu.");
 
+    // Background workers
+    workers::spawn_todo_trash_purger(todo_service.clone(), config.todo_trash_retention_days);
+    workers::spawn_timeline_fanout_worker(
+        social_service.clone(),
+        config.timeline_fanout_max_followers,
+    );
+    workers::spawn_event_listener(event_service.clone());
+    workers::spawn_event_pruner(event_service.clone());
+    workers::spawn_media_cleanup(media_service.clone());
+    workers::spawn_scheduled_post_publisher(social_service.clone());
+
     // Create application state
     let app_state = AppState {
+        config: Arc::new(config.clone()),
         todo_service,
         auth_service,
         jwt_service: jwt_service.clone(),
         social_service,
+        event_service,
+        media_service,
     };
 
     // Create router
@@ -0,0 +1,342 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{StatusCode, Url};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Time allowed to open a connection to the S3 endpoint
+pub const S3_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time allowed for a whole S3 request, body included
+pub const S3_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where uploaded files are kept. Keys are relative, `/`-separated paths
+/// such as `12/4f1c…9a.jpg`.
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> Result<()>;
+
+    /// Contents of a blob, or `None` when nothing is stored under the key
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Removes a blob; deleting a missing key is not an error
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidBlobKey;
+
+impl fmt::Display for InvalidBlobKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Invalid blob key")
+    }
+}
+
+impl std::error::Error for InvalidBlobKey {}
+
+/// Accepts relative keys made of ASCII letters, digits, `-`, `_` and `.`
+/// with no empty, `.` or `..` segments, so a key can neither escape the
+/// local root nor need escaping in an S3 URL
+pub fn validate_key(key: &str) -> Result<(), InvalidBlobKey> {
+    if key.is_empty() || key.len() > 255 {
+        return Err(InvalidBlobKey);
+    }
+
+    let valid = key.split('/').all(|segment| {
+        !segment.is_empty()
+            && segment != "."
+            && segment != ".."
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(InvalidBlobKey)
+    }
+}
+
+/// Storage backend selected with `MEDIA_STORAGE`
+#[derive(Debug, Clone)]
+pub enum BlobStoreConfig {
+    Local { root: PathBuf },
+    S3(S3Config),
+}
+
+#[derive(Clone)]
+pub struct S3Config {
+    /// Base URL of the S3-compatible service, e.g. `https://s3.eu-west-1.amazonaws.com`
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+impl fmt::Debug for S3Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("S3Config")
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("region", &self.region)
+            .field("access_key_id", &self.access_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BlobStoreConfig {
+    pub fn from_env() -> Result<Self> {
+        let backend = std::env::var("MEDIA_STORAGE").unwrap_or_else(|_| "local".to_string());
+
+        match backend.as_str() {
+            "local" => Ok(Self::Local {
+                root: std::env::var("MEDIA_LOCAL_DIR")
+                    .unwrap_or_else(|_| "./media".to_string())
+                    .into(),
+            }),
+            "s3" => Ok(Self::S3(S3Config {
+                endpoint: std::env::var("S3_ENDPOINT")
+                    .context("S3_ENDPOINT is required when MEDIA_STORAGE=s3")?,
+                bucket: std::env::var("S3_BUCKET")
+                    .context("S3_BUCKET is required when MEDIA_STORAGE=s3")?,
+                region: std::env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+                access_key_id: std::env::var("S3_ACCESS_KEY_ID")
+                    .context("S3_ACCESS_KEY_ID is required when MEDIA_STORAGE=s3")?,
+                secret_access_key: std::env::var("S3_SECRET_ACCESS_KEY")
+                    .context("S3_SECRET_ACCESS_KEY is required when MEDIA_STORAGE=s3")?,
+            })),
+            other => anyhow::bail!("Invalid MEDIA_STORAGE value: '{}'", other),
+        }
+    }
+
+    pub fn build(&self) -> Result<Box<dyn BlobStore>> {
+        match self {
+            Self::Local { root } => Ok(Box::new(LocalBlobStore::new(root.clone()))),
+            Self::S3(config) => Ok(Box::new(S3BlobStore::new(config.clone())?)),
+        }
+    }
+}
+
+/// Keeps blobs as files below a root directory
+pub struct LocalBlobStore {
+    root: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, data: Vec<u8>, _content_type: &str) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, data)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.path(key)?;
+        match tokio::fs::read(&path).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Talks to any S3-compatible service (AWS, MinIO, R2, …) with path-style
+/// URLs and AWS Signature Version 4
+pub struct S3BlobStore {
+    client: reqwest::Client,
+    config: S3Config,
+    endpoint: Url,
+}
+
+impl S3BlobStore {
+    pub fn new(config: S3Config) -> Result<Self> {
+        let endpoint = Url::parse(&config.endpoint).context("Invalid S3_ENDPOINT value")?;
+        if endpoint.host_str().is_none() {
+            anyhow::bail!("Invalid S3_ENDPOINT value");
+        }
+
+        // A stalled endpoint must not hold up uploads or the cleanup worker
+        let client = reqwest::Client::builder()
+            .connect_timeout(S3_CONNECT_TIMEOUT)
+            .timeout(S3_REQUEST_TIMEOUT)
+            .build()
+            .context("Failed to build the S3 HTTP client")?;
+
+        Ok(Self {
+            client,
+            config,
+            endpoint,
+        })
+    }
+
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        key: &str,
+        body: Vec<u8>,
+        content_type: Option<&str>,
+    ) -> Result<reqwest::Response> {
+        validate_key(key)?;
+
+        let path = format!(
+            "{}/{}/{}",
+            self.endpoint.path().trim_end_matches('/'),
+            self.config.bucket,
+            key
+        );
+        let mut url = self.endpoint.clone();
+        url.set_path(&path);
+
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+
+        let authorization = authorization_header(
+            &SigningRequest {
+                method: method.as_str(),
+                path: &path,
+                host: &host,
+                amz_date: &amz_date,
+                payload_hash: &payload_hash,
+            },
+            &self.config,
+        );
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("x-amz-date", &amz_date)
+            .header("x-amz-content-sha256", &payload_hash)
+            .header(reqwest::header::AUTHORIZATION, authorization);
+        if let Some(content_type) = content_type {
+            request = request.header(reqwest::header::CONTENT_TYPE, content_type);
+        }
+
+        Ok(request.body(body).send().await?)
+    }
+}
+
+#[async_trait]
+impl BlobStore for S3BlobStore {
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> Result<()> {
+        let response = self
+            .send(reqwest::Method::PUT, key, data, Some(content_type))
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("S3 PUT {} failed with {}", key, response.status());
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let response = self
+            .send(reqwest::Method::GET, key, Vec::new(), None)
+            .await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(Some(response.bytes().await?.to_vec())),
+            status => anyhow::bail!("S3 GET {} failed with {}", key, status),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let response = self
+            .send(reqwest::Method::DELETE, key, Vec::new(), None)
+            .await?;
+        if !response.status().is_success() && response.status() != StatusCode::NOT_FOUND {
+            anyhow::bail!("S3 DELETE {} failed with {}", key, response.status());
+        }
+        Ok(())
+    }
+}
+
+/// Parts of a request covered by the signature. Only `host`,
+/// `x-amz-content-sha256` and `x-amz-date` are signed.
+struct SigningRequest<'a> {
+    method: &'a str,
+    path: &'a str,
+    host: &'a str,
+    amz_date: &'a str,
+    payload_hash: &'a str,
+}
+
+const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+
+fn authorization_header(request: &SigningRequest<'_>, config: &S3Config) -> String {
+    let date = &request.amz_date[..8];
+    let scope = format!("{}/{}/s3/aws4_request", date, config.region);
+
+    let canonical_request = format!(
+        "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        request.method,
+        request.path,
+        request.host,
+        request.payload_hash,
+        request.amz_date,
+        SIGNED_HEADERS,
+        request.payload_hash
+    );
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        request.amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = signing_key(&config.secret_access_key, date, &config.region, "s3");
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        config.access_key_id, scope, SIGNED_HEADERS, signature
+    )
+}
+
+/// SigV4 key derivation: `date` is `YYYYMMDD`
+pub fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{}", secret).as_bytes(), date.as_bytes());
+    let key = hmac_sha256(&key, region.as_bytes());
+    let key = hmac_sha256(&key, service.as_bytes());
+    hmac_sha256(&key, b"aws4_request")
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDateTime};
+
+use crate::db::models::{Bookmark, BookmarkEntry, FeedPost};
+
+/// Longest collection name, in characters
+pub const MAX_COLLECTION_NAME_LENGTH: usize = 100;
+
+/// Bookmarks listed per page unless the client asks otherwise
+pub const DEFAULT_BOOKMARK_PAGE_SIZE: i64 = 20;
+
+/// Trimmed collection name, or `None` when it is blank or too long
+pub fn normalize_collection_name(name: &str) -> Option<String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_COLLECTION_NAME_LENGTH {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+/// Pairs a page of bookmarks with their hydrated posts, keeping the page
+/// order. Bookmarks whose post is missing from `posts` become tombstones.
+pub fn assemble(bookmarks: Vec<Bookmark>, posts: Vec<FeedPost>) -> Vec<BookmarkEntry> {
+    let mut posts: HashMap<i32, FeedPost> = posts
+        .into_iter()
+        .map(|post| (post.post.post.id, post))
+        .collect();
+
+    bookmarks
+        .into_iter()
+        .map(|bookmark| {
+            let post = posts.remove(&bookmark.post_id);
+            BookmarkEntry {
+                id: bookmark.id,
+                post_id: bookmark.post_id,
+                collection_id: bookmark.collection_id,
+                created_at: bookmark.created_at,
+                tombstone: post.is_none(),
+                post,
+            }
+        })
+        .collect()
+}
+
+/// Keyset position in a bookmark list, ordered by bookmark time and then
+/// bookmark id, both descending. Serialized as `<unix micros>_<bookmark id>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookmarkCursor {
+    pub created_at: NaiveDateTime,
+    pub id: i32,
+}
+
+impl BookmarkCursor {
+    /// Cursor pointing right after `bookmark`
+    pub fn after(bookmark: &Bookmark) -> Self {
+        Self {
+            created_at: bookmark.created_at,
+            id: bookmark.id,
+        }
+    }
+}
+
+impl fmt::Display for BookmarkCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}_{}",
+            self.created_at.and_utc().timestamp_micros(),
+            self.id
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidBookmarkCursor;
+
+impl fmt::Display for InvalidBookmarkCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Invalid bookmark cursor")
+    }
+}
+
+impl std::error::Error for InvalidBookmarkCursor {}
+
+impl FromStr for BookmarkCursor {
+    type Err = InvalidBookmarkCursor;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (micros, id) = value.split_once('_').ok_or(InvalidBookmarkCursor)?;
+        let created_at = micros
+            .parse::<i64>()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or(InvalidBookmarkCursor)?
+            .naive_utc();
+        let id = id.parse::<i32>().map_err(|_| InvalidBookmarkCursor)?;
+
+        Ok(Self { created_at, id })
+    }
+}
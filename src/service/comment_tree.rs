@@ -0,0 +1,60 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::db::models::{Comment, CommentNode};
+
+/// Deepest reply level a comment tree request may ask for
+pub const MAX_COMMENT_DEPTH: i32 = 10;
+
+/// Assembles the flat rows returned by the comment CTE into a tree.
+///
+/// `rows` must be ordered by depth and then by display order. At most `limit`
+/// top-level comments are kept; the returned flag tells whether any were
+/// dropped. Replies whose parent is not part of the tree are ignored.
+pub fn build_comment_tree(
+    rows: Vec<(Comment, i32)>,
+    liked: &HashSet<i32>,
+    limit: usize,
+) -> (Vec<CommentNode>, bool) {
+    let mut top_level = Vec::new();
+    let mut children: HashMap<i32, Vec<(Comment, i32)>> = HashMap::new();
+    for (comment, depth) in rows {
+        match comment.reply_to_comment_id {
+            Some(parent_id) => children
+                .entry(parent_id)
+                .or_default()
+                .push((comment, depth)),
+            None => top_level.push((comment, depth)),
+        }
+    }
+
+    let has_more = top_level.len() > limit;
+    top_level.truncate(limit);
+
+    let comments = top_level
+        .into_iter()
+        .map(|(comment, depth)| attach(comment, depth, liked, &mut children))
+        .collect();
+
+    (comments, has_more)
+}
+
+fn attach(
+    comment: Comment,
+    depth: i32,
+    liked: &HashSet<i32>,
+    children: &mut HashMap<i32, Vec<(Comment, i32)>>,
+) -> CommentNode {
+    let replies = children
+        .remove(&comment.id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(child, depth)| attach(child, depth, liked, children))
+        .collect();
+
+    CommentNode {
+        is_liked: liked.contains(&comment.id),
+        comment,
+        depth,
+        replies,
+    }
+}
@@ -0,0 +1,103 @@
+use crate::db::models::{EntityKind, PostEntity};
+
+/// Longest username registration accepts
+pub const MAX_USERNAME_LENGTH: usize = 50;
+
+/// Longest hashtag that is indexed
+pub const MAX_HASHTAG_LENGTH: usize = 100;
+
+/// Most distinct usernames resolved, and so notified, per post
+pub const MAX_MENTIONS_PER_POST: usize = 10;
+
+/// Window used for trending hashtags unless the client asks otherwise
+pub const DEFAULT_TRENDING_WINDOW_HOURS: i32 = 24;
+
+/// Widest window trending hashtags can be computed over
+pub const MAX_TRENDING_WINDOW_HOURS: i32 = 24 * 7;
+
+/// Finds `@username` mentions and `#tag` hashtags in post content. A sigil
+/// only counts at the start of the text or after a character that cannot be
+/// part of a word, so `me@example.com` and `C#` are left alone. Offsets are
+/// in characters, `end` exclusive. Hashtags are lowercased and must contain
+/// something other than digits.
+pub fn parse(content: &str) -> Vec<PostEntity> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut entities = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let kind = match chars[i] {
+            '@' => EntityKind::Mention,
+            '#' => EntityKind::Hashtag,
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        if i > 0 && is_word_char(chars[i - 1], kind) {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let mut end = i + 1;
+        while end < chars.len() && is_word_char(chars[end], kind) {
+            end += 1;
+        }
+        let body: String = chars[start + 1..end].iter().collect();
+        let length = end - start - 1;
+
+        let text = match kind {
+            // `@zoë` is not a mention of `zo`
+            EntityKind::Mention
+                if (1..=MAX_USERNAME_LENGTH).contains(&length)
+                    && !chars.get(end).is_some_and(|c| c.is_alphanumeric()) =>
+            {
+                Some(body)
+            }
+            EntityKind::Hashtag
+                if (1..=MAX_HASHTAG_LENGTH).contains(&length)
+                    && !body.chars().all(|c| c.is_ascii_digit()) =>
+            {
+                Some(body.to_lowercase())
+            }
+            _ => None,
+        };
+        if let Some(text) = text {
+            entities.push(PostEntity {
+                kind,
+                start,
+                end,
+                text,
+                user_id: None,
+            });
+        }
+        i = end.max(i + 1);
+    }
+
+    entities
+}
+
+/// Distinct texts of the entities of one kind, in order of first appearance
+pub fn distinct(entities: &[PostEntity], kind: EntityKind) -> Vec<String> {
+    let mut seen = Vec::new();
+    for entity in entities.iter().filter(|entity| entity.kind == kind) {
+        if !seen.contains(&entity.text) {
+            seen.push(entity.text.clone());
+        }
+    }
+    seen
+}
+
+/// Normalizes a tag taken from a URL, with or without its leading `#`
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim_start_matches('#').to_lowercase()
+}
+
+fn is_word_char(c: char, kind: EntityKind) -> bool {
+    match kind {
+        // Usernames are ASCII only
+        EntityKind::Mention => c.is_ascii_alphanumeric() || c == '_',
+        EntityKind::Hashtag => c.is_alphanumeric() || c == '_',
+    }
+}
@@ -0,0 +1,128 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use sqlx::{postgres::PgListener, PgPool};
+use tokio::sync::broadcast;
+
+use crate::db::models::StreamEvent;
+
+/// Postgres channel `notify_stream_event()` publishes on
+pub const STREAM_EVENTS_CHANNEL: &str = "stream_events";
+
+/// Events buffered per subscriber before it is considered lagging
+pub const BUS_CAPACITY: usize = 1024;
+
+/// Events read per page when a client resumes with Last-Event-ID
+pub const REPLAY_LIMIT: i64 = 500;
+
+/// Interval between keep-alive comments on an idle stream
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How long events stay available for replay
+pub const EVENT_RETENTION_HOURS: i32 = 24;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidLastEventId;
+
+impl fmt::Display for InvalidLastEventId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Invalid Last-Event-ID")
+    }
+}
+
+impl std::error::Error for InvalidLastEventId {}
+
+/// Event id a stream resumes after. The `Last-Event-ID` header, which
+/// browsers send on reconnect, wins over the query parameter.
+pub fn resume_after(
+    header: Option<&str>,
+    query: Option<i64>,
+) -> Result<Option<i64>, InvalidLastEventId> {
+    match header {
+        Some(value) => value
+            .trim()
+            .parse::<i64>()
+            .map(Some)
+            .map_err(|_| InvalidLastEventId),
+        None => Ok(query),
+    }
+}
+
+/// Whether a live event belongs on `user_id`'s stream, given the last event
+/// already sent to it
+pub fn is_deliverable(event: &StreamEvent, user_id: i32, last_sent: Option<i64>) -> bool {
+    event.user_id == user_id && last_sent.is_none_or(|id| event.id > id)
+}
+
+/// In-process pub/sub for realtime events. Rows written to `stream_events`
+/// by database triggers reach every instance through `LISTEN/NOTIFY` and are
+/// rebroadcast to the streams connected to it.
+pub struct EventService {
+    pool: PgPool,
+    sender: broadcast::Sender<Arc<StreamEvent>>,
+}
+
+impl EventService {
+    pub fn new(pool: PgPool) -> Self {
+        let (sender, _) = broadcast::channel(BUS_CAPACITY);
+        Self { pool, sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<StreamEvent>> {
+        self.sender.subscribe()
+    }
+
+    /// Forwards notifications from Postgres to local subscribers until the
+    /// listener fails
+    pub async fn listen(&self) -> Result<()> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(STREAM_EVENTS_CHANNEL).await?;
+
+        loop {
+            let notification = listener.recv().await?;
+            match serde_json::from_str::<StreamEvent>(notification.payload()) {
+                // Nobody being connected is not an error
+                Ok(event) => {
+                    let _ = self.sender.send(Arc::new(event));
+                }
+                Err(e) => tracing::warn!(error = %e, "Skipping malformed stream event"),
+            }
+        }
+    }
+
+    /// Up to `REPLAY_LIMIT` events for `user_id` newer than `after_id`,
+    /// oldest first
+    pub async fn replay(&self, user_id: i32, after_id: i64) -> Result<Vec<StreamEvent>> {
+        let events = sqlx::query_as!(
+            StreamEvent,
+            r#"
+            SELECT id, user_id, kind, payload, created_at
+            FROM stream_events
+            WHERE user_id = $1 AND id > $2
+            ORDER BY id ASC
+            LIMIT $3
+            "#,
+            user_id,
+            after_id,
+            REPLAY_LIMIT
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Deletes events older than `retention_hours` and returns how many
+    pub async fn prune(&self, retention_hours: i32) -> Result<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM stream_events WHERE created_at < NOW() - make_interval(hours => $1)",
+            retention_hours
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
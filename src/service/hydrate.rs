@@ -0,0 +1,76 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::db::models::{
+    Comment, CommentWithUser, EntityKind, FeedEntry, FeedPost, Poll, PostMedia, PostWithUser,
+    UserProfile,
+};
+use crate::service::entities;
+
+/// Most comments that can be previewed under a post
+pub const MAX_COMMENT_PREVIEW: i64 = 10;
+
+/// Everything fetched in bulk for one page of posts
+#[derive(Debug, Default)]
+pub struct HydrationData {
+    pub users: HashMap<i32, UserProfile>,
+    /// Posts the viewer has liked
+    pub liked: HashSet<i32>,
+    /// Authors the viewer follows
+    pub following: HashSet<i32>,
+    /// Preview comments per post, already in display order
+    pub comments: HashMap<i32, Vec<Comment>>,
+    /// Mentioned users per post, by username
+    pub mentions: HashMap<i32, HashMap<String, i32>>,
+    /// Attachments per post, in display order
+    pub media: HashMap<i32, Vec<PostMedia>>,
+    /// Polls per post, already redacted for the viewer
+    pub polls: HashMap<i32, Poll>,
+}
+
+/// Joins a page of posts with the data fetched for it, keeping the page order.
+/// Posts and comments whose author could not be loaded are dropped, and so
+/// are mentions of usernames that did not resolve to a user.
+pub fn assemble(entries: Vec<FeedEntry>, mut data: HydrationData) -> Vec<FeedPost> {
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let user = data.users.get(&entry.post.user_id)?.clone();
+            let comments = data
+                .comments
+                .remove(&entry.post.id)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|comment| {
+                    let user = data.users.get(&comment.user_id)?.clone();
+                    Some(CommentWithUser { comment, user })
+                })
+                .collect();
+            let media = data.media.remove(&entry.post.id).unwrap_or_default();
+            let poll = data.polls.remove(&entry.post.id);
+            let mentions = data.mentions.remove(&entry.post.id).unwrap_or_default();
+            let entities = entities::parse(&entry.post.content)
+                .into_iter()
+                .filter_map(|mut entity| {
+                    if entity.kind == EntityKind::Mention {
+                        entity.user_id = Some(*mentions.get(&entity.text)?);
+                    }
+                    Some(entity)
+                })
+                .collect();
+
+            Some(FeedPost {
+                post: PostWithUser {
+                    is_liked: data.liked.contains(&entry.post.id),
+                    is_following_author: data.following.contains(&entry.post.user_id),
+                    post: entry.post,
+                    user,
+                },
+                entities,
+                media,
+                comments,
+                poll,
+                reposted_by: entry.reposted_by,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,406 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use image::codecs::jpeg::JpegEncoder;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits};
+use sqlx::PgPool;
+use thiserror::Error;
+
+use crate::db::models::{Media, NewPostMedia, UploadedMedia};
+use crate::service::blob_store::BlobStore;
+
+/// Upload limit used when `MEDIA_MAX_UPLOAD_BYTES` is not set
+pub const DEFAULT_MAX_UPLOAD_BYTES: usize = 10 * 1024 * 1024;
+
+/// Largest width or height accepted, which bounds decoding memory
+pub const MAX_IMAGE_DIMENSION: u32 = 8192;
+
+/// Bounding box of generated thumbnails
+pub const THUMBNAIL_SIZE: u32 = 320;
+
+const JPEG_QUALITY: u8 = 85;
+
+/// Blurhash components along the x and y axis
+const BLURHASH_COMPONENTS: (u32, u32) = (4, 3);
+
+/// Most attachments a post can carry
+pub const MAX_POST_MEDIA: usize = 4;
+
+/// Longest alt text accepted, in characters
+pub const MAX_ALT_TEXT_LENGTH: usize = 1500;
+
+/// How long an upload may stay unattached before it is deleted
+pub const ORPHAN_GRACE_HOURS: i32 = 24;
+
+/// Stored files removed per cleanup batch
+pub const DELETION_BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Error)]
+pub enum MediaError {
+    #[error("Only JPEG, PNG and WebP images are supported")]
+    UnsupportedType,
+    #[error("File exceeds the upload size limit")]
+    TooLarge,
+    #[error("Image could not be decoded: {0}")]
+    InvalidImage(String),
+    #[error("Media does not exist or belongs to another user")]
+    NotFound,
+    #[error("A post can have at most 4 attachments")]
+    TooManyAttachments,
+    #[error("The same media is attached more than once")]
+    DuplicateAttachment,
+    #[error("Alt text is limited to 1500 characters")]
+    AltTextTooLong,
+    #[error("Image URLs must point at uploaded media")]
+    ExternalUrl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageType {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+impl ImageType {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ImageType::Jpeg => "image/jpeg",
+            ImageType::Png => "image/png",
+            ImageType::Webp => "image/webp",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageType::Jpeg => "jpg",
+            ImageType::Png => "png",
+            ImageType::Webp => "webp",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "jpg" => Some(ImageType::Jpeg),
+            "png" => Some(ImageType::Png),
+            "webp" => Some(ImageType::Webp),
+            _ => None,
+        }
+    }
+
+    fn format(self) -> ImageFormat {
+        match self {
+            ImageType::Jpeg => ImageFormat::Jpeg,
+            ImageType::Png => ImageFormat::Png,
+            ImageType::Webp => ImageFormat::WebP,
+        }
+    }
+}
+
+/// Detects the image type from the file's magic bytes; the client's
+/// Content-Type and file name are never trusted
+pub fn sniff_image_type(data: &[u8]) -> Option<ImageType> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(ImageType::Jpeg)
+    } else if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some(ImageType::Png)
+    } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        Some(ImageType::Webp)
+    } else {
+        None
+    }
+}
+
+/// Content type served for a stored key, derived from its extension
+pub fn content_type_for_key(key: &str) -> Option<&'static str> {
+    let (_, extension) = key.rsplit_once('.')?;
+    ImageType::from_extension(extension).map(ImageType::content_type)
+}
+
+#[derive(Debug)]
+pub struct ProcessedImage {
+    pub image_type: ImageType,
+    pub data: Vec<u8>,
+    pub thumbnail: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub blurhash: String,
+}
+
+/// Validates and normalizes an upload. The image is rotated according to
+/// its EXIF orientation and re-encoded, which drops EXIF and every other
+/// metadata block (GPS position, camera serial, …) from the stored file.
+pub fn process_image(data: &[u8], max_bytes: usize) -> Result<ProcessedImage, MediaError> {
+    if data.len() > max_bytes {
+        return Err(MediaError::TooLarge);
+    }
+    let image_type = sniff_image_type(data).ok_or(MediaError::UnsupportedType)?;
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+
+    let mut reader = ImageReader::with_format(Cursor::new(data), image_type.format());
+    reader.limits(limits);
+
+    let invalid = |e: image::ImageError| MediaError::InvalidImage(e.to_string());
+    let mut decoder = reader.into_decoder().map_err(invalid)?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder).map_err(invalid)?;
+    image.apply_orientation(orientation);
+
+    let thumbnail = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+    // The thumbnail is plenty for a placeholder and much cheaper to scan
+    let (components_x, components_y) = BLURHASH_COMPONENTS;
+    let blurhash = blurhash::encode(
+        components_x,
+        components_y,
+        thumbnail.width(),
+        thumbnail.height(),
+        thumbnail.to_rgba8().as_raw(),
+    )
+    .map_err(|e| MediaError::InvalidImage(e.to_string()))?;
+
+    Ok(ProcessedImage {
+        image_type,
+        data: encode(&image, image_type).map_err(invalid)?,
+        thumbnail: encode(&thumbnail, image_type).map_err(invalid)?,
+        width: image.width(),
+        height: image.height(),
+        blurhash,
+    })
+}
+
+/// Checks the attachments of a new post before they are looked up
+pub fn validate_attachments(attachments: &[NewPostMedia]) -> Result<(), MediaError> {
+    if attachments.len() > MAX_POST_MEDIA {
+        return Err(MediaError::TooManyAttachments);
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    for attachment in attachments {
+        if !seen.insert(attachment.media_id) {
+            return Err(MediaError::DuplicateAttachment);
+        }
+        let alt_text_length = attachment
+            .alt_text
+            .as_deref()
+            .map_or(0, |alt_text| alt_text.chars().count());
+        if alt_text_length > MAX_ALT_TEXT_LENGTH {
+            return Err(MediaError::AltTextTooLong);
+        }
+    }
+
+    Ok(())
+}
+
+fn encode(image: &DynamicImage, image_type: ImageType) -> image::ImageResult<Vec<u8>> {
+    let mut buffer = Vec::new();
+    match image_type {
+        ImageType::Jpeg => {
+            let encoder = JpegEncoder::new_with_quality(&mut buffer, JPEG_QUALITY);
+            image.to_rgb8().write_with_encoder(encoder)?;
+        }
+        ImageType::Png => image.write_to(&mut Cursor::new(&mut buffer), ImageFormat::Png)?,
+        // The WebP encoder only takes 8-bit RGB(A)
+        ImageType::Webp => DynamicImage::ImageRgba8(image.to_rgba8())
+            .write_to(&mut Cursor::new(&mut buffer), ImageFormat::WebP)?,
+    }
+    Ok(buffer)
+}
+
+/// Keys of an upload and its thumbnail, namespaced by owner
+pub fn storage_keys(user_id: i32, image_type: ImageType) -> (String, String) {
+    let name = uuid::Uuid::new_v4().simple().to_string();
+    let extension = image_type.extension();
+    (
+        format!("{}/{}.{}", user_id, name, extension),
+        format!("{}/{}_thumb.{}", user_id, name, extension),
+    )
+}
+
+pub fn public_url(base_url: &str, key: &str) -> String {
+    format!("{}/{}", base_url.trim_end_matches('/'), key)
+}
+
+/// Raw image and avatar URLs are only accepted when they point at media
+/// served from `base_url`, so posts and profiles cannot link elsewhere
+pub fn validate_image_url(base_url: &str, url: Option<&str>) -> Result<(), MediaError> {
+    let Some(url) = url else {
+        return Ok(());
+    };
+    let key = url
+        .strip_prefix(base_url.trim_end_matches('/'))
+        .and_then(|rest| rest.strip_prefix('/'));
+    match key {
+        Some(key) if !key.split('/').any(|part| part.is_empty() || part == "..") => Ok(()),
+        _ => Err(MediaError::ExternalUrl),
+    }
+}
+
+/// Stores uploads in the configured blob store and records them in `media`
+pub struct MediaService {
+    pool: PgPool,
+    store: Box<dyn BlobStore>,
+    public_base_url: String,
+    max_upload_bytes: usize,
+}
+
+impl MediaService {
+    pub fn new(
+        pool: PgPool,
+        store: Box<dyn BlobStore>,
+        public_base_url: String,
+        max_upload_bytes: usize,
+    ) -> Self {
+        Self {
+            pool,
+            store,
+            public_base_url,
+            max_upload_bytes,
+        }
+    }
+
+    pub fn max_upload_bytes(&self) -> usize {
+        self.max_upload_bytes
+    }
+
+    pub async fn upload(&self, user_id: i32, data: Vec<u8>) -> Result<UploadedMedia> {
+        let max_bytes = self.max_upload_bytes;
+        let processed =
+            tokio::task::spawn_blocking(move || process_image(&data, max_bytes)).await??;
+
+        let (storage_key, thumbnail_key) = storage_keys(user_id, processed.image_type);
+        let content_type = processed.image_type.content_type();
+        let size_bytes = processed.data.len() as i64;
+
+        self.store
+            .put(&storage_key, processed.data, content_type)
+            .await?;
+        self.store
+            .put(&thumbnail_key, processed.thumbnail, content_type)
+            .await?;
+
+        let inserted = sqlx::query_as!(
+            Media,
+            r#"
+            INSERT INTO media (user_id, content_type, size_bytes, width, height, storage_key,
+                               thumbnail_key, blurhash)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, user_id, content_type, size_bytes, width, height, storage_key,
+                      thumbnail_key, blurhash, created_at
+            "#,
+            user_id,
+            content_type,
+            size_bytes,
+            processed.width as i32,
+            processed.height as i32,
+            storage_key,
+            thumbnail_key,
+            processed.blurhash
+        )
+        .fetch_one(&self.pool)
+        .await;
+
+        match inserted {
+            Ok(media) => Ok(self.to_uploaded(media)),
+            Err(e) => {
+                self.delete_files(&[storage_key, thumbnail_key]).await;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// An upload owned by `user_id`, for attaching it to a post or profile
+    pub async fn get_owned_media(&self, user_id: i32, media_id: i32) -> Result<Media> {
+        let media = sqlx::query_as!(
+            Media,
+            r#"
+            SELECT id, user_id, content_type, size_bytes, width, height, storage_key,
+                   thumbnail_key, blurhash, created_at
+            FROM media
+            WHERE id = $1 AND user_id = $2
+            "#,
+            media_id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        media.ok_or_else(|| MediaError::NotFound.into())
+    }
+
+    pub async fn read_file(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        self.store.get(key).await
+    }
+
+    /// Best-effort removal of stored files; failures are logged, not returned
+    pub async fn delete_files(&self, keys: &[String]) {
+        for key in keys {
+            if let Err(e) = self.store.delete(key).await {
+                tracing::warn!("Failed to delete media file {}: {}", key, e);
+            }
+        }
+    }
+
+    /// Deletes uploads that were never attached, or whose posts, drafts and
+    /// profiles are gone, once they are older than `grace_hours`. Their files are
+    /// queued in `media_deletions` by a trigger.
+    pub async fn delete_orphans(&self, grace_hours: i32) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM media m
+            WHERE m.created_at < NOW() - make_interval(hours => $1)
+              AND NOT EXISTS (SELECT 1 FROM post_media pm WHERE pm.media_id = m.id)
+              AND NOT EXISTS (SELECT 1 FROM scheduled_post_media sm WHERE sm.media_id = m.id)
+              AND NOT EXISTS (SELECT 1 FROM users u WHERE u.avatar_media_id = m.id)
+            "#,
+            grace_hours
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Removes a batch of queued files from the blob store. Files that fail
+    /// to delete stay queued for the next run.
+    pub async fn process_deletions(&self, batch_size: i64) -> Result<usize> {
+        let keys = sqlx::query_scalar!(
+            "SELECT storage_key FROM media_deletions ORDER BY created_at LIMIT $1",
+            batch_size
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut deleted = Vec::with_capacity(keys.len());
+        for key in keys {
+            match self.store.delete(&key).await {
+                Ok(()) => deleted.push(key),
+                Err(e) => tracing::warn!("Failed to delete media file {}: {}", key, e),
+            }
+        }
+
+        sqlx::query!(
+            "DELETE FROM media_deletions WHERE storage_key = ANY($1)",
+            &deleted
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(deleted.len())
+    }
+
+    pub fn url(&self, key: &str) -> String {
+        public_url(&self.public_base_url, key)
+    }
+
+    pub fn to_uploaded(&self, media: Media) -> UploadedMedia {
+        UploadedMedia {
+            url: self.url(&media.storage_key),
+            thumbnail_url: self.url(&media.thumbnail_key),
+            media,
+        }
+    }
+}
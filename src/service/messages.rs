@@ -0,0 +1,46 @@
+use crate::service::social::SocialError;
+
+/// Most people in one conversation, the creator included
+pub const MAX_CONVERSATION_MEMBERS: usize = 10;
+
+/// Longest message, in characters
+pub const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// Messages listed per page unless the client asks otherwise
+pub const DEFAULT_MESSAGE_PAGE_SIZE: i64 = 50;
+
+/// Conversations listed per page unless the client asks otherwise
+pub const DEFAULT_CONVERSATION_PAGE_SIZE: i64 = 20;
+
+/// The other members of a new conversation: duplicates and the creator are
+/// dropped, and `SocialError::InvalidConversationMembers` is returned when
+/// nobody is left or the conversation would be too large
+pub fn normalize_members(creator_id: i32, member_ids: &[i32]) -> Result<Vec<i32>, SocialError> {
+    let mut members: Vec<i32> = Vec::with_capacity(member_ids.len());
+    for &id in member_ids {
+        if id != creator_id && !members.contains(&id) {
+            members.push(id);
+        }
+    }
+
+    if members.is_empty() || members.len() + 1 > MAX_CONVERSATION_MEMBERS {
+        return Err(SocialError::InvalidConversationMembers);
+    }
+    Ok(members)
+}
+
+/// Key shared by every attempt to start a one-to-one conversation between
+/// the same two users, whichever of them starts it
+pub fn direct_key(user_id: i32, other_id: i32) -> String {
+    format!("{}:{}", user_id.min(other_id), user_id.max(other_id))
+}
+
+/// Trimmed message text, or `SocialError::InvalidMessage` when it is blank
+/// or too long
+pub fn normalize_message(content: &str) -> Result<String, SocialError> {
+    let content = content.trim();
+    if content.is_empty() || content.chars().count() > MAX_MESSAGE_LENGTH {
+        return Err(SocialError::InvalidMessage);
+    }
+    Ok(content.to_string())
+}
@@ -1,4 +1,23 @@
 pub mod auth;
+pub mod blob_store;
+pub mod bookmarks;
+pub mod comment_tree;
+pub mod entities;
+pub mod events;
+pub mod hydrate;
 pub mod jwt;
+pub mod media;
+pub mod messages;
+pub mod moderation;
+pub mod notifications;
+pub mod polls;
+pub mod post_thread;
+pub mod ranking;
+pub mod relationships;
+pub mod scheduled_posts;
 pub mod social;
+pub mod suggestions;
+pub mod timeline;
 pub mod todo;
+pub mod todo_history;
+pub mod todo_sync;
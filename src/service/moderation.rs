@@ -0,0 +1,239 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+/// Longest free-text explanation a report can carry
+pub const MAX_REPORT_DETAILS_LENGTH: usize = 1000;
+
+/// Reports listed per page unless the client asks otherwise
+pub const DEFAULT_REPORT_PAGE_SIZE: i64 = 50;
+
+/// Links whose host is checked against domain denylists. Bare `www.` hosts
+/// count as links too.
+static LINK_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)\b(?:https?://|www\.)[^\s<>"']+"#).unwrap());
+
+/// What happens to content a filter rule matches, from mildest to harshest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterAction {
+    /// Stored and shown to its author only, without telling them
+    ShadowHide,
+    /// Shown to its author only until a moderator dismisses the filter's report
+    Hold,
+    /// Refused with `SocialError::ContentRejected`
+    Reject,
+}
+
+impl FilterAction {
+    /// Value stored in `posts.moderation_state` and `comments.moderation_state`
+    pub fn moderation_state(&self) -> Option<&'static str> {
+        match self {
+            FilterAction::ShadowHide => Some("hidden"),
+            FilterAction::Hold => Some("held"),
+            FilterAction::Reject => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterMatch {
+    pub action: FilterAction,
+    /// Human-readable description of the rule, recorded on held content's report
+    pub rule: String,
+}
+
+/// Automatic check run on post and comment text before it is stored
+pub trait ContentFilter: Send + Sync {
+    /// The harshest rule `text` matches, if any
+    fn check(&self, text: &str) -> Option<FilterMatch>;
+}
+
+/// Rule file format read from `CONTENT_FILTER_PATH`:
+///
+/// ```json
+/// {"rules": [{"action": "reject", "keywords": ["..."], "patterns": ["..."], "domains": ["..."]}]}
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+struct RuleFile {
+    #[serde(default)]
+    rules: Vec<RuleSpec>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RuleSpec {
+    action: FilterAction,
+    /// Whole words or phrases, matched case-insensitively
+    #[serde(default)]
+    keywords: Vec<String>,
+    /// Regular expressions, matched as written
+    #[serde(default)]
+    patterns: Vec<String>,
+    /// Link hosts; a domain also covers its subdomains
+    #[serde(default)]
+    domains: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    action: FilterAction,
+    keywords: Vec<(String, Regex)>,
+    patterns: Vec<Regex>,
+    domains: Vec<String>,
+}
+
+/// Keyword, regex and link-domain lists, each with its own action
+#[derive(Debug, Clone, Default)]
+pub struct RuleFilter {
+    rules: Vec<Rule>,
+}
+
+impl RuleFilter {
+    /// Loads the rules from the file named by `CONTENT_FILTER_PATH`, or an
+    /// empty filter that lets everything through when it is unset
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("CONTENT_FILTER_PATH") {
+            Ok(path) => Self::from_file(Path::new(&path)),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read content filter {}", path.display()))?;
+        Self::from_json(&json).with_context(|| format!("Invalid content filter {}", path.display()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: RuleFile = serde_json::from_str(json)?;
+
+        let rules = file
+            .rules
+            .into_iter()
+            .map(|spec| {
+                let keywords = spec
+                    .keywords
+                    .into_iter()
+                    .filter(|keyword| !keyword.trim().is_empty())
+                    .map(|keyword| {
+                        let regex = Regex::new(&format!(
+                            r"(?i)(?:^|\W){}(?:\W|$)",
+                            regex::escape(keyword.trim())
+                        ))?;
+                        Ok((keyword, regex))
+                    })
+                    .collect::<Result<_>>()?;
+                let patterns = spec
+                    .patterns
+                    .iter()
+                    .map(|pattern| {
+                        Regex::new(pattern).with_context(|| format!("Invalid pattern {}", pattern))
+                    })
+                    .collect::<Result<_>>()?;
+                let domains = spec
+                    .domains
+                    .iter()
+                    .map(|domain| normalize_host(domain))
+                    .filter(|domain| !domain.is_empty())
+                    .collect();
+
+                Ok(Rule {
+                    action: spec.action,
+                    keywords,
+                    patterns,
+                    domains,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+impl ContentFilter for RuleFilter {
+    fn check(&self, text: &str) -> Option<FilterMatch> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        let hosts = extract_domains(text);
+
+        let mut harshest: Option<FilterMatch> = None;
+        for rule in &self.rules {
+            if harshest
+                .as_ref()
+                .is_some_and(|found| found.action >= rule.action)
+            {
+                continue;
+            }
+
+            let matched = rule
+                .keywords
+                .iter()
+                .find(|(_, regex)| regex.is_match(text))
+                .map(|(keyword, _)| format!("keyword \"{}\"", keyword))
+                .or_else(|| {
+                    rule.patterns
+                        .iter()
+                        .find(|regex| regex.is_match(text))
+                        .map(|regex| format!("pattern /{}/", regex.as_str()))
+                })
+                .or_else(|| {
+                    rule.domains
+                        .iter()
+                        .find(|domain| hosts.iter().any(|host| host_matches(host, domain)))
+                        .map(|domain| format!("domain {}", domain))
+                });
+
+            if let Some(rule_description) = matched {
+                harshest = Some(FilterMatch {
+                    action: rule.action,
+                    rule: rule_description,
+                });
+            }
+        }
+
+        harshest
+    }
+}
+
+/// Lowercased hosts of the links in `text`, in order of appearance
+pub fn extract_domains(text: &str) -> Vec<String> {
+    let mut hosts = Vec::new();
+    for link in LINK_PATTERN.find_iter(text) {
+        let link = link
+            .as_str()
+            .trim_end_matches(['.', ',', ';', ':', '!', '?', ')']);
+        let url = if link.to_ascii_lowercase().starts_with("www.") {
+            Url::parse(&format!("http://{}", link))
+        } else {
+            Url::parse(link)
+        };
+        if let Some(host) = url.ok().as_ref().and_then(Url::host_str) {
+            let host = normalize_host(host);
+            if !hosts.contains(&host) {
+                hosts.push(host);
+            }
+        }
+    }
+    hosts
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// `host` is `domain` or one of its subdomains
+fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDateTime};
+
+use crate::db::models::{NotificationKind, NotificationPreference};
+
+/// Actors returned with each notification
+pub const MAX_NOTIFICATION_ACTORS: i64 = 3;
+
+/// Key under which unread notifications of the same kind and target are
+/// grouped. Follows and follow requests are grouped regardless of target.
+pub fn group_key(kind: NotificationKind, post_id: Option<i32>, comment_id: Option<i32>) -> String {
+    match kind {
+        NotificationKind::Follow | NotificationKind::FollowRequest => kind.as_str().to_string(),
+        _ => format!(
+            "{}:{}:{}",
+            kind.as_str(),
+            post_id.map_or("-".to_string(), |id| id.to_string()),
+            comment_id.map_or("-".to_string(), |id| id.to_string())
+        ),
+    }
+}
+
+/// Display text such as "alice and 3 others liked your post"
+pub fn summary(kind: NotificationKind, actor_name: &str, actor_count: i32) -> String {
+    let actors = match actor_count {
+        ..=1 => actor_name.to_string(),
+        2 => format!("{} and 1 other", actor_name),
+        n => format!("{} and {} others", actor_name, n - 1),
+    };
+    let action = match kind {
+        NotificationKind::Follow => "followed you",
+        NotificationKind::FollowRequest => "requested to follow you",
+        NotificationKind::Like => "liked your post",
+        NotificationKind::Comment => "commented on your post",
+        NotificationKind::Reply => "replied to you",
+        NotificationKind::Repost => "reposted your post",
+        NotificationKind::Mention => "mentioned you",
+    };
+    format!("{} {}", actors, action)
+}
+
+/// Full preference list for a user; kinds without a stored row are enabled
+pub fn resolve_preferences(stored: &[NotificationPreference]) -> Vec<NotificationPreference> {
+    let stored: HashMap<NotificationKind, bool> = stored
+        .iter()
+        .map(|preference| (preference.kind, preference.enabled))
+        .collect();
+
+    NotificationKind::ALL
+        .into_iter()
+        .map(|kind| NotificationPreference {
+            kind,
+            enabled: stored.get(&kind).copied().unwrap_or(true),
+        })
+        .collect()
+}
+
+/// Keyset position in a notification list, ordered by last activity and
+/// then id, both descending. Serialized as `<unix micros>_<notification id>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotificationCursor {
+    pub updated_at: NaiveDateTime,
+    pub id: i32,
+}
+
+impl fmt::Display for NotificationCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}_{}",
+            self.updated_at.and_utc().timestamp_micros(),
+            self.id
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidNotificationCursor;
+
+impl fmt::Display for InvalidNotificationCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Invalid notification cursor")
+    }
+}
+
+impl std::error::Error for InvalidNotificationCursor {}
+
+impl FromStr for NotificationCursor {
+    type Err = InvalidNotificationCursor;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (micros, id) = value.split_once('_').ok_or(InvalidNotificationCursor)?;
+        let updated_at = micros
+            .parse::<i64>()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or(InvalidNotificationCursor)?
+            .naive_utc();
+        let id = id.parse::<i32>().map_err(|_| InvalidNotificationCursor)?;
+
+        Ok(Self { updated_at, id })
+    }
+}
@@ -0,0 +1,51 @@
+use crate::db::models::{NewPoll, Poll};
+use crate::service::social::SocialError;
+
+/// Fewest and most answers a poll can offer
+pub const MIN_POLL_OPTIONS: usize = 2;
+pub const MAX_POLL_OPTIONS: usize = 4;
+
+/// Longest answer, in characters
+pub const MAX_POLL_OPTION_LENGTH: usize = 100;
+
+/// Shortest and longest time a poll can stay open: five minutes to a week
+pub const MIN_POLL_DURATION_MINUTES: i32 = 5;
+pub const MAX_POLL_DURATION_MINUTES: i32 = 7 * 24 * 60;
+
+/// Trimmed answers of a poll, or `SocialError::InvalidPoll` when there are
+/// too few or too many, one is blank, too long or repeated regardless of
+/// case, or the duration is out of range
+pub fn validate(poll: &NewPoll) -> Result<Vec<String>, SocialError> {
+    if !(MIN_POLL_OPTIONS..=MAX_POLL_OPTIONS).contains(&poll.options.len())
+        || !(MIN_POLL_DURATION_MINUTES..=MAX_POLL_DURATION_MINUTES).contains(&poll.duration_minutes)
+    {
+        return Err(SocialError::InvalidPoll);
+    }
+
+    let mut labels: Vec<String> = Vec::with_capacity(poll.options.len());
+    for option in &poll.options {
+        let label = option.trim();
+        if label.is_empty()
+            || label.chars().count() > MAX_POLL_OPTION_LENGTH
+            || labels
+                .iter()
+                .any(|other| other.to_lowercase() == label.to_lowercase())
+        {
+            return Err(SocialError::InvalidPoll);
+        }
+        labels.push(label.to_string());
+    }
+
+    Ok(labels)
+}
+
+/// Per-answer tallies are shown once the viewer voted or the poll closed,
+/// so they cannot sway the vote
+pub fn redact_results(poll: &mut Poll) {
+    if poll.closed || poll.voted_option_id.is_some() {
+        return;
+    }
+    for option in &mut poll.options {
+        option.vote_count = None;
+    }
+}
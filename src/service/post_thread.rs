@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use crate::db::models::{Post, ThreadReply};
+
+/// Deepest reply level a thread request may ask for
+pub const MAX_THREAD_DEPTH: i32 = 10;
+
+/// Assembles the flat rows returned by the reply CTE into a tree below
+/// `parent_id`.
+///
+/// `rows` must be ordered by depth and then by display order. At most `limit`
+/// direct replies are kept; the returned flag tells whether any were dropped.
+/// Rows whose parent is not part of the tree are ignored.
+pub fn build_reply_tree(
+    parent_id: i32,
+    rows: Vec<(Post, i32)>,
+    limit: usize,
+) -> (Vec<ThreadReply>, bool) {
+    let mut children: HashMap<i32, Vec<(Post, i32)>> = HashMap::new();
+    for (post, depth) in rows {
+        if let Some(reply_to) = post.reply_to_post_id {
+            children.entry(reply_to).or_default().push((post, depth));
+        }
+    }
+
+    let mut direct = children.remove(&parent_id).unwrap_or_default();
+    let has_more = direct.len() > limit;
+    direct.truncate(limit);
+
+    let replies = direct
+        .into_iter()
+        .map(|(post, depth)| attach(post, depth, &mut children))
+        .collect();
+
+    (replies, has_more)
+}
+
+fn attach(post: Post, depth: i32, children: &mut HashMap<i32, Vec<(Post, i32)>>) -> ThreadReply {
+    let replies = children
+        .remove(&post.id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(child, depth)| attach(child, depth, children))
+        .collect();
+
+    ThreadReply {
+        post,
+        depth,
+        replies,
+    }
+}
@@ -0,0 +1,170 @@
+use std::cmp::Ordering;
+
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+
+use crate::db::models::Post;
+
+/// Most posts each candidate source contributes to a ranking pass
+pub const CANDIDATES_PER_SOURCE: i64 = 200;
+
+/// Tunable weights of the ranked timeline. Every field can be overridden
+/// with the matching `RANKING_*` environment variable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RankingWeights {
+    /// Hours after which a post's score is halved
+    pub half_life_hours: f64,
+    pub like: f64,
+    pub comment: f64,
+    pub repost: f64,
+    /// Bonus for posts by accounts the viewer follows
+    pub follow: f64,
+    /// Weight of the viewer's past likes and comments on the author's posts
+    pub interaction: f64,
+    /// Bonus for posts by accounts followed by the viewer's follows
+    pub second_degree: f64,
+    /// Bonus for posts picked for their engagement alone
+    pub trending: f64,
+    /// How far back candidates are collected
+    pub candidate_window_hours: i32,
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        Self {
+            half_life_hours: 12.0,
+            like: 1.0,
+            comment: 2.0,
+            repost: 3.0,
+            follow: 2.0,
+            interaction: 1.0,
+            second_degree: 0.5,
+            trending: 0.0,
+            candidate_window_hours: 72,
+        }
+    }
+}
+
+impl RankingWeights {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+
+        Ok(Self {
+            half_life_hours: env_or("RANKING_HALF_LIFE_HOURS", defaults.half_life_hours)?,
+            like: env_or("RANKING_LIKE_WEIGHT", defaults.like)?,
+            comment: env_or("RANKING_COMMENT_WEIGHT", defaults.comment)?,
+            repost: env_or("RANKING_REPOST_WEIGHT", defaults.repost)?,
+            follow: env_or("RANKING_FOLLOW_WEIGHT", defaults.follow)?,
+            interaction: env_or("RANKING_INTERACTION_WEIGHT", defaults.interaction)?,
+            second_degree: env_or("RANKING_SECOND_DEGREE_WEIGHT", defaults.second_degree)?,
+            trending: env_or("RANKING_TRENDING_WEIGHT", defaults.trending)?,
+            candidate_window_hours: env_or(
+                "RANKING_CANDIDATE_WINDOW_HOURS",
+                defaults.candidate_window_hours,
+            )?,
+        })
+    }
+}
+
+fn env_or<T>(name: &str, default: T) -> Result<T>
+where
+    T: std::str::FromStr,
+{
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse::<T>()
+            .ok()
+            .with_context(|| format!("Invalid {} value", name)),
+        Err(_) => Ok(default),
+    }
+}
+
+/// Why a post was considered for the viewer's timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandidateSource {
+    Followed,
+    SecondDegree,
+    Trending,
+}
+
+impl CandidateSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CandidateSource::Followed => "followed",
+            CandidateSource::SecondDegree => "second_degree",
+            CandidateSource::Trending => "trending",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "followed" => Some(CandidateSource::Followed),
+            "second_degree" => Some(CandidateSource::SecondDegree),
+            "trending" => Some(CandidateSource::Trending),
+            _ => None,
+        }
+    }
+}
+
+/// A post competing for a spot in the ranked timeline
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub post: Post,
+    pub source: CandidateSource,
+    /// Posts by the same author the viewer has liked
+    pub liked_author_posts: i64,
+    /// Posts by the same author the viewer has commented on
+    pub commented_author_posts: i64,
+}
+
+/// Scores a candidate: engagement, affinity and source bonus, decayed by age
+pub fn score(candidate: &Candidate, now: NaiveDateTime, weights: &RankingWeights) -> f64 {
+    let post = &candidate.post;
+    let engagement = weights.like * log_count(post.like_count.unwrap_or(0) as i64)
+        + weights.comment * log_count(post.comment_count.unwrap_or(0) as i64)
+        + weights.repost * log_count(post.repost_count.unwrap_or(0) as i64);
+
+    let affinity = weights.interaction
+        * log_count(candidate.liked_author_posts + candidate.commented_author_posts);
+
+    let source = match candidate.source {
+        CandidateSource::Followed => weights.follow,
+        CandidateSource::SecondDegree => weights.second_degree,
+        CandidateSource::Trending => weights.trending,
+    };
+
+    let age_hours = (now - post.created_at).num_seconds().max(0) as f64 / 3600.0;
+    let decay = if weights.half_life_hours > 0.0 {
+        0.5_f64.powf(age_hours / weights.half_life_hours)
+    } else {
+        1.0
+    };
+
+    (1.0 + engagement + affinity + source) * decay
+}
+
+fn log_count(count: i64) -> f64 {
+    (count.max(0) as f64).ln_1p()
+}
+
+/// Orders candidates by descending score. Ties go to the newer post.
+pub fn rank(
+    candidates: Vec<Candidate>,
+    now: NaiveDateTime,
+    weights: &RankingWeights,
+) -> Vec<Candidate> {
+    let mut scored: Vec<(f64, Candidate)> = candidates
+        .into_iter()
+        .map(|candidate| (score(&candidate, now, weights), candidate))
+        .collect();
+
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .partial_cmp(a_score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| b.post.created_at.cmp(&a.post.created_at))
+            .then_with(|| b.post.id.cmp(&a.post.id))
+    });
+
+    scored.into_iter().map(|(_, candidate)| candidate).collect()
+}
@@ -0,0 +1,37 @@
+/// Mutual followers returned, and named in the summary, per relationship
+pub const MAX_MUTUAL_FOLLOWERS_SHOWN: i64 = 2;
+
+/// Most users one batch relationship request can ask about
+pub const MAX_RELATIONSHIP_BATCH: usize = 100;
+
+/// Display text such as "Followed by alice, bob and 12 others", or `None`
+/// without mutual followers. `total` counts all of them, named or not.
+pub fn mutual_followers_summary(names: &[&str], total: i64) -> Option<String> {
+    let others = total - names.len() as i64;
+    let names = match (names, others) {
+        ([], _) => return None,
+        ([name], 0) => name.to_string(),
+        ([rest @ .., last], 0) => format!("{} and {}", rest.join(", "), last),
+        (_, 1) => format!("{} and 1 other", names.join(", ")),
+        (_, n) => format!("{} and {} others", names.join(", "), n),
+    };
+    Some(format!("Followed by {}", names))
+}
+
+/// User ids of a batch request given as `1,2,3`, in order and without
+/// repeats. Returns `None` when an id is malformed or there are none or too
+/// many.
+pub fn parse_user_ids(value: &str) -> Option<Vec<i32>> {
+    let mut ids: Vec<i32> = Vec::new();
+    for part in value.split(',') {
+        let id = part.trim().parse::<i32>().ok()?;
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+
+    if ids.is_empty() || ids.len() > MAX_RELATIONSHIP_BATCH {
+        return None;
+    }
+    Some(ids)
+}
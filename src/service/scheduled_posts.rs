@@ -0,0 +1,31 @@
+use chrono::NaiveDateTime;
+
+use crate::db::models::ScheduledPostStatus;
+use crate::service::{media::MediaError, social::SocialError};
+
+/// Due posts the publishing worker handles per pass
+pub const PUBLISH_BATCH_SIZE: i64 = 100;
+
+/// Drafts listed per page unless the client asks otherwise
+pub const DEFAULT_SCHEDULED_PAGE_SIZE: i64 = 20;
+
+/// Status of a saved draft: scheduled when it has a publish time, which must
+/// lie after `now`, and a draft otherwise. `None` for a publish time that
+/// already passed.
+pub fn status_for(
+    publish_at: Option<NaiveDateTime>,
+    now: NaiveDateTime,
+) -> Option<ScheduledPostStatus> {
+    match publish_at {
+        Some(publish_at) if publish_at <= now => None,
+        Some(_) => Some(ScheduledPostStatus::Scheduled),
+        None => Some(ScheduledPostStatus::Draft),
+    }
+}
+
+/// Whether publishing failed for a reason retrying will not fix, such as a
+/// deleted reply target or rejected content, rather than a database or
+/// network error
+pub fn is_permanent_failure(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<SocialError>().is_some() || error.downcast_ref::<MediaError>().is_some()
+}
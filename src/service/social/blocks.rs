@@ -0,0 +1,182 @@
+use super::{SocialError, SocialService};
+use crate::db::models::*;
+use anyhow::Result;
+
+impl SocialService {
+    /// Blocks a user, dropping follows and follow requests in both
+    /// directions. Returns `None` if the user does not exist.
+    pub async fn block_user(&self, blocker_id: i32, blocked_id: i32) -> Result<Option<Block>> {
+        let mut tx = self.pool.begin().await?;
+
+        let block = sqlx::query_as!(
+            Block,
+            r#"
+            INSERT INTO blocks (blocker_id, blocked_id)
+            SELECT $1, id FROM users WHERE id = $2
+            ON CONFLICT (blocker_id, blocked_id) DO UPDATE SET blocker_id = EXCLUDED.blocker_id
+            RETURNING id, blocker_id, blocked_id, created_at
+            "#,
+            blocker_id,
+            blocked_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if block.is_some() {
+            sqlx::query!(
+                r#"
+                DELETE FROM follows
+                WHERE (follower_id = $1 AND following_id = $2)
+                   OR (follower_id = $2 AND following_id = $1)
+                "#,
+                blocker_id,
+                blocked_id
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query!(
+                r#"
+                DELETE FROM follow_requests
+                WHERE (requester_id = $1 AND target_id = $2)
+                   OR (requester_id = $2 AND target_id = $1)
+                "#,
+                blocker_id,
+                blocked_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(block)
+    }
+
+    pub async fn unblock_user(&self, blocker_id: i32, blocked_id: i32) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM blocks WHERE blocker_id = $1 AND blocked_id = $2",
+            blocker_id,
+            blocked_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_blocked_users(
+        &self,
+        user_id: i32,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<UserProfile>> {
+        let users = sqlx::query_as!(
+            UserProfile,
+            r#"
+            SELECT u.id, u.username, u.display_name, u.bio, u.avatar_url, u.location,
+                   u.website, u.is_verified, u.is_private, u.follower_count, u.following_count,
+                   u.post_count, u.created
+            FROM users u
+            INNER JOIN blocks b ON u.id = b.blocked_id
+            WHERE b.blocker_id = $1
+            ORDER BY b.created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            user_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(users)
+    }
+
+    /// Mutes a user. The muted user is not told and can still follow and
+    /// interact; their posts and reposts just stop showing up in the
+    /// muter's feeds. Returns `None` if the user does not exist.
+    pub async fn mute_user(&self, muter_id: i32, muted_id: i32) -> Result<Option<Mute>> {
+        let mute = sqlx::query_as!(
+            Mute,
+            r#"
+            INSERT INTO mutes (muter_id, muted_id)
+            SELECT $1, id FROM users WHERE id = $2
+            ON CONFLICT (muter_id, muted_id) DO UPDATE SET muter_id = EXCLUDED.muter_id
+            RETURNING id, muter_id, muted_id, created_at
+            "#,
+            muter_id,
+            muted_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(mute)
+    }
+
+    pub async fn unmute_user(&self, muter_id: i32, muted_id: i32) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM mutes WHERE muter_id = $1 AND muted_id = $2",
+            muter_id,
+            muted_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_muted_users(
+        &self,
+        user_id: i32,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<UserProfile>> {
+        let users = sqlx::query_as!(
+            UserProfile,
+            r#"
+            SELECT u.id, u.username, u.display_name, u.bio, u.avatar_url, u.location,
+                   u.website, u.is_verified, u.is_private, u.follower_count, u.following_count,
+                   u.post_count, u.created
+            FROM users u
+            INNER JOIN mutes m ON u.id = m.muted_id
+            WHERE m.muter_id = $1
+            ORDER BY m.created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            user_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(users)
+    }
+
+    /// Whether either user has blocked the other
+    pub async fn is_blocked(&self, user_id: i32, other_id: i32) -> Result<bool> {
+        let blocked = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM blocks
+                WHERE (blocker_id = $1 AND blocked_id = $2)
+                   OR (blocker_id = $2 AND blocked_id = $1)
+            ) as "blocked!"
+            "#,
+            user_id,
+            other_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(blocked)
+    }
+
+    pub(super) async fn ensure_not_blocked(&self, user_id: i32, other_id: i32) -> Result<()> {
+        if self.is_blocked(user_id, other_id).await? {
+            Err(SocialError::Blocked.into())
+        } else {
+            Ok(())
+        }
+    }
+}
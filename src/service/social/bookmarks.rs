@@ -0,0 +1,315 @@
+use super::{SocialError, SocialService};
+use crate::db::models::*;
+use crate::service::bookmarks::{self, BookmarkCursor};
+use anyhow::Result;
+
+impl SocialService {
+    /// Saves a post for the user, filed in `collection_id` if given.
+    /// Bookmarking a saved post again moves it to that collection. Deleted
+    /// posts can still be bookmarked and come back as a tombstone. Returns
+    /// `None` when the post does not exist or the user cannot see it, and a
+    /// flag telling whether the bookmark is new.
+    pub async fn bookmark_post(
+        &self,
+        user_id: i32,
+        post_id: i32,
+        collection_id: Option<i32>,
+    ) -> Result<Option<(BookmarkEntry, bool)>> {
+        if let Some(collection_id) = collection_id {
+            if !self.owns_collection(user_id, collection_id).await? {
+                return Err(SocialError::CollectionNotFound.into());
+            }
+        }
+
+        let post = self.get_post(user_id, post_id).await?;
+        if post.is_none() {
+            // Posts that went away after they were saved, or before, are
+            // kept as tombstones; hidden live posts stay out of reach
+            let tombstone = sqlx::query_scalar!(
+                r#"
+                SELECT p.is_deleted IS TRUE OR EXISTS (
+                    SELECT 1 FROM bookmarks b WHERE b.user_id = $2 AND b.post_id = p.id
+                ) as "tombstone!"
+                FROM posts p
+                WHERE p.id = $1
+                "#,
+                post_id,
+                user_id
+            )
+            .fetch_optional(&self.pool)
+            .await?;
+            if tombstone != Some(true) {
+                return Ok(None);
+            }
+        }
+
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO bookmarks (user_id, post_id, collection_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, post_id) DO UPDATE SET collection_id = EXCLUDED.collection_id
+            RETURNING id, user_id, post_id, collection_id, created_at, (xmax = 0) as "created!"
+            "#,
+            user_id,
+            post_id,
+            collection_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let bookmark = Bookmark {
+            id: row.id,
+            user_id: row.user_id,
+            post_id: row.post_id,
+            collection_id: row.collection_id,
+            created_at: row.created_at,
+        };
+        let posts = match post {
+            Some(post) => {
+                let entry = FeedEntry {
+                    post,
+                    reposted_by: None,
+                };
+                self.hydrate_posts(user_id, vec![entry], 0).await?
+            }
+            None => Vec::new(),
+        };
+        let entry = bookmarks::assemble(vec![bookmark], posts)
+            .pop()
+            .expect("one bookmark in, one entry out");
+
+        Ok(Some((entry, row.created)))
+    }
+
+    pub async fn remove_bookmark(&self, user_id: i32, post_id: i32) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM bookmarks WHERE user_id = $1 AND post_id = $2",
+            user_id,
+            post_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns a page of the user's bookmarks, newest first, starting after
+    /// `cursor` and limited to one collection if given. Bookmarked posts the
+    /// user can no longer see are returned as tombstones. Returns `None` when
+    /// the collection does not belong to the user; the returned cursor is
+    /// `None` on the last page.
+    pub async fn get_bookmarks(
+        &self,
+        user_id: i32,
+        collection_id: Option<i32>,
+        cursor: Option<BookmarkCursor>,
+        limit: i64,
+    ) -> Result<Option<(Vec<BookmarkEntry>, Option<BookmarkCursor>)>> {
+        if let Some(collection_id) = collection_id {
+            if !self.owns_collection(user_id, collection_id).await? {
+                return Ok(None);
+            }
+        }
+        let before_at = cursor.map(|c| c.created_at);
+        let before_id = cursor.map(|c| c.id);
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT b.id, b.user_id, b.post_id, b.collection_id, b.created_at,
+                   (p.is_deleted IS NOT TRUE
+                    AND (p.moderation_state IS NULL OR p.user_id = $1)
+                    AND (p.user_id = $1 OR u.is_private IS NOT TRUE OR EXISTS (
+                        SELECT 1 FROM follows WHERE follower_id = $1 AND following_id = p.user_id
+                    ))
+                    AND NOT EXISTS (
+                        SELECT 1 FROM blocks bl
+                        WHERE (bl.blocker_id = $1 AND bl.blocked_id = p.user_id)
+                           OR (bl.blocker_id = p.user_id AND bl.blocked_id = $1)
+                    )) as "visible!"
+            FROM bookmarks b
+            INNER JOIN posts p ON p.id = b.post_id
+            INNER JOIN users u ON u.id = p.user_id
+            WHERE b.user_id = $1
+              AND ($2::INT4 IS NULL OR b.collection_id = $2)
+              AND ($3::TIMESTAMP IS NULL OR (b.created_at, b.id) < ($3::TIMESTAMP, $4::INT4))
+            ORDER BY b.created_at DESC, b.id DESC
+            LIMIT $5
+            "#,
+            user_id,
+            collection_id,
+            before_at,
+            before_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let visible_ids: Vec<i32> = rows
+            .iter()
+            .filter(|row| row.visible)
+            .map(|row| row.post_id)
+            .collect();
+        let posts = if visible_ids.is_empty() {
+            Vec::new()
+        } else {
+            sqlx::query_as!(
+                Post,
+                r#"
+                SELECT id, user_id, content, image_url, like_count, comment_count, repost_count,
+                       created_at, updated_at, reply_to_post_id, quote_of_post_id, is_deleted,
+                       deleted_at, version, moderation_state, edited_at, revision_count
+                FROM posts
+                WHERE id = ANY($1)
+                "#,
+                &visible_ids
+            )
+            .fetch_all(&self.pool)
+            .await?
+        };
+        let entries = posts
+            .into_iter()
+            .map(|post| FeedEntry {
+                post,
+                reposted_by: None,
+            })
+            .collect();
+        let posts = self.hydrate_posts(user_id, entries, 0).await?;
+
+        let page: Vec<Bookmark> = rows
+            .into_iter()
+            .map(|row| Bookmark {
+                id: row.id,
+                user_id: row.user_id,
+                post_id: row.post_id,
+                collection_id: row.collection_id,
+                created_at: row.created_at,
+            })
+            .collect();
+        let next = match page.last() {
+            Some(last) if page.len() as i64 == limit => Some(BookmarkCursor::after(last)),
+            _ => None,
+        };
+
+        Ok(Some((bookmarks::assemble(page, posts), next)))
+    }
+
+    /// The user's collections by name, with how many bookmarks each holds
+    pub async fn get_bookmark_collections(&self, user_id: i32) -> Result<Vec<BookmarkCollection>> {
+        let collections = sqlx::query_as!(
+            BookmarkCollection,
+            r#"
+            SELECT c.id, c.user_id, c.name,
+                   (SELECT COUNT(*) FROM bookmarks b WHERE b.collection_id = c.id) as "bookmark_count!",
+                   c.created_at
+            FROM bookmark_collections c
+            WHERE c.user_id = $1
+            ORDER BY LOWER(c.name), c.id
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(collections)
+    }
+
+    /// Creates a collection. Names are unique per user regardless of case;
+    /// a duplicate fails with `SocialError::CollectionNameTaken`.
+    pub async fn create_bookmark_collection(
+        &self,
+        user_id: i32,
+        name: &str,
+    ) -> Result<BookmarkCollection> {
+        let name =
+            bookmarks::normalize_collection_name(name).ok_or(SocialError::InvalidCollectionName)?;
+
+        let collection = sqlx::query_as!(
+            BookmarkCollection,
+            r#"
+            INSERT INTO bookmark_collections (user_id, name)
+            VALUES ($1, $2)
+            RETURNING id, user_id, name, 0::INT8 as "bookmark_count!", created_at
+            "#,
+            user_id,
+            name
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(collection_name_error)?;
+
+        Ok(collection)
+    }
+
+    /// Renames one of the user's collections; `None` if it does not exist
+    pub async fn rename_bookmark_collection(
+        &self,
+        user_id: i32,
+        collection_id: i32,
+        name: &str,
+    ) -> Result<Option<BookmarkCollection>> {
+        let name =
+            bookmarks::normalize_collection_name(name).ok_or(SocialError::InvalidCollectionName)?;
+
+        let collection = sqlx::query_as!(
+            BookmarkCollection,
+            r#"
+            UPDATE bookmark_collections c
+            SET name = $3
+            WHERE c.id = $1 AND c.user_id = $2
+            RETURNING c.id, c.user_id, c.name,
+                      (SELECT COUNT(*) FROM bookmarks b WHERE b.collection_id = c.id) as "bookmark_count!",
+                      c.created_at
+            "#,
+            collection_id,
+            user_id,
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(collection_name_error)?;
+
+        Ok(collection)
+    }
+
+    /// Deletes one of the user's collections. Its bookmarks are kept, unfiled.
+    pub async fn delete_bookmark_collection(
+        &self,
+        user_id: i32,
+        collection_id: i32,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM bookmark_collections WHERE id = $1 AND user_id = $2",
+            collection_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn owns_collection(&self, user_id: i32, collection_id: i32) -> Result<bool> {
+        let exists = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM bookmark_collections WHERE id = $1 AND user_id = $2
+            ) as "exists!"
+            "#,
+            collection_id,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists)
+    }
+}
+
+/// Turns a unique violation on a collection name into
+/// `SocialError::CollectionNameTaken`
+fn collection_name_error(error: sqlx::Error) -> anyhow::Error {
+    match error.as_database_error() {
+        Some(db_error) if db_error.is_unique_violation() => SocialError::CollectionNameTaken.into(),
+        _ => error.into(),
+    }
+}
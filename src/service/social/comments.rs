@@ -0,0 +1,353 @@
+use super::{SocialError, SocialService};
+use crate::db::models::*;
+use crate::service::comment_tree;
+use anyhow::Result;
+use std::collections::HashSet;
+
+impl SocialService {
+    /// Comments on a post the user can see. Returns `None` when the post does
+    /// not exist, was deleted or is hidden from the user.
+    pub async fn create_comment(
+        &self,
+        user_id: i32,
+        create_comment: CreateComment,
+    ) -> Result<Option<Comment>> {
+        let screened = self.screen_content(&create_comment.content)?;
+
+        let Some(post) = self.get_post(user_id, create_comment.post_id).await? else {
+            return Ok(None);
+        };
+        let mut parent_author = None;
+        if let Some(reply_to_comment_id) = create_comment.reply_to_comment_id {
+            let author_id = sqlx::query_scalar!(
+                r#"
+                SELECT user_id FROM comments
+                WHERE id = $1 AND post_id = $2 AND (is_deleted IS NULL OR is_deleted = FALSE)
+                  AND (moderation_state IS NULL OR user_id = $3)
+                "#,
+                reply_to_comment_id,
+                create_comment.post_id,
+                user_id
+            )
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(SocialError::ParentCommentNotFound)?;
+            self.ensure_not_blocked(user_id, author_id).await?;
+            parent_author = Some(author_id);
+        }
+
+        let comment = sqlx::query_as!(
+            Comment,
+            r#"
+            INSERT INTO comments (user_id, post_id, content, reply_to_comment_id, moderation_state)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, user_id, post_id, content, like_count, created_at, updated_at,
+                      reply_to_comment_id, is_deleted, deleted_at, version, moderation_state
+            "#,
+            user_id,
+            create_comment.post_id,
+            create_comment.content,
+            create_comment.reply_to_comment_id,
+            screened
+                .as_ref()
+                .and_then(|found| found.action.moderation_state())
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        if let Some(found) = &screened {
+            self.file_filter_report(ReportTarget::Comment, comment.id, found)
+                .await?;
+            return Ok(Some(comment));
+        }
+
+        // A post author replied to under their own post only hears about the reply
+        if let Some(author_id) = parent_author {
+            self.notify(
+                author_id,
+                user_id,
+                NotificationKind::Reply,
+                Some(comment.post_id),
+                comment.reply_to_comment_id,
+            )
+            .await;
+        }
+        if Some(post.user_id) != parent_author {
+            self.notify(
+                post.user_id,
+                user_id,
+                NotificationKind::Comment,
+                Some(comment.post_id),
+                None,
+            )
+            .await;
+        }
+
+        Ok(Some(comment))
+    }
+
+    /// Lists the comments of a post the viewer may see, or `None` if the
+    /// post is missing or hidden from them
+    pub async fn get_post_comments(
+        &self,
+        viewer_id: i32,
+        post_id: i32,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Option<Vec<Comment>>> {
+        if self.get_post(viewer_id, post_id).await?.is_none() {
+            return Ok(None);
+        }
+
+        let comments = sqlx::query_as!(
+            Comment,
+            r#"
+            SELECT id, user_id, post_id, content, like_count, created_at, updated_at,
+                   reply_to_comment_id, is_deleted, deleted_at, version, moderation_state
+            FROM comments c
+            WHERE post_id = $1 AND (is_deleted IS NULL OR is_deleted = FALSE)
+              AND (moderation_state IS NULL OR user_id = $4)
+              AND NOT EXISTS (
+                  SELECT 1 FROM blocks b
+                  WHERE (b.blocker_id = $4 AND b.blocked_id = c.user_id)
+                     OR (b.blocker_id = c.user_id AND b.blocked_id = $4)
+              )
+            ORDER BY created_at ASC
+            LIMIT $2 OFFSET $3
+            "#,
+            post_id,
+            limit,
+            offset,
+            viewer_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Some(comments))
+    }
+
+    /// Lists the comments of a post as a tree. Top-level comments are paged
+    /// oldest first; replies are nested up to `max_depth` levels. Deleted
+    /// comments and comments by blocked users are left out together with
+    /// their replies. Returns `None` if the post is missing or hidden.
+    pub async fn get_comment_tree(
+        &self,
+        viewer_id: i32,
+        post_id: i32,
+        limit: i64,
+        offset: i64,
+        max_depth: i32,
+    ) -> Result<Option<CommentTree>> {
+        if self.get_post(viewer_id, post_id).await?.is_none() {
+            return Ok(None);
+        }
+        let max_depth = max_depth.clamp(1, comment_tree::MAX_COMMENT_DEPTH);
+
+        let rows = sqlx::query!(
+            r#"
+            WITH RECURSIVE visible AS (
+                SELECT c.* FROM comments c
+                WHERE c.post_id = $1 AND (c.is_deleted IS NULL OR c.is_deleted = FALSE)
+                  AND (c.moderation_state IS NULL OR c.user_id = $4)
+                  AND NOT EXISTS (
+                      SELECT 1 FROM blocks b
+                      WHERE (b.blocker_id = $4 AND b.blocked_id = c.user_id)
+                         OR (b.blocker_id = c.user_id AND b.blocked_id = $4)
+                  )
+            ),
+            top_level AS (
+                SELECT * FROM visible
+                WHERE reply_to_comment_id IS NULL
+                ORDER BY created_at ASC, id ASC
+                LIMIT $2 OFFSET $3
+            ),
+            tree AS (
+                SELECT top_level.*, 1 AS depth FROM top_level
+                UNION ALL
+                SELECT v.*, tree.depth + 1 FROM visible v
+                JOIN tree ON v.reply_to_comment_id = tree.id
+                WHERE tree.depth < $5
+            )
+            SELECT id as "id!", user_id as "user_id!", post_id as "post_id!",
+                   content as "content!", like_count, created_at as "created_at!",
+                   updated_at as "updated_at!", reply_to_comment_id, is_deleted, deleted_at,
+                   version as "version!", moderation_state, depth as "depth!"
+            FROM tree
+            ORDER BY depth ASC, created_at ASC, id ASC
+            "#,
+            post_id,
+            limit + 1,
+            offset,
+            viewer_id,
+            max_depth
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let ids: Vec<i32> = rows.iter().map(|row| row.id).collect();
+        let liked: HashSet<i32> = sqlx::query_scalar!(
+            "SELECT comment_id FROM comment_likes WHERE user_id = $1 AND comment_id = ANY($2)",
+            viewer_id,
+            &ids
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .collect();
+
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                let comment = Comment {
+                    id: row.id,
+                    user_id: row.user_id,
+                    post_id: row.post_id,
+                    content: row.content,
+                    like_count: row.like_count,
+                    created_at: row.created_at,
+                    updated_at: row.updated_at,
+                    reply_to_comment_id: row.reply_to_comment_id,
+                    is_deleted: row.is_deleted,
+                    deleted_at: row.deleted_at,
+                    version: row.version,
+                    moderation_state: row.moderation_state,
+                };
+                (comment, row.depth)
+            })
+            .collect();
+        let (comments, has_more) =
+            comment_tree::build_comment_tree(rows, &liked, limit.max(0) as usize);
+
+        Ok(Some(CommentTree { comments, has_more }))
+    }
+
+    /// Updates a comment the user owns. `if_match` works as in `update_post`.
+    pub async fn update_comment(
+        &self,
+        comment_id: i32,
+        user_id: i32,
+        update_comment: UpdateComment,
+        if_match: Option<Vec<i32>>,
+    ) -> Result<Option<Comment>> {
+        let screened = self.screen_content(&update_comment.content)?;
+
+        let comment = sqlx::query_as!(
+            Comment,
+            r#"
+            UPDATE comments
+            SET content = $1, version = version + 1, updated_at = NOW(),
+                moderation_state = CASE
+                    WHEN moderation_state = 'held' THEN moderation_state
+                    ELSE COALESCE($5, moderation_state)
+                END
+            WHERE id = $2 AND user_id = $3 AND (is_deleted IS NULL OR is_deleted = FALSE)
+              AND ($4::INT4[] IS NULL OR version = ANY($4))
+            RETURNING id, user_id, post_id, content, like_count, created_at, updated_at,
+                      reply_to_comment_id, is_deleted, deleted_at, version, moderation_state
+            "#,
+            update_comment.content,
+            comment_id,
+            user_id,
+            if_match.as_deref(),
+            screened
+                .as_ref()
+                .and_then(|found| found.action.moderation_state())
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let (Some(comment), Some(found)) = (&comment, &screened) {
+            self.file_filter_report(ReportTarget::Comment, comment.id, found)
+                .await?;
+        }
+
+        if comment.is_none() && if_match.is_some() {
+            let exists = sqlx::query_scalar!(
+                r#"
+                SELECT EXISTS(
+                    SELECT 1 FROM comments
+                    WHERE id = $1 AND user_id = $2 AND (is_deleted IS NULL OR is_deleted = FALSE)
+                ) as "exists!"
+                "#,
+                comment_id,
+                user_id
+            )
+            .fetch_one(&self.pool)
+            .await?;
+
+            if exists {
+                return Err(SocialError::PreconditionFailed.into());
+            }
+        }
+
+        Ok(comment)
+    }
+
+    pub async fn delete_comment(&self, comment_id: i32, user_id: i32) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE comments
+            SET is_deleted = TRUE, deleted_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND (is_deleted IS NULL OR is_deleted = FALSE)
+            "#,
+            comment_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Likes a live comment on a post the user can see, or returns `None` if
+    /// there is no such comment
+    pub async fn like_comment(&self, user_id: i32, comment_id: i32) -> Result<Option<CommentLike>> {
+        let Some(comment) = sqlx::query!(
+            r#"
+            SELECT user_id, post_id FROM comments
+            WHERE id = $1 AND (is_deleted IS NULL OR is_deleted = FALSE)
+              AND (moderation_state IS NULL OR user_id = $2)
+            "#,
+            comment_id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+        if self.get_post(user_id, comment.post_id).await?.is_none() {
+            return Ok(None);
+        }
+        self.ensure_not_blocked(user_id, comment.user_id).await?;
+
+        let like = sqlx::query_as!(
+            CommentLike,
+            r#"
+            INSERT INTO comment_likes (user_id, comment_id)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id, comment_id) DO NOTHING
+            RETURNING id, user_id, comment_id, created_at
+            "#,
+            user_id,
+            comment_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(SocialError::AlreadyLiked)?;
+
+        Ok(Some(like))
+    }
+
+    pub async fn unlike_comment(&self, user_id: i32, comment_id: i32) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM comment_likes WHERE user_id = $1 AND comment_id = $2",
+            user_id,
+            comment_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,143 @@
+use super::{SocialError, SocialService};
+use crate::db::models::*;
+use anyhow::Result;
+
+impl SocialService {
+    /// Lists pending requests to follow `user_id`, newest first
+    pub async fn get_follow_requests(
+        &self,
+        user_id: i32,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<FollowRequestWithUser>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT r.id as request_id, r.requester_id, r.target_id, r.created_at as requested_at,
+                   u.id, u.username, u.display_name, u.bio, u.avatar_url, u.location,
+                   u.website, u.is_verified, u.is_private, u.follower_count, u.following_count,
+                   u.post_count, u.created
+            FROM follow_requests r
+            INNER JOIN users u ON u.id = r.requester_id
+            WHERE r.target_id = $1
+            ORDER BY r.created_at DESC, r.id DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            user_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| FollowRequestWithUser {
+                request: FollowRequest {
+                    id: row.request_id,
+                    requester_id: row.requester_id,
+                    target_id: row.target_id,
+                    created_at: row.requested_at,
+                },
+                user: UserProfile {
+                    id: row.id,
+                    username: row.username,
+                    display_name: row.display_name,
+                    bio: row.bio,
+                    avatar_url: row.avatar_url,
+                    location: row.location,
+                    website: row.website,
+                    is_verified: row.is_verified,
+                    is_private: row.is_private,
+                    follower_count: row.follower_count,
+                    following_count: row.following_count,
+                    post_count: row.post_count,
+                    created: row.created,
+                },
+            })
+            .collect())
+    }
+
+    /// Turns a pending request addressed to `user_id` into a follow.
+    /// Returns `None` if there is no such request.
+    pub async fn approve_follow_request(
+        &self,
+        user_id: i32,
+        request_id: i32,
+    ) -> Result<Option<Follow>> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(request) = sqlx::query!(
+            r#"
+            DELETE FROM follow_requests
+            WHERE id = $1 AND target_id = $2
+            RETURNING requester_id, target_id
+            "#,
+            request_id,
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let follow = sqlx::query_as!(
+            Follow,
+            r#"
+            INSERT INTO follows (follower_id, following_id)
+            VALUES ($1, $2)
+            ON CONFLICT (follower_id, following_id) DO UPDATE SET follower_id = EXCLUDED.follower_id
+            RETURNING id, follower_id, following_id, created_at
+            "#,
+            request.requester_id,
+            request.target_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(follow))
+    }
+
+    /// Drops a pending request addressed to `user_id`
+    pub async fn deny_follow_request(&self, user_id: i32, request_id: i32) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM follow_requests WHERE id = $1 AND target_id = $2",
+            request_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub(super) async fn is_private(&self, user_id: i32) -> Result<bool> {
+        let is_private = sqlx::query_scalar!("SELECT is_private FROM users WHERE id = $1", user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(is_private.flatten() == Some(true))
+    }
+
+    /// Whether `viewer_id` may see the posts and follow lists of `user_id`:
+    /// always for public accounts, and for private ones only for the owner
+    /// and approved followers
+    pub async fn can_view_user(&self, viewer_id: i32, user_id: i32) -> Result<bool> {
+        if viewer_id == user_id || !self.is_private(user_id).await? {
+            return Ok(true);
+        }
+        self.is_following(viewer_id, user_id).await
+    }
+
+    pub(super) async fn ensure_can_view_user(&self, viewer_id: i32, user_id: i32) -> Result<()> {
+        if self.is_blocked(viewer_id, user_id).await? {
+            return Err(SocialError::Blocked.into());
+        }
+        if self.can_view_user(viewer_id, user_id).await? {
+            Ok(())
+        } else {
+            Err(SocialError::PrivateAccount.into())
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use super::SocialService;
+use crate::db::models::*;
+use crate::service::entities;
+use anyhow::Result;
+use std::collections::HashSet;
+
+impl SocialService {
+    /// Replaces the stored mentions and hashtags of a post with the ones in
+    /// its current content, then notifies newly mentioned users who can see
+    /// the post
+    pub(super) async fn index_post_entities(&self, post: &Post) -> Result<()> {
+        let parsed = entities::parse(&post.content);
+        let mut usernames = entities::distinct(&parsed, EntityKind::Mention);
+        usernames.truncate(entities::MAX_MENTIONS_PER_POST);
+        let tags = entities::distinct(&parsed, EntityKind::Hashtag);
+
+        let mut tx = self.pool.begin().await?;
+
+        let previous: HashSet<i32> = sqlx::query_scalar!(
+            "SELECT user_id FROM post_mentions WHERE post_id = $1",
+            post.id
+        )
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .collect();
+
+        sqlx::query!("DELETE FROM post_mentions WHERE post_id = $1", post.id)
+            .execute(&mut *tx)
+            .await?;
+
+        let mentioned = sqlx::query_scalar!(
+            r#"
+            INSERT INTO post_mentions (post_id, user_id)
+            SELECT $1, id FROM users WHERE username = ANY($2)
+            RETURNING user_id
+            "#,
+            post.id,
+            &usernames
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM post_hashtags WHERE post_id = $1", post.id)
+            .execute(&mut *tx)
+            .await?;
+
+        if !tags.is_empty() {
+            sqlx::query!(
+                r#"
+                INSERT INTO hashtags (tag)
+                SELECT UNNEST($1::VARCHAR[])
+                ON CONFLICT (tag) DO NOTHING
+                "#,
+                &tags
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query!(
+                r#"
+                INSERT INTO post_hashtags (post_id, hashtag_id, created_at)
+                SELECT $1, id, $3 FROM hashtags WHERE tag = ANY($2)
+                "#,
+                post.id,
+                &tags,
+                post.created_at
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        for user_id in mentioned {
+            if previous.contains(&user_id) || self.get_post(user_id, post.id).await?.is_none() {
+                continue;
+            }
+            self.notify(
+                user_id,
+                post.user_id,
+                NotificationKind::Mention,
+                Some(post.id),
+                None,
+            )
+            .await;
+        }
+
+        Ok(())
+    }
+
+    /// Lists live posts tagged with `tag` that the viewer may see, newest first
+    pub async fn get_hashtag_posts(
+        &self,
+        viewer_id: i32,
+        tag: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Post>> {
+        let posts = sqlx::query_as!(
+            Post,
+            r#"
+            SELECT p.id, p.user_id, p.content, p.image_url, p.like_count, p.comment_count,
+                   p.repost_count, p.created_at, p.updated_at, p.reply_to_post_id,
+                   p.quote_of_post_id, p.is_deleted, p.deleted_at, p.version, p.moderation_state,
+                   p.edited_at, p.revision_count
+            FROM post_hashtags ph
+            INNER JOIN hashtags h ON h.id = ph.hashtag_id
+            INNER JOIN posts p ON p.id = ph.post_id
+            INNER JOIN users u ON u.id = p.user_id
+            WHERE h.tag = $2
+              AND p.is_deleted IS NOT TRUE
+              AND (p.moderation_state IS NULL OR p.user_id = $1)
+              AND (p.user_id = $1 OR u.is_private IS NOT TRUE OR EXISTS (
+                  SELECT 1 FROM follows WHERE follower_id = $1 AND following_id = p.user_id
+              ))
+              AND NOT EXISTS (
+                  SELECT 1 FROM blocks b
+                  WHERE (b.blocker_id = $1 AND b.blocked_id = p.user_id)
+                     OR (b.blocker_id = p.user_id AND b.blocked_id = $1)
+              )
+              AND NOT EXISTS (
+                  SELECT 1 FROM mutes m WHERE m.muter_id = $1 AND m.muted_id = p.user_id
+              )
+            ORDER BY ph.created_at DESC, p.id DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            viewer_id,
+            entities::normalize_tag(tag),
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(posts)
+    }
+
+    /// Most used hashtags on live public posts from the last `window_hours`
+    pub async fn get_trending_hashtags(
+        &self,
+        window_hours: i32,
+        limit: i64,
+    ) -> Result<Vec<TrendingHashtag>> {
+        let window_hours = window_hours.clamp(1, entities::MAX_TRENDING_WINDOW_HOURS);
+
+        let hashtags = sqlx::query_as!(
+            TrendingHashtag,
+            r#"
+            SELECT h.tag, COUNT(*) as "post_count!"
+            FROM post_hashtags ph
+            INNER JOIN hashtags h ON h.id = ph.hashtag_id
+            INNER JOIN posts p ON p.id = ph.post_id
+            INNER JOIN users u ON u.id = p.user_id
+            WHERE ph.created_at >= NOW() - make_interval(hours => $1)
+              AND p.is_deleted IS NOT TRUE
+              AND p.moderation_state IS NULL
+              AND u.is_private IS NOT TRUE
+            GROUP BY h.tag
+            ORDER BY COUNT(*) DESC, h.tag ASC
+            LIMIT $2
+            "#,
+            window_hours,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(hashtags)
+    }
+}
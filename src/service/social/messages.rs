@@ -0,0 +1,486 @@
+use super::{SocialError, SocialService};
+use crate::db::models::*;
+use crate::service::messages;
+use anyhow::Result;
+use sqlx::{Postgres, Transaction};
+use std::collections::HashMap;
+
+impl SocialService {
+    /// Starts a conversation between the user and `member_ids`, optionally
+    /// with a first message, and returns it with a flag telling whether it
+    /// is new. A single member reopens the one-to-one conversation the two
+    /// already share, if any; more members always start a new group.
+    pub async fn start_conversation(
+        &self,
+        user_id: i32,
+        create: CreateConversation,
+    ) -> Result<(Conversation, bool)> {
+        let members = messages::normalize_members(user_id, &create.member_ids)?;
+        let content = create
+            .content
+            .as_deref()
+            .map(messages::normalize_message)
+            .transpose()?;
+        if let Some(content) = &content {
+            self.screen_content(content)?;
+        }
+
+        let existing = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM users WHERE id = ANY($1)"#,
+            &members
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if existing != members.len() as i64 {
+            return Err(SocialError::InvalidConversationMembers.into());
+        }
+
+        let direct_key = match members.as_slice() {
+            [other_id] => Some(messages::direct_key(user_id, *other_id)),
+            _ => None,
+        };
+        let direct_id = match &direct_key {
+            Some(key) => {
+                sqlx::query_scalar!("SELECT id FROM conversations WHERE direct_key = $1", key)
+                    .fetch_optional(&self.pool)
+                    .await?
+            }
+            None => None,
+        };
+        self.ensure_can_message(user_id, &members, direct_id)
+            .await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        // Two users starting the same conversation at once both end up in
+        // the row the first insert created
+        let created_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO conversations (created_by, is_group, direct_key)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (direct_key) DO NOTHING
+            RETURNING id
+            "#,
+            user_id,
+            direct_key.is_none(),
+            direct_key
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let (conversation_id, created) = match created_id {
+            Some(id) => (id, true),
+            None => {
+                let id = sqlx::query_scalar!(
+                    "SELECT id FROM conversations WHERE direct_key = $1",
+                    direct_key
+                )
+                .fetch_one(&mut *tx)
+                .await?;
+                (id, false)
+            }
+        };
+
+        let mut member_ids = members;
+        member_ids.push(user_id);
+        sqlx::query!(
+            r#"
+            INSERT INTO conversation_members (conversation_id, user_id)
+            SELECT $1, UNNEST($2::INT[])
+            ON CONFLICT DO NOTHING
+            "#,
+            conversation_id,
+            &member_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(content) = content {
+            Self::insert_message(&mut tx, conversation_id, user_id, &content).await?;
+        }
+        tx.commit().await?;
+
+        let conversation = self
+            .get_conversation(user_id, conversation_id)
+            .await?
+            .ok_or_else(|| {
+                anyhow::anyhow!("Conversation {} vanished after saving", conversation_id)
+            })?;
+        Ok((conversation, created))
+    }
+
+    /// The user's conversations, most recently active first
+    pub async fn get_conversations(
+        &self,
+        user_id: i32,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Conversation>> {
+        self.load_conversations(user_id, None, limit, offset).await
+    }
+
+    /// Returns `None` when the conversation does not exist or the user is
+    /// not a member
+    pub async fn get_conversation(
+        &self,
+        user_id: i32,
+        conversation_id: i32,
+    ) -> Result<Option<Conversation>> {
+        Ok(self
+            .load_conversations(user_id, Some(conversation_id), 1, 0)
+            .await?
+            .pop())
+    }
+
+    /// Sends a message to a conversation the user is a member of. Returns
+    /// `None` when they are not. The privacy rules of `start_conversation`
+    /// apply to every message, so a block placed later silences the
+    /// conversation.
+    pub async fn send_message(
+        &self,
+        user_id: i32,
+        conversation_id: i32,
+        content: &str,
+    ) -> Result<Option<Message>> {
+        let content = messages::normalize_message(content)?;
+        let Some(others) = self.other_members(user_id, conversation_id).await? else {
+            return Ok(None);
+        };
+        self.screen_content(&content)?;
+        self.ensure_can_message(user_id, &others, Some(conversation_id))
+            .await?;
+
+        let mut tx = self.pool.begin().await?;
+        let message = Self::insert_message(&mut tx, conversation_id, user_id, &content).await?;
+        tx.commit().await?;
+
+        Ok(Some(message))
+    }
+
+    /// Messages of a conversation, newest first, older than the message
+    /// `before` when given. Returns `None` when the user is not a member.
+    pub async fn get_messages(
+        &self,
+        user_id: i32,
+        conversation_id: i32,
+        before: Option<i32>,
+        limit: i64,
+    ) -> Result<Option<Vec<Message>>> {
+        if self
+            .other_members(user_id, conversation_id)
+            .await?
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        let messages = sqlx::query_as!(
+            Message,
+            r#"
+            SELECT id, conversation_id, sender_id, content, created_at
+            FROM messages
+            WHERE conversation_id = $1 AND ($2::INT IS NULL OR id < $2)
+            ORDER BY id DESC
+            LIMIT $3
+            "#,
+            conversation_id,
+            before,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Some(messages))
+    }
+
+    /// Moves the user's read receipt forward to `message_id`, or to the
+    /// newest message without one. Receipts never move backwards. Returns
+    /// `false` when the user is not a member.
+    pub async fn mark_conversation_read(
+        &self,
+        user_id: i32,
+        conversation_id: i32,
+        message_id: Option<i32>,
+    ) -> Result<bool> {
+        if self
+            .other_members(user_id, conversation_id)
+            .await?
+            .is_none()
+        {
+            return Ok(false);
+        }
+
+        let read_up_to = sqlx::query_scalar!(
+            r#"
+            SELECT MAX(id) FROM messages
+            WHERE conversation_id = $1 AND ($2::INT IS NULL OR id = $2)
+            "#,
+            conversation_id,
+            message_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        let Some(read_up_to) = read_up_to else {
+            return match message_id {
+                Some(_) => Err(SocialError::MessageNotFound.into()),
+                None => Ok(true),
+            };
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE conversation_members
+            SET last_read_message_id = GREATEST(COALESCE(last_read_message_id, 0), $3),
+                last_read_at = NOW()
+            WHERE conversation_id = $1 AND user_id = $2
+            "#,
+            conversation_id,
+            user_id,
+            read_up_to
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(true)
+    }
+
+    /// Messages from others the user has not read, across all conversations
+    pub async fn get_unread_message_count(&self, user_id: i32) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!"
+            FROM conversation_members cm
+            INNER JOIN messages m ON m.conversation_id = cm.conversation_id
+            WHERE cm.user_id = $1
+              AND m.id > COALESCE(cm.last_read_message_id, 0)
+              AND m.sender_id IS DISTINCT FROM $1
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Fails with `SocialError::Blocked` when the sender and any recipient
+    /// block each other, and with `SocialError::PrivateAccount` when a
+    /// recipient is private and the sender does not follow them. A private
+    /// recipient who created the conversation or wrote in it already has
+    /// opened it to the other members.
+    async fn ensure_can_message(
+        &self,
+        sender_id: i32,
+        recipient_ids: &[i32],
+        conversation_id: Option<i32>,
+    ) -> Result<()> {
+        let check = sqlx::query!(
+            r#"
+            SELECT
+                EXISTS (
+                    SELECT 1 FROM blocks
+                    WHERE (blocker_id = $1 AND blocked_id = ANY($2))
+                       OR (blocked_id = $1 AND blocker_id = ANY($2))
+                ) as "blocked!",
+                EXISTS (
+                    SELECT 1 FROM users u
+                    WHERE u.id = ANY($2) AND u.is_private IS TRUE
+                      AND NOT EXISTS (
+                          SELECT 1 FROM follows f
+                          WHERE f.follower_id = $1 AND f.following_id = u.id
+                      )
+                      AND NOT EXISTS (
+                          SELECT 1 FROM conversations c
+                          WHERE c.id = $3 AND c.created_by = u.id
+                      )
+                      AND NOT EXISTS (
+                          SELECT 1 FROM messages m
+                          WHERE m.conversation_id = $3 AND m.sender_id = u.id
+                      )
+                ) as "private!"
+            "#,
+            sender_id,
+            recipient_ids,
+            conversation_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        if check.blocked {
+            Err(SocialError::Blocked.into())
+        } else if check.private {
+            Err(SocialError::PrivateAccount.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The other members of a conversation, or `None` when the user is not
+    /// a member of it
+    async fn other_members(&self, user_id: i32, conversation_id: i32) -> Result<Option<Vec<i32>>> {
+        let member_ids = sqlx::query_scalar!(
+            "SELECT user_id FROM conversation_members WHERE conversation_id = $1",
+            conversation_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        if !member_ids.contains(&user_id) {
+            return Ok(None);
+        }
+        Ok(Some(
+            member_ids.into_iter().filter(|&id| id != user_id).collect(),
+        ))
+    }
+
+    /// Stores a message, bumps the conversation in its members' lists and
+    /// marks it read for the sender
+    async fn insert_message(
+        tx: &mut Transaction<'_, Postgres>,
+        conversation_id: i32,
+        sender_id: i32,
+        content: &str,
+    ) -> Result<Message> {
+        let message = sqlx::query_as!(
+            Message,
+            r#"
+            INSERT INTO messages (conversation_id, sender_id, content)
+            VALUES ($1, $2, $3)
+            RETURNING id, conversation_id, sender_id, content, created_at
+            "#,
+            conversation_id,
+            sender_id,
+            content
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE conversations SET last_message_at = $2 WHERE id = $1",
+            conversation_id,
+            message.created_at
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE conversation_members
+            SET last_read_message_id = $3, last_read_at = NOW()
+            WHERE conversation_id = $1 AND user_id = $2
+            "#,
+            conversation_id,
+            sender_id,
+            message.id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(message)
+    }
+
+    /// Conversations of the user with their members, last message and unread
+    /// count, most recently active first. `conversation_id` narrows the list
+    /// to that one conversation.
+    async fn load_conversations(
+        &self,
+        user_id: i32,
+        conversation_id: Option<i32>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Conversation>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT c.id, c.is_group, c.created_at,
+                   (
+                       SELECT COUNT(*) FROM messages m
+                       WHERE m.conversation_id = c.id
+                         AND m.id > COALESCE(cm.last_read_message_id, 0)
+                         AND m.sender_id IS DISTINCT FROM $1
+                   ) as "unread_count!"
+            FROM conversations c
+            INNER JOIN conversation_members cm ON cm.conversation_id = c.id AND cm.user_id = $1
+            WHERE $2::INT IS NULL OR c.id = $2
+            ORDER BY COALESCE(c.last_message_at, c.created_at) DESC, c.id DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            user_id,
+            conversation_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids: Vec<i32> = rows.iter().map(|row| row.id).collect();
+
+        let mut last_messages: HashMap<i32, Message> = sqlx::query_as!(
+            Message,
+            r#"
+            SELECT DISTINCT ON (conversation_id) id, conversation_id, sender_id, content, created_at
+            FROM messages
+            WHERE conversation_id = ANY($1)
+            ORDER BY conversation_id, id DESC
+            "#,
+            &ids
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|message| (message.conversation_id, message))
+        .collect();
+
+        let member_rows = sqlx::query!(
+            r#"
+            SELECT cm.conversation_id, cm.last_read_message_id,
+                   u.id, u.username, u.display_name, u.bio, u.avatar_url, u.location,
+                   u.website, u.is_verified, u.is_private, u.follower_count, u.following_count,
+                   u.post_count, u.created
+            FROM conversation_members cm
+            INNER JOIN users u ON u.id = cm.user_id
+            WHERE cm.conversation_id = ANY($1)
+            ORDER BY cm.conversation_id, cm.joined_at, u.id
+            "#,
+            &ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut members: HashMap<i32, Vec<ConversationMember>> = HashMap::new();
+        for row in member_rows {
+            members
+                .entry(row.conversation_id)
+                .or_default()
+                .push(ConversationMember {
+                    user: UserProfile {
+                        id: row.id,
+                        username: row.username,
+                        display_name: row.display_name,
+                        bio: row.bio,
+                        avatar_url: row.avatar_url,
+                        location: row.location,
+                        website: row.website,
+                        is_verified: row.is_verified,
+                        is_private: row.is_private,
+                        follower_count: row.follower_count,
+                        following_count: row.following_count,
+                        post_count: row.post_count,
+                        created: row.created,
+                    },
+                    last_read_message_id: row.last_read_message_id,
+                });
+        }
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Conversation {
+                id: row.id,
+                is_group: row.is_group,
+                members: members.remove(&row.id).unwrap_or_default(),
+                last_message: last_messages.remove(&row.id),
+                unread_count: row.unread_count,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,1095 @@
+use crate::db::models::*;
+use crate::service::{
+    hydrate,
+    media::{self, MediaError},
+    moderation::{ContentFilter, FilterMatch},
+};
+use anyhow::Result;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::HashSet;
+use std::sync::Arc;
+use thiserror::Error;
+
+mod blocks;
+mod bookmarks;
+mod comments;
+mod follow_requests;
+mod hashtags;
+mod messages;
+mod moderation;
+mod notifications;
+mod polls;
+mod relationships;
+mod reposts;
+mod scheduled_posts;
+mod suggestions;
+mod threads;
+mod timeline;
+
+/// Domain errors raised by `SocialService`. They travel inside `anyhow::Error`
+/// so handlers can downcast them to pick a status code.
+#[derive(Error, Debug)]
+pub enum SocialError {
+    #[error("Resource was modified by another request")]
+    PreconditionFailed,
+    #[error("The post being replied to does not exist or was deleted")]
+    ReplyTargetNotFound,
+    #[error("The quoted post does not exist or was deleted")]
+    QuoteTargetNotFound,
+    #[error("Post was already reposted")]
+    AlreadyReposted,
+    #[error("This account is private")]
+    PrivateAccount,
+    #[error("You cannot interact with this user")]
+    Blocked,
+    #[error("The comment being replied to does not exist, was deleted or belongs to another post")]
+    ParentCommentNotFound,
+    #[error("Comment was already liked")]
+    AlreadyLiked,
+    #[error("Content was rejected by the content filter")]
+    ContentRejected,
+    #[error("You already reported this")]
+    AlreadyReported,
+    #[error("The reported post, comment or user does not exist")]
+    ReportTargetNotFound,
+    #[error("Report details are too long")]
+    ReportDetailsTooLong,
+    #[error("This reason is reserved for the content filter")]
+    InvalidReportReason,
+    #[error("Report was already resolved or dismissed")]
+    ReportClosed,
+    #[error("The post can no longer be edited")]
+    EditWindowClosed,
+    #[error("The bookmark collection does not exist")]
+    CollectionNotFound,
+    #[error("Collection names must be 1 to 100 characters long")]
+    InvalidCollectionName,
+    #[error("A collection with this name already exists")]
+    CollectionNameTaken,
+    #[error("The publish time must be in the future")]
+    PublishTimeInPast,
+    #[error("Polls need 2 to 4 distinct answers and must stay open 5 minutes to 7 days")]
+    InvalidPoll,
+    #[error("The poll is closed")]
+    PollClosed,
+    #[error("You already voted in this poll")]
+    AlreadyVoted,
+    #[error("The answer does not belong to this poll")]
+    InvalidPollOption,
+    #[error("Conversations need 1 to 9 other existing members")]
+    InvalidConversationMembers,
+    #[error("Messages must be 1 to 2000 characters long")]
+    InvalidMessage,
+    #[error("The message does not belong to this conversation")]
+    MessageNotFound,
+}
+
+/// A post written by `insert_post` whose follow-up work waits until the
+/// transaction commits
+struct InsertedPost {
+    post: Post,
+    screened: Option<FilterMatch>,
+    reply_author_id: Option<i32>,
+}
+
+pub struct SocialService {
+    pub pool: PgPool,
+    /// Prefix of media URLs, see `Config::media_public_base_url`
+    media_base_url: String,
+    content_filter: Arc<dyn ContentFilter>,
+}
+
+impl SocialService {
+    pub fn new(
+        pool: PgPool,
+        media_base_url: String,
+        content_filter: Arc<dyn ContentFilter>,
+    ) -> Self {
+        Self {
+            pool,
+            media_base_url,
+            content_filter,
+        }
+    }
+
+    // Posts
+    pub async fn create_post(&self, user_id: i32, create_post: CreatePost) -> Result<Post> {
+        let mut tx = self.pool.begin().await?;
+        let inserted = self.insert_post(&mut tx, user_id, create_post).await?;
+        tx.commit().await?;
+
+        self.announce_post(inserted).await
+    }
+
+    /// Checks a new post and writes it with its attachments and poll in
+    /// `tx`, so callers can commit it together with their own changes. Once
+    /// `tx` has committed, `announce_post` indexes, reports and notifies.
+    async fn insert_post(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        create_post: CreatePost,
+    ) -> Result<InsertedPost> {
+        let screened = self.screen_content(&create_post.content)?;
+
+        // Replies must target a live post and join that post's conversation
+        let reply_target = match create_post.reply_to_post_id {
+            Some(reply_to_post_id) => {
+                let target = sqlx::query!(
+                    r#"
+                    SELECT COALESCE(root_post_id, id) as "root!", user_id
+                    FROM posts
+                    WHERE id = $1 AND (is_deleted IS NULL OR is_deleted = FALSE)
+                    "#,
+                    reply_to_post_id
+                )
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or(SocialError::ReplyTargetNotFound)?;
+
+                self.ensure_not_blocked(user_id, target.user_id).await?;
+                if self.get_post(user_id, reply_to_post_id).await?.is_none() {
+                    return Err(SocialError::ReplyTargetNotFound.into());
+                }
+
+                Some((target.root, target.user_id))
+            }
+            None => None,
+        };
+
+        if let Some(quote_of_post_id) = create_post.quote_of_post_id {
+            if self.get_post(user_id, quote_of_post_id).await?.is_none() {
+                return Err(SocialError::QuoteTargetNotFound.into());
+            }
+        }
+
+        let poll = create_post
+            .poll
+            .as_ref()
+            .map(|poll| {
+                crate::service::polls::validate(poll).map(|labels| (labels, poll.duration_minutes))
+            })
+            .transpose()?;
+
+        media::validate_attachments(&create_post.media)?;
+        media::validate_image_url(&self.media_base_url, create_post.image_url.as_deref())?;
+        let media_ids: Vec<i32> = create_post.media.iter().map(|m| m.media_id).collect();
+        let alt_texts: Vec<Option<String>> = create_post
+            .media
+            .iter()
+            .map(|m| m.alt_text.clone())
+            .collect();
+
+        // Attachments must be the author's own uploads
+        let owned = sqlx::query!(
+            "SELECT id, storage_key FROM media WHERE id = ANY($1) AND user_id = $2",
+            &media_ids,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        if owned.len() != media_ids.len() {
+            return Err(MediaError::NotFound.into());
+        }
+
+        // Clients that only know `image_url` keep seeing the first attachment
+        let image_url = match media_ids.first() {
+            Some(first) => owned
+                .iter()
+                .find(|row| row.id == *first)
+                .map(|row| media::public_url(&self.media_base_url, &row.storage_key)),
+            None => create_post.image_url,
+        };
+
+        let post = sqlx::query_as!(
+            Post,
+            r#"
+            INSERT INTO posts (user_id, content, image_url, reply_to_post_id, root_post_id,
+                               quote_of_post_id, moderation_state)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, user_id, content, image_url, like_count, comment_count, repost_count,
+                      created_at, updated_at, reply_to_post_id, quote_of_post_id, is_deleted, deleted_at, version, moderation_state,
+                      edited_at, revision_count
+            "#,
+            user_id,
+            create_post.content,
+            image_url,
+            create_post.reply_to_post_id,
+            reply_target.map(|(root, _)| root),
+            create_post.quote_of_post_id,
+            screened
+                .as_ref()
+                .and_then(|found| found.action.moderation_state())
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO post_media (post_id, media_id, position, alt_text)
+            SELECT $1, m.media_id, (m.ordinality - 1)::SMALLINT, NULLIF(BTRIM(m.alt_text), '')
+            FROM UNNEST($2::INT4[], $3::TEXT[]) WITH ORDINALITY AS m(media_id, alt_text, ordinality)
+            "#,
+            post.id,
+            &media_ids,
+            &alt_texts as &[Option<String>]
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        if let Some((labels, duration_minutes)) = &poll {
+            let poll_id = sqlx::query_scalar!(
+                r#"
+                INSERT INTO polls (post_id, closes_at)
+                VALUES ($1, NOW() + make_interval(mins => $2))
+                RETURNING id
+                "#,
+                post.id,
+                duration_minutes
+            )
+            .fetch_one(&mut **tx)
+            .await?;
+
+            sqlx::query!(
+                r#"
+                INSERT INTO poll_options (poll_id, position, label)
+                SELECT $1, (o.ordinality - 1)::SMALLINT, o.label
+                FROM UNNEST($2::TEXT[]) WITH ORDINALITY AS o(label, ordinality)
+                "#,
+                poll_id,
+                labels
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        // Update user's post count
+        sqlx::query!(
+            "UPDATE users SET post_count = COALESCE(post_count, 0) + 1 WHERE id = $1",
+            user_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(InsertedPost {
+            post,
+            screened,
+            reply_author_id: reply_target.map(|(_, author_id)| author_id),
+        })
+    }
+
+    /// Follow-up work for a committed post from `insert_post`
+    async fn announce_post(&self, inserted: InsertedPost) -> Result<Post> {
+        let InsertedPost {
+            post,
+            screened,
+            reply_author_id,
+        } = inserted;
+
+        self.index_post_entities(&post).await?;
+
+        if let Some(found) = &screened {
+            self.file_filter_report(ReportTarget::Post, post.id, found)
+                .await?;
+        }
+
+        if let Some(parent_author) = reply_author_id.filter(|_| post.moderation_state.is_none()) {
+            self.notify(
+                parent_author,
+                post.user_id,
+                NotificationKind::Reply,
+                post.reply_to_post_id,
+                None,
+            )
+            .await;
+        }
+
+        Ok(post)
+    }
+
+    /// Returns a post if `viewer_id` may see it. Posts by private accounts
+    /// are only visible to the author and their approved followers, and
+    /// posts are hidden in both directions of a block.
+    pub async fn get_post(&self, viewer_id: i32, post_id: i32) -> Result<Option<Post>> {
+        let post = sqlx::query_as!(
+            Post,
+            r#"
+            SELECT p.id, p.user_id, p.content, p.image_url, p.like_count, p.comment_count,
+                   p.repost_count, p.created_at, p.updated_at, p.reply_to_post_id,
+                   p.quote_of_post_id, p.is_deleted, p.deleted_at, p.version, p.moderation_state,
+                   p.edited_at, p.revision_count
+            FROM posts p
+            INNER JOIN users u ON u.id = p.user_id
+            WHERE p.id = $1 AND (p.is_deleted IS NULL OR p.is_deleted = FALSE)
+              AND (p.moderation_state IS NULL OR p.user_id = $2)
+              AND (p.user_id = $2 OR u.is_private IS NOT TRUE OR EXISTS (
+                  SELECT 1 FROM follows WHERE follower_id = $2 AND following_id = p.user_id
+              ))
+              AND NOT EXISTS (
+                  SELECT 1 FROM blocks b
+                  WHERE (b.blocker_id = $2 AND b.blocked_id = p.user_id)
+                     OR (b.blocker_id = p.user_id AND b.blocked_id = $2)
+              )
+            "#,
+            post_id,
+            viewer_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(post)
+    }
+
+    pub async fn get_user_posts(
+        &self,
+        viewer_id: i32,
+        user_id: i32,
+        limit: i64,
+        offset: i64,
+        replies: ReplyMode,
+    ) -> Result<Vec<Post>> {
+        self.ensure_can_view_user(viewer_id, user_id).await?;
+
+        let posts = if replies == ReplyMode::Collapse {
+            sqlx::query_as!(
+                Post,
+                r#"
+                SELECT id, user_id, content, image_url, like_count, comment_count, repost_count,
+                       created_at, updated_at, reply_to_post_id, quote_of_post_id, is_deleted, deleted_at, version, moderation_state,
+                       edited_at, revision_count
+                FROM (
+                    SELECT DISTINCT ON (COALESCE(root_post_id, id)) *
+                    FROM posts
+                    WHERE user_id = $1 AND (is_deleted IS NULL OR is_deleted = FALSE)
+                      AND (moderation_state IS NULL OR user_id = $4)
+                    ORDER BY COALESCE(root_post_id, id), created_at DESC
+                ) latest
+                ORDER BY created_at DESC
+                LIMIT $2 OFFSET $3
+                "#,
+                user_id,
+                limit,
+                offset,
+                viewer_id
+            )
+            .fetch_all(&self.pool)
+            .await?
+        } else {
+            sqlx::query_as!(
+                Post,
+                r#"
+                SELECT id, user_id, content, image_url, like_count, comment_count, repost_count,
+                       created_at, updated_at, reply_to_post_id, quote_of_post_id, is_deleted, deleted_at, version, moderation_state,
+                       edited_at, revision_count
+                FROM posts
+                WHERE user_id = $1 AND (is_deleted IS NULL OR is_deleted = FALSE)
+                  AND (moderation_state IS NULL OR user_id = $5)
+                  AND (NOT $4 OR reply_to_post_id IS NULL)
+                ORDER BY created_at DESC
+                LIMIT $2 OFFSET $3
+                "#,
+                user_id,
+                limit,
+                offset,
+                replies == ReplyMode::Exclude,
+                viewer_id
+            )
+            .fetch_all(&self.pool)
+            .await?
+        };
+
+        Ok(posts)
+    }
+
+    /// Updates a post the user owns while it is younger than
+    /// `edit_window_minutes`, keeping the replaced version in `post_revisions`.
+    /// Older posts fail with `SocialError::EditWindowClosed`. With `if_match`
+    /// set, the update only applies when the stored version is one of the
+    /// given versions and fails with `SocialError::PreconditionFailed`
+    /// otherwise.
+    pub async fn update_post(
+        &self,
+        post_id: i32,
+        user_id: i32,
+        update_post: UpdatePost,
+        if_match: Option<Vec<i32>>,
+        edit_window_minutes: i32,
+    ) -> Result<Option<Post>> {
+        let screened = match &update_post.content {
+            Some(content) => self.screen_content(content)?,
+            None => None,
+        };
+        media::validate_image_url(&self.media_base_url, update_post.image_url.as_deref())?;
+
+        let mut tx = self.pool.begin().await?;
+        let Some(current) = sqlx::query!(
+            r#"
+            SELECT version, content, image_url,
+                   COALESCE(edited_at, created_at) as "published_at!",
+                   created_at > NOW() - make_interval(mins => $3) as "editable!"
+            FROM posts
+            WHERE id = $1 AND user_id = $2 AND (is_deleted IS NULL OR is_deleted = FALSE)
+            FOR UPDATE
+            "#,
+            post_id,
+            user_id,
+            edit_window_minutes
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        if !current.editable {
+            return Err(SocialError::EditWindowClosed.into());
+        }
+        if if_match
+            .as_ref()
+            .is_some_and(|versions| !versions.contains(&current.version))
+        {
+            return Err(SocialError::PreconditionFailed.into());
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO post_revisions (post_id, version, content, image_url, published_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            post_id,
+            current.version,
+            current.content,
+            current.image_url,
+            current.published_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Edits never lift a moderation state, and held posts stay held
+        let post = sqlx::query_as!(
+            Post,
+            r#"
+            UPDATE posts
+            SET content = COALESCE($1, content),
+                image_url = COALESCE($2, image_url),
+                version = version + 1,
+                updated_at = NOW(),
+                edited_at = NOW(),
+                revision_count = revision_count + 1,
+                moderation_state = CASE
+                    WHEN moderation_state = 'held' THEN moderation_state
+                    ELSE COALESCE($4, moderation_state)
+                END
+            WHERE id = $3
+            RETURNING id, user_id, content, image_url, like_count, comment_count, repost_count,
+                      created_at, updated_at, reply_to_post_id, quote_of_post_id, is_deleted, deleted_at, version, moderation_state,
+                      edited_at, revision_count
+            "#,
+            update_post.content,
+            update_post.image_url,
+            post_id,
+            screened
+                .as_ref()
+                .and_then(|found| found.action.moderation_state())
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        if update_post.content.is_some() {
+            self.index_post_entities(&post).await?;
+        }
+        if let Some(found) = &screened {
+            self.file_filter_report(ReportTarget::Post, post.id, found)
+                .await?;
+        }
+
+        Ok(Some(post))
+    }
+
+    /// Earlier versions of a post the viewer can see, newest first, or `None`
+    /// if the post is missing or hidden from them
+    pub async fn get_post_revisions(
+        &self,
+        viewer_id: i32,
+        post_id: i32,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Option<Vec<PostRevision>>> {
+        if self.get_post(viewer_id, post_id).await?.is_none() {
+            return Ok(None);
+        }
+
+        let revisions = sqlx::query_as!(
+            PostRevision,
+            r#"
+            SELECT id, post_id, version, content, image_url, published_at, replaced_at
+            FROM post_revisions
+            WHERE post_id = $1
+            ORDER BY version DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            post_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Some(revisions))
+    }
+
+    pub async fn delete_post(&self, post_id: i32, user_id: i32) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE posts
+            SET is_deleted = TRUE, deleted_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND (is_deleted IS NULL OR is_deleted = FALSE)
+            "#,
+            post_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            sqlx::query!(
+                "UPDATE users SET post_count = GREATEST(0, COALESCE(post_count, 0) - 1) WHERE id = $1",
+                user_id
+            )
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Attaches authors, the viewer's like and follow state, and up to
+    /// `comment_limit` top comments to a page of posts. Uses one query per kind
+    /// of data regardless of the page size.
+    pub async fn hydrate_posts(
+        &self,
+        viewer_id: i32,
+        entries: Vec<FeedEntry>,
+        comment_limit: i64,
+    ) -> Result<Vec<FeedPost>> {
+        if entries.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let post_ids: Vec<i32> = entries.iter().map(|entry| entry.post.id).collect();
+        let author_ids: Vec<i32> = entries.iter().map(|entry| entry.post.user_id).collect();
+        let comment_limit = comment_limit.clamp(0, hydrate::MAX_COMMENT_PREVIEW);
+
+        let comments = if comment_limit > 0 {
+            sqlx::query_as!(
+                Comment,
+                r#"
+                SELECT id as "id!", user_id as "user_id!", post_id as "post_id!",
+                       content as "content!", like_count, created_at as "created_at!",
+                       updated_at as "updated_at!", reply_to_comment_id, is_deleted, deleted_at,
+                       version as "version!", moderation_state
+                FROM (
+                    SELECT c.*, ROW_NUMBER() OVER (
+                        PARTITION BY c.post_id
+                        ORDER BY c.like_count DESC NULLS LAST, c.created_at ASC, c.id ASC
+                    ) AS rank
+                    FROM comments c
+                    WHERE c.post_id = ANY($1) AND (c.is_deleted IS NULL OR c.is_deleted = FALSE)
+                      AND (c.moderation_state IS NULL OR c.user_id = $3)
+                      AND NOT EXISTS (
+                          SELECT 1 FROM blocks b
+                          WHERE (b.blocker_id = $3 AND b.blocked_id = c.user_id)
+                             OR (b.blocker_id = c.user_id AND b.blocked_id = $3)
+                      )
+                ) ranked
+                WHERE rank <= $2
+                ORDER BY post_id, rank
+                "#,
+                &post_ids,
+                comment_limit,
+                viewer_id
+            )
+            .fetch_all(&self.pool)
+            .await?
+        } else {
+            Vec::new()
+        };
+
+        let user_ids: Vec<i32> = author_ids
+            .iter()
+            .copied()
+            .chain(comments.iter().map(|comment| comment.user_id))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+
+        let users = sqlx::query_as!(
+            UserProfile,
+            r#"
+            SELECT id, username, display_name, bio, avatar_url, location,
+                   website, is_verified, is_private, follower_count, following_count,
+                   post_count, created
+            FROM users
+            WHERE id = ANY($1)
+            "#,
+            &user_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let liked = sqlx::query_scalar!(
+            "SELECT post_id FROM likes WHERE user_id = $1 AND post_id = ANY($2)",
+            viewer_id,
+            &post_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let following = sqlx::query_scalar!(
+            "SELECT following_id FROM follows WHERE follower_id = $1 AND following_id = ANY($2)",
+            viewer_id,
+            &author_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mentions = sqlx::query!(
+            r#"
+            SELECT pm.post_id, pm.user_id, u.username
+            FROM post_mentions pm
+            INNER JOIN users u ON u.id = pm.user_id
+            WHERE pm.post_id = ANY($1)
+            "#,
+            &post_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let media = sqlx::query!(
+            r#"
+            SELECT pm.post_id, pm.media_id, pm.alt_text, m.content_type, m.width, m.height,
+                   m.blurhash, m.storage_key, m.thumbnail_key
+            FROM post_media pm
+            INNER JOIN media m ON m.id = pm.media_id
+            WHERE pm.post_id = ANY($1)
+            ORDER BY pm.post_id, pm.position
+            "#,
+            &post_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let polls = self.load_polls(viewer_id, &post_ids).await?;
+
+        let mut data = hydrate::HydrationData {
+            users: users.into_iter().map(|user| (user.id, user)).collect(),
+            liked: liked.into_iter().collect(),
+            following: following.into_iter().collect(),
+            polls,
+            ..Default::default()
+        };
+        for mention in mentions {
+            data.mentions
+                .entry(mention.post_id)
+                .or_default()
+                .insert(mention.username, mention.user_id);
+        }
+        for comment in comments {
+            data.comments
+                .entry(comment.post_id)
+                .or_default()
+                .push(comment);
+        }
+        for row in media {
+            data.media.entry(row.post_id).or_default().push(PostMedia {
+                media_id: row.media_id,
+                url: media::public_url(&self.media_base_url, &row.storage_key),
+                thumbnail_url: media::public_url(&self.media_base_url, &row.thumbnail_key),
+                content_type: row.content_type,
+                width: row.width,
+                height: row.height,
+                blurhash: row.blurhash,
+                alt_text: row.alt_text,
+            });
+        }
+
+        Ok(hydrate::assemble(entries, data))
+    }
+
+    // Follows
+
+    /// Follows a public account right away and files a follow request for a
+    /// private one. Returns `None` if the user does not exist.
+    pub async fn follow_user(
+        &self,
+        follower_id: i32,
+        following_id: i32,
+    ) -> Result<Option<FollowOutcome>> {
+        let Some(target) = sqlx::query!("SELECT is_private FROM users WHERE id = $1", following_id)
+            .fetch_optional(&self.pool)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        self.ensure_not_blocked(follower_id, following_id).await?;
+
+        if target.is_private != Some(true) {
+            let follow = sqlx::query_as!(
+                Follow,
+                r#"
+                INSERT INTO follows (follower_id, following_id)
+                VALUES ($1, $2)
+                RETURNING id, follower_id, following_id, created_at
+                "#,
+                follower_id,
+                following_id
+            )
+            .fetch_one(&self.pool)
+            .await?;
+
+            self.notify(
+                following_id,
+                follower_id,
+                NotificationKind::Follow,
+                None,
+                None,
+            )
+            .await;
+            return Ok(Some(FollowOutcome::Followed(follow)));
+        }
+
+        let existing = sqlx::query_as!(
+            Follow,
+            r#"
+            SELECT id, follower_id, following_id, created_at
+            FROM follows
+            WHERE follower_id = $1 AND following_id = $2
+            "#,
+            follower_id,
+            following_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(follow) = existing {
+            return Ok(Some(FollowOutcome::Followed(follow)));
+        }
+
+        // Asking again returns the pending request
+        let request = sqlx::query_as!(
+            FollowRequest,
+            r#"
+            INSERT INTO follow_requests (requester_id, target_id)
+            VALUES ($1, $2)
+            ON CONFLICT (requester_id, target_id) DO UPDATE SET requester_id = EXCLUDED.requester_id
+            RETURNING id, requester_id, target_id, created_at
+            "#,
+            follower_id,
+            following_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        self.notify(
+            following_id,
+            follower_id,
+            NotificationKind::FollowRequest,
+            None,
+            None,
+        )
+        .await;
+        Ok(Some(FollowOutcome::Requested(request)))
+    }
+
+    /// Unfollows a user, or withdraws a pending follow request
+    pub async fn unfollow_user(&self, follower_id: i32, following_id: i32) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM follows WHERE follower_id = $1 AND following_id = $2",
+            follower_id,
+            following_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            return Ok(true);
+        }
+
+        let result = sqlx::query!(
+            "DELETE FROM follow_requests WHERE requester_id = $1 AND target_id = $2",
+            follower_id,
+            following_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn post_author(&self, post_id: i32) -> Result<Option<i32>> {
+        let author_id = sqlx::query_scalar!("SELECT user_id FROM posts WHERE id = $1", post_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(author_id)
+    }
+
+    pub async fn is_following(&self, follower_id: i32, following_id: i32) -> Result<bool> {
+        let exists = sqlx::query!(
+            "SELECT EXISTS(SELECT 1 FROM follows WHERE follower_id = $1 AND following_id = $2) as exists",
+            follower_id,
+            following_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists.exists.unwrap_or(false))
+    }
+
+    pub async fn get_followers(
+        &self,
+        viewer_id: i32,
+        user_id: i32,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<UserProfile>> {
+        self.ensure_can_view_user(viewer_id, user_id).await?;
+
+        let users = sqlx::query_as!(
+            UserProfile,
+            r#"
+            SELECT u.id, u.username, u.display_name, u.bio, u.avatar_url, u.location, 
+                   u.website, u.is_verified, u.is_private, u.follower_count, u.following_count, 
+                   u.post_count, u.created
+            FROM users u
+            INNER JOIN follows f ON u.id = f.follower_id
+            WHERE f.following_id = $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM blocks b
+                  WHERE (b.blocker_id = $4 AND b.blocked_id = u.id)
+                     OR (b.blocker_id = u.id AND b.blocked_id = $4)
+              )
+            ORDER BY f.created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            user_id,
+            limit,
+            offset,
+            viewer_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(users)
+    }
+
+    pub async fn get_following(
+        &self,
+        viewer_id: i32,
+        user_id: i32,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<UserProfile>> {
+        self.ensure_can_view_user(viewer_id, user_id).await?;
+
+        let users = sqlx::query_as!(
+            UserProfile,
+            r#"
+            SELECT u.id, u.username, u.display_name, u.bio, u.avatar_url, u.location, 
+                   u.website, u.is_verified, u.is_private, u.follower_count, u.following_count, 
+                   u.post_count, u.created
+            FROM users u
+            INNER JOIN follows f ON u.id = f.following_id
+            WHERE f.follower_id = $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM blocks b
+                  WHERE (b.blocker_id = $4 AND b.blocked_id = u.id)
+                     OR (b.blocker_id = u.id AND b.blocked_id = $4)
+              )
+            ORDER BY f.created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            user_id,
+            limit,
+            offset,
+            viewer_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(users)
+    }
+
+    // Likes
+    /// Likes a post the user can see. Returns `None` when the post does not
+    /// exist, was deleted or is hidden from the user.
+    pub async fn like_post(&self, user_id: i32, post_id: i32) -> Result<Option<Like>> {
+        let Some(post) = self.get_post(user_id, post_id).await? else {
+            return Ok(None);
+        };
+
+        let like = sqlx::query_as!(
+            Like,
+            r#"
+            INSERT INTO likes (user_id, post_id)
+            VALUES ($1, $2)
+            RETURNING id, user_id, post_id, created_at
+            "#,
+            user_id,
+            post_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        self.notify(
+            post.user_id,
+            user_id,
+            NotificationKind::Like,
+            Some(post_id),
+            None,
+        )
+        .await;
+
+        Ok(Some(like))
+    }
+
+    pub async fn unlike_post(&self, user_id: i32, post_id: i32) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM likes WHERE user_id = $1 AND post_id = $2",
+            user_id,
+            post_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn is_liked(&self, user_id: i32, post_id: i32) -> Result<bool> {
+        let exists = sqlx::query!(
+            "SELECT EXISTS(SELECT 1 FROM likes WHERE user_id = $1 AND post_id = $2) as exists",
+            user_id,
+            post_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists.exists.unwrap_or(false))
+    }
+
+    // User Profile
+    pub async fn get_user_profile(&self, user_id: i32) -> Result<Option<UserProfile>> {
+        let user = sqlx::query_as!(
+            UserProfile,
+            r#"
+            SELECT id, username, display_name, bio, avatar_url, location, 
+                   website, is_verified, is_private, follower_count, following_count, 
+                   post_count, created
+            FROM users
+            WHERE id = $1
+            "#,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    pub async fn update_user_profile(
+        &self,
+        user_id: i32,
+        update_profile: UpdateUserProfile,
+    ) -> Result<Option<UserProfile>> {
+        media::validate_image_url(&self.media_base_url, update_profile.avatar_url.as_deref())?;
+
+        let user = sqlx::query_as!(
+            UserProfile,
+            r#"
+            UPDATE users
+            SET display_name = COALESCE($1, display_name),
+                bio = COALESCE($2, bio),
+                avatar_url = COALESCE($3, avatar_url),
+                location = COALESCE($4, location),
+                website = COALESCE($5, website),
+                is_private = COALESCE($6, is_private),
+                avatar_media_id = CASE WHEN $3 IS NULL THEN avatar_media_id ELSE $8 END,
+                updated = NOW()
+            WHERE id = $7
+            RETURNING id, username, display_name, bio, avatar_url, location, 
+                      website, is_verified, is_private, follower_count, following_count, 
+                      post_count, created
+            "#,
+            update_profile.display_name,
+            update_profile.bio,
+            update_profile.avatar_url,
+            update_profile.location,
+            update_profile.website,
+            update_profile.is_private,
+            user_id,
+            update_profile.avatar_media_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        // An account that goes public accepts everyone who was waiting
+        if user.is_some() && update_profile.is_private == Some(false) {
+            let mut tx = self.pool.begin().await?;
+            sqlx::query!(
+                r#"
+                INSERT INTO follows (follower_id, following_id)
+                SELECT requester_id, target_id FROM follow_requests WHERE target_id = $1
+                ON CONFLICT (follower_id, following_id) DO NOTHING
+                "#,
+                user_id
+            )
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query!("DELETE FROM follow_requests WHERE target_id = $1", user_id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+        }
+
+        Ok(user)
+    }
+
+    pub async fn search_users(
+        &self,
+        viewer_id: i32,
+        query: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<UserProfile>> {
+        let search_term = format!("%{}%", query);
+        let users = sqlx::query_as!(
+            UserProfile,
+            r#"
+            SELECT id, username, display_name, bio, avatar_url, location, 
+                   website, is_verified, is_private, follower_count, following_count, 
+                   post_count, created
+            FROM users u
+            WHERE (username ILIKE $1 OR display_name ILIKE $1)
+              AND NOT EXISTS (
+                  SELECT 1 FROM blocks b
+                  WHERE (b.blocker_id = $4 AND b.blocked_id = u.id)
+                     OR (b.blocker_id = u.id AND b.blocked_id = $4)
+              )
+            ORDER BY 
+                CASE WHEN username ILIKE $1 THEN 1 ELSE 2 END,
+                follower_count DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            search_term,
+            limit,
+            offset,
+            viewer_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(users)
+    }
+}
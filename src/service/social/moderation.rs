@@ -0,0 +1,391 @@
+use super::{SocialError, SocialService};
+use crate::db::models::*;
+use crate::service::moderation::{self, FilterAction, FilterMatch};
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use sqlx::{Postgres, Transaction};
+
+/// Row of the `reports` table with its enums still as text
+struct ReportRow {
+    id: i32,
+    reporter_id: Option<i32>,
+    target_type: String,
+    target_id: i32,
+    reason: String,
+    details: Option<String>,
+    status: String,
+    resolved_by: Option<i32>,
+    resolution_note: Option<String>,
+    resolved_at: Option<NaiveDateTime>,
+    created_at: NaiveDateTime,
+}
+
+impl ReportRow {
+    fn into_report(self) -> Option<Report> {
+        let (Some(target_type), Some(reason), Some(status)) = (
+            ReportTarget::parse(&self.target_type),
+            ReportReason::parse(&self.reason),
+            ReportStatus::parse(&self.status),
+        ) else {
+            tracing::warn!(report_id = self.id, "Skipping report with unknown values");
+            return None;
+        };
+
+        Some(Report {
+            id: self.id,
+            reporter_id: self.reporter_id,
+            target_type,
+            target_id: self.target_id,
+            reason,
+            details: self.details,
+            status,
+            resolved_by: self.resolved_by,
+            resolution_note: self.resolution_note,
+            resolved_at: self.resolved_at,
+            created_at: self.created_at,
+        })
+    }
+}
+
+impl SocialService {
+    /// Runs the content filter over new or edited text. Rejected text fails
+    /// with `SocialError::ContentRejected`; any other match is returned so
+    /// the caller can store its moderation state.
+    pub(super) fn screen_content(&self, text: &str) -> Result<Option<FilterMatch>> {
+        match self.content_filter.check(text) {
+            Some(found) if found.action == FilterAction::Reject => {
+                Err(SocialError::ContentRejected.into())
+            }
+            found => Ok(found),
+        }
+    }
+
+    /// Queues content the filter held for review, unless it already waits
+    /// there. Shadow-hidden content is not queued.
+    pub(super) async fn file_filter_report(
+        &self,
+        target: ReportTarget,
+        target_id: i32,
+        found: &FilterMatch,
+    ) -> Result<()> {
+        if found.action != FilterAction::Hold {
+            return Ok(());
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO reports (reporter_id, target_type, target_id, reason, details)
+            SELECT NULL, $1::VARCHAR, $2, $3, $4
+            WHERE NOT EXISTS (
+                SELECT 1 FROM reports
+                WHERE reporter_id IS NULL AND target_type = $1 AND target_id = $2
+                  AND status = 'open'
+            )
+            "#,
+            target.as_str(),
+            target_id,
+            ReportReason::ContentFilter.as_str(),
+            format!("Matched {}", found.rule)
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn is_admin(&self, user_id: i32) -> Result<bool> {
+        let is_admin = sqlx::query_scalar!("SELECT is_admin FROM users WHERE id = $1", user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(is_admin == Some(true))
+    }
+
+    /// Files a report against a post, comment or user the reporter can see.
+    /// Each reporter has at most one open report per target, which fails
+    /// with `SocialError::AlreadyReported`.
+    pub async fn create_report(&self, reporter_id: i32, report: CreateReport) -> Result<Report> {
+        if report.reason == ReportReason::ContentFilter {
+            return Err(SocialError::InvalidReportReason.into());
+        }
+        let details = report
+            .details
+            .map(|details| details.trim().to_string())
+            .filter(|details| !details.is_empty());
+        if details
+            .as_ref()
+            .is_some_and(|details| details.chars().count() > moderation::MAX_REPORT_DETAILS_LENGTH)
+        {
+            return Err(SocialError::ReportDetailsTooLong.into());
+        }
+
+        let visible = match report.target_type {
+            ReportTarget::Post => self
+                .get_post(reporter_id, report.target_id)
+                .await?
+                .is_some(),
+            ReportTarget::Comment => {
+                let post_id = sqlx::query_scalar!(
+                    r#"
+                    SELECT post_id FROM comments
+                    WHERE id = $1 AND (is_deleted IS NULL OR is_deleted = FALSE)
+                      AND (moderation_state IS NULL OR user_id = $2)
+                    "#,
+                    report.target_id,
+                    reporter_id
+                )
+                .fetch_optional(&self.pool)
+                .await?;
+                match post_id {
+                    Some(post_id) => self.get_post(reporter_id, post_id).await?.is_some(),
+                    None => false,
+                }
+            }
+            ReportTarget::User => {
+                report.target_id != reporter_id
+                    && self.get_user_profile(report.target_id).await?.is_some()
+            }
+        };
+        if !visible {
+            return Err(SocialError::ReportTargetNotFound.into());
+        }
+
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO reports (reporter_id, target_type, target_id, reason, details)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (reporter_id, target_type, target_id) WHERE status = 'open' DO NOTHING
+            RETURNING id, created_at
+            "#,
+            reporter_id,
+            report.target_type.as_str(),
+            report.target_id,
+            report.reason.as_str(),
+            details
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(SocialError::AlreadyReported)?;
+
+        Ok(Report {
+            id: row.id,
+            reporter_id: Some(reporter_id),
+            target_type: report.target_type,
+            target_id: report.target_id,
+            reason: report.reason,
+            details,
+            status: ReportStatus::Open,
+            resolved_by: None,
+            resolution_note: None,
+            resolved_at: None,
+            created_at: row.created_at,
+        })
+    }
+
+    /// Lists reports with the given status, oldest first, so the queue is
+    /// worked through in the order reports came in
+    pub async fn get_reports(
+        &self,
+        status: ReportStatus,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Report>> {
+        let rows = sqlx::query_as!(
+            ReportRow,
+            r#"
+            SELECT id, reporter_id, target_type, target_id, reason, details, status,
+                   resolved_by, resolution_note, resolved_at, created_at
+            FROM reports
+            WHERE status = $1
+            ORDER BY created_at ASC, id ASC
+            LIMIT $2 OFFSET $3
+            "#,
+            status.as_str(),
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(ReportRow::into_report)
+            .collect())
+    }
+
+    /// Takes action on an open report: a reported post or comment is deleted,
+    /// and every open report on the same target is resolved with it. Reports
+    /// against users only record the decision. Returns `None` if there is no
+    /// such report and fails with `SocialError::ReportClosed` if it was
+    /// already decided.
+    pub async fn resolve_report(
+        &self,
+        moderator_id: i32,
+        report_id: i32,
+        note: Option<String>,
+    ) -> Result<Option<Report>> {
+        let mut tx = self.pool.begin().await?;
+        let Some(report) = Self::lock_open_report(&mut tx, report_id).await? else {
+            return Ok(None);
+        };
+
+        match report.target_type {
+            ReportTarget::Post => {
+                let author = sqlx::query_scalar!(
+                    r#"
+                    UPDATE posts
+                    SET is_deleted = TRUE, deleted_at = NOW()
+                    WHERE id = $1 AND (is_deleted IS NULL OR is_deleted = FALSE)
+                    RETURNING user_id
+                    "#,
+                    report.target_id
+                )
+                .fetch_optional(&mut *tx)
+                .await?;
+
+                if let Some(author) = author {
+                    sqlx::query!(
+                        "UPDATE users SET post_count = GREATEST(0, COALESCE(post_count, 0) - 1) WHERE id = $1",
+                        author
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+            ReportTarget::Comment => {
+                sqlx::query!(
+                    r#"
+                    UPDATE comments
+                    SET is_deleted = TRUE, deleted_at = NOW()
+                    WHERE id = $1 AND (is_deleted IS NULL OR is_deleted = FALSE)
+                    "#,
+                    report.target_id
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            ReportTarget::User => {}
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE reports
+            SET status = 'resolved', resolved_by = $3, resolution_note = $4, resolved_at = NOW()
+            WHERE target_type = $1 AND target_id = $2 AND status = 'open'
+            "#,
+            report.target_type.as_str(),
+            report.target_id,
+            moderator_id,
+            note
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let report = Self::fetch_report(&mut tx, report_id).await?;
+        tx.commit().await?;
+        Ok(report)
+    }
+
+    /// Closes an open report without acting on its target. Content the filter
+    /// held is released when its report is dismissed. Missing and already
+    /// decided reports are handled as in `resolve_report`.
+    pub async fn dismiss_report(
+        &self,
+        moderator_id: i32,
+        report_id: i32,
+        note: Option<String>,
+    ) -> Result<Option<Report>> {
+        let mut tx = self.pool.begin().await?;
+        let Some(report) = Self::lock_open_report(&mut tx, report_id).await? else {
+            return Ok(None);
+        };
+
+        if report.reason == ReportReason::ContentFilter {
+            match report.target_type {
+                ReportTarget::Post => {
+                    sqlx::query!(
+                        "UPDATE posts SET moderation_state = NULL WHERE id = $1 AND moderation_state = 'held'",
+                        report.target_id
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                ReportTarget::Comment => {
+                    sqlx::query!(
+                        "UPDATE comments SET moderation_state = NULL WHERE id = $1 AND moderation_state = 'held'",
+                        report.target_id
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                ReportTarget::User => {}
+            }
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE reports
+            SET status = 'dismissed', resolved_by = $2, resolution_note = $3, resolved_at = NOW()
+            WHERE id = $1
+            "#,
+            report_id,
+            moderator_id,
+            note
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let report = Self::fetch_report(&mut tx, report_id).await?;
+        tx.commit().await?;
+        Ok(report)
+    }
+
+    /// Locks a report for a decision. Returns `None` if it does not exist and
+    /// fails with `SocialError::ReportClosed` if it is no longer open.
+    async fn lock_open_report(
+        tx: &mut Transaction<'_, Postgres>,
+        report_id: i32,
+    ) -> Result<Option<Report>> {
+        let Some(report) = sqlx::query_as!(
+            ReportRow,
+            r#"
+            SELECT id, reporter_id, target_type, target_id, reason, details, status,
+                   resolved_by, resolution_note, resolved_at, created_at
+            FROM reports
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            report_id
+        )
+        .fetch_optional(&mut **tx)
+        .await?
+        .and_then(ReportRow::into_report) else {
+            return Ok(None);
+        };
+
+        if report.status != ReportStatus::Open {
+            return Err(SocialError::ReportClosed.into());
+        }
+        Ok(Some(report))
+    }
+
+    async fn fetch_report(
+        tx: &mut Transaction<'_, Postgres>,
+        report_id: i32,
+    ) -> Result<Option<Report>> {
+        let row = sqlx::query_as!(
+            ReportRow,
+            r#"
+            SELECT id, reporter_id, target_type, target_id, reason, details, status,
+                   resolved_by, resolution_note, resolved_at, created_at
+            FROM reports
+            WHERE id = $1
+            "#,
+            report_id
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        Ok(row.and_then(ReportRow::into_report))
+    }
+}
@@ -0,0 +1,330 @@
+use super::SocialService;
+use crate::db::models::*;
+use crate::service::notifications::{self, NotificationCursor};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+impl SocialService {
+    /// Tells `user_id` that `actor_id` acted on `post_id`/`comment_id`. The
+    /// action has already happened, so failures are logged, not returned.
+    pub(super) async fn notify(
+        &self,
+        user_id: i32,
+        actor_id: i32,
+        kind: NotificationKind,
+        post_id: Option<i32>,
+        comment_id: Option<i32>,
+    ) {
+        if let Err(e) = self
+            .record_notification(user_id, actor_id, kind, post_id, comment_id)
+            .await
+        {
+            tracing::warn!(error = %e, kind = kind.as_str(), "Failed to record notification");
+        }
+    }
+
+    /// Adds the actor to the recipient's unread group for this target, opening
+    /// a new group if there is none. Self-actions, disabled kinds and actors
+    /// the recipient blocked or muted are skipped.
+    async fn record_notification(
+        &self,
+        user_id: i32,
+        actor_id: i32,
+        kind: NotificationKind,
+        post_id: Option<i32>,
+        comment_id: Option<i32>,
+    ) -> Result<()> {
+        if user_id == actor_id {
+            return Ok(());
+        }
+
+        let suppressed = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(
+                       SELECT 1 FROM notification_preferences
+                       WHERE user_id = $1 AND kind = $3 AND enabled = FALSE
+                   )
+                OR EXISTS(
+                       SELECT 1 FROM blocks
+                       WHERE (blocker_id = $1 AND blocked_id = $2)
+                          OR (blocker_id = $2 AND blocked_id = $1)
+                   )
+                OR EXISTS(SELECT 1 FROM mutes WHERE muter_id = $1 AND muted_id = $2)
+                as "suppressed!"
+            "#,
+            user_id,
+            actor_id,
+            kind.as_str()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if suppressed {
+            return Ok(());
+        }
+
+        let group_key = notifications::group_key(kind, post_id, comment_id);
+        let mut tx = self.pool.begin().await?;
+
+        let notification_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO notifications (user_id, kind, group_key, post_id, comment_id, actor_id)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id, group_key) WHERE is_read = FALSE
+            DO UPDATE SET updated_at = NOW()
+            RETURNING id
+            "#,
+            user_id,
+            kind.as_str(),
+            group_key,
+            post_id,
+            comment_id,
+            actor_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO notification_actors (notification_id, actor_id)
+            VALUES ($1, $2)
+            ON CONFLICT (notification_id, actor_id) DO UPDATE SET created_at = NOW()
+            "#,
+            notification_id,
+            actor_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE notifications
+            SET actor_id = $2,
+                actor_count = (
+                    SELECT COUNT(*) FROM notification_actors WHERE notification_id = $1
+                ),
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+            notification_id,
+            actor_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Returns a page of the user's notifications, most recently active
+    /// first, starting after `cursor`. The returned cursor is `None` on the
+    /// last page.
+    pub async fn get_notifications(
+        &self,
+        user_id: i32,
+        cursor: Option<NotificationCursor>,
+        limit: i64,
+        unread_only: bool,
+    ) -> Result<(Vec<NotificationGroup>, Option<NotificationCursor>)> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, kind, post_id, comment_id, actor_count, is_read, created_at, updated_at
+            FROM notifications
+            WHERE user_id = $1
+              AND (NOT $2 OR is_read = FALSE)
+              AND ($3::TIMESTAMP IS NULL OR (updated_at, id) < ($3::TIMESTAMP, $4::INT4))
+            ORDER BY updated_at DESC, id DESC
+            LIMIT $5
+            "#,
+            user_id,
+            unread_only,
+            cursor.map(|c| c.updated_at),
+            cursor.map(|c| c.id),
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let next = match rows.last() {
+            Some(last) if rows.len() as i64 == limit => Some(NotificationCursor {
+                updated_at: last.updated_at,
+                id: last.id,
+            }),
+            _ => None,
+        };
+
+        let notification_ids: Vec<i32> = rows.iter().map(|row| row.id).collect();
+        let actor_rows = sqlx::query!(
+            r#"
+            SELECT notification_id as "notification_id!", actor_id as "actor_id!"
+            FROM (
+                SELECT notification_id, actor_id, ROW_NUMBER() OVER (
+                    PARTITION BY notification_id ORDER BY created_at DESC
+                ) AS rank
+                FROM notification_actors
+                WHERE notification_id = ANY($1)
+            ) ranked
+            WHERE rank <= $2
+            ORDER BY notification_id, rank
+            "#,
+            &notification_ids,
+            notifications::MAX_NOTIFICATION_ACTORS
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let actor_ids: Vec<i32> = actor_rows
+            .iter()
+            .map(|row| row.actor_id)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let users: HashMap<i32, UserProfile> = sqlx::query_as!(
+            UserProfile,
+            r#"
+            SELECT id, username, display_name, bio, avatar_url, location,
+                   website, is_verified, is_private, follower_count, following_count,
+                   post_count, created
+            FROM users
+            WHERE id = ANY($1)
+            "#,
+            &actor_ids
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user))
+        .collect();
+
+        let mut actors: HashMap<i32, Vec<UserProfile>> = HashMap::new();
+        for row in actor_rows {
+            if let Some(user) = users.get(&row.actor_id) {
+                actors
+                    .entry(row.notification_id)
+                    .or_default()
+                    .push(user.clone());
+            }
+        }
+
+        let groups = rows
+            .into_iter()
+            .filter_map(|row| {
+                let Some(kind) = NotificationKind::parse(&row.kind) else {
+                    tracing::warn!(kind = %row.kind, "Skipping unknown notification kind");
+                    return None;
+                };
+                let actors = actors.remove(&row.id).unwrap_or_default();
+                let actor_name = actors
+                    .first()
+                    .map_or("Someone", |actor| actor.username.as_str());
+                let summary = notifications::summary(kind, actor_name, row.actor_count);
+
+                Some(NotificationGroup {
+                    notification: Notification {
+                        id: row.id,
+                        kind,
+                        post_id: row.post_id,
+                        comment_id: row.comment_id,
+                        actor_count: row.actor_count,
+                        is_read: row.is_read,
+                        created_at: row.created_at,
+                        updated_at: row.updated_at,
+                    },
+                    actors,
+                    summary,
+                })
+            })
+            .collect();
+
+        Ok((groups, next))
+    }
+
+    pub async fn get_unread_notification_count(&self, user_id: i32) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!"
+            FROM notifications
+            WHERE user_id = $1 AND is_read = FALSE
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Marks one of the user's notifications as read. Later activity on the
+    /// same target starts a new group.
+    pub async fn mark_notification_read(&self, user_id: i32, notification_id: i32) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE notifications SET is_read = TRUE WHERE id = $1 AND user_id = $2",
+            notification_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Marks every unread notification of the user as read and returns how
+    /// many there were
+    pub async fn mark_all_notifications_read(&self, user_id: i32) -> Result<u64> {
+        let result = sqlx::query!(
+            "UPDATE notifications SET is_read = TRUE WHERE user_id = $1 AND is_read = FALSE",
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn get_notification_preferences(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<NotificationPreference>> {
+        let stored: Vec<NotificationPreference> = sqlx::query!(
+            "SELECT kind, enabled FROM notification_preferences WHERE user_id = $1",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .filter_map(|row| {
+            NotificationKind::parse(&row.kind).map(|kind| NotificationPreference {
+                kind,
+                enabled: row.enabled,
+            })
+        })
+        .collect();
+
+        Ok(notifications::resolve_preferences(&stored))
+    }
+
+    /// Stores the given per-kind settings and returns the full preference list
+    pub async fn update_notification_preferences(
+        &self,
+        user_id: i32,
+        preferences: Vec<NotificationPreference>,
+    ) -> Result<Vec<NotificationPreference>> {
+        let mut tx = self.pool.begin().await?;
+        for preference in preferences {
+            sqlx::query!(
+                r#"
+                INSERT INTO notification_preferences (user_id, kind, enabled)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (user_id, kind) DO UPDATE SET enabled = EXCLUDED.enabled
+                "#,
+                user_id,
+                preference.kind.as_str(),
+                preference.enabled
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        self.get_notification_preferences(user_id).await
+    }
+}
@@ -0,0 +1,135 @@
+use super::{SocialError, SocialService};
+use crate::db::models::*;
+use crate::service::polls;
+use anyhow::Result;
+use std::collections::HashMap;
+
+impl SocialService {
+    /// Records the user's vote in the poll of a post they can see and returns
+    /// the poll with its results. Returns `None` when the post is not visible
+    /// or has no poll. Each user votes once; a second vote fails with
+    /// `SocialError::AlreadyVoted`.
+    pub async fn vote_in_poll(
+        &self,
+        user_id: i32,
+        post_id: i32,
+        option_id: i32,
+    ) -> Result<Option<Poll>> {
+        if self.get_post(user_id, post_id).await?.is_none() {
+            return Ok(None);
+        }
+        let Some(poll) = sqlx::query!(
+            r#"SELECT id, closes_at <= NOW() as "closed!" FROM polls WHERE post_id = $1"#,
+            post_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+        if poll.closed {
+            return Err(SocialError::PollClosed.into());
+        }
+
+        // The counting trigger updates the option and poll rows, which
+        // serializes concurrent votes without a lock of our own
+        let voted = sqlx::query_scalar!(
+            r#"
+            INSERT INTO poll_votes (poll_id, user_id, option_id)
+            SELECT o.poll_id, $2, o.id
+            FROM poll_options o
+            INNER JOIN polls p ON p.id = o.poll_id
+            WHERE o.id = $3 AND o.poll_id = $1 AND p.closes_at > NOW()
+            ON CONFLICT (poll_id, user_id) DO NOTHING
+            RETURNING option_id
+            "#,
+            poll.id,
+            user_id,
+            option_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if voted.is_none() {
+            let state = sqlx::query!(
+                r#"
+                SELECT p.closes_at <= NOW() as "closed!",
+                       EXISTS (
+                           SELECT 1 FROM poll_options WHERE id = $3 AND poll_id = p.id
+                       ) as "valid_option!",
+                       EXISTS (
+                           SELECT 1 FROM poll_votes WHERE poll_id = p.id AND user_id = $2
+                       ) as "voted!"
+                FROM polls p
+                WHERE p.id = $1
+                "#,
+                poll.id,
+                user_id,
+                option_id
+            )
+            .fetch_one(&self.pool)
+            .await?;
+
+            return Err(if !state.valid_option {
+                SocialError::InvalidPollOption
+            } else if state.voted {
+                SocialError::AlreadyVoted
+            } else {
+                SocialError::PollClosed
+            }
+            .into());
+        }
+
+        Ok(self.load_polls(user_id, &[post_id]).await?.remove(&post_id))
+    }
+
+    /// Polls of the given posts by post id, with the viewer's vote and the
+    /// results redacted until they voted or the poll closed
+    pub(super) async fn load_polls(
+        &self,
+        viewer_id: i32,
+        post_ids: &[i32],
+    ) -> Result<HashMap<i32, Poll>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT p.post_id, p.id, p.closes_at, p.closes_at <= NOW() as "closed!", p.vote_count,
+                   o.id as option_id, o.label, o.vote_count as option_vote_count,
+                   v.option_id as "voted_option_id?"
+            FROM polls p
+            INNER JOIN poll_options o ON o.poll_id = p.id
+            LEFT JOIN poll_votes v ON v.poll_id = p.id AND v.user_id = $2
+            WHERE p.post_id = ANY($1)
+            ORDER BY p.post_id, o.position
+            "#,
+            post_ids,
+            viewer_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut polls: HashMap<i32, Poll> = HashMap::new();
+        for row in rows {
+            polls
+                .entry(row.post_id)
+                .or_insert_with(|| Poll {
+                    id: row.id,
+                    closes_at: row.closes_at,
+                    closed: row.closed,
+                    vote_count: row.vote_count,
+                    options: Vec::new(),
+                    voted_option_id: row.voted_option_id,
+                })
+                .options
+                .push(PollOption {
+                    id: row.option_id,
+                    label: row.label,
+                    vote_count: Some(row.option_vote_count),
+                });
+        }
+        for poll in polls.values_mut() {
+            polls::redact_results(poll);
+        }
+
+        Ok(polls)
+    }
+}
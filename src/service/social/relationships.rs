@@ -0,0 +1,182 @@
+use super::SocialService;
+use crate::db::models::*;
+use crate::service::relationships;
+use anyhow::Result;
+use std::collections::HashMap;
+
+impl SocialService {
+    /// How the viewer relates to `user_id`, or `None` when there is no such
+    /// user
+    pub async fn get_relationship(
+        &self,
+        viewer_id: i32,
+        user_id: i32,
+    ) -> Result<Option<Relationship>> {
+        Ok(self.get_relationships(viewer_id, &[user_id]).await?.pop())
+    }
+
+    /// Relationships with several users at once, in the order asked.
+    /// Unknown user ids are skipped.
+    pub async fn get_relationships(
+        &self,
+        viewer_id: i32,
+        user_ids: &[i32],
+    ) -> Result<Vec<Relationship>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT u.id, u.is_private,
+                   EXISTS (
+                       SELECT 1 FROM follows WHERE follower_id = $1 AND following_id = u.id
+                   ) as "following!",
+                   EXISTS (
+                       SELECT 1 FROM follows WHERE follower_id = u.id AND following_id = $1
+                   ) as "followed_by!",
+                   EXISTS (
+                       SELECT 1 FROM blocks WHERE blocker_id = $1 AND blocked_id = u.id
+                   ) as "blocking!",
+                   EXISTS (
+                       SELECT 1 FROM blocks WHERE blocker_id = u.id AND blocked_id = $1
+                   ) as "blocked_by!",
+                   EXISTS (
+                       SELECT 1 FROM mutes WHERE muter_id = $1 AND muted_id = u.id
+                   ) as "muting!",
+                   EXISTS (
+                       SELECT 1 FROM follow_requests WHERE requester_id = $1 AND target_id = u.id
+                   ) as "follow_requested!",
+                   EXISTS (
+                       SELECT 1 FROM follow_requests WHERE requester_id = u.id AND target_id = $1
+                   ) as "follow_requested_by!"
+            FROM users u
+            WHERE u.id = ANY($2)
+            "#,
+            viewer_id,
+            user_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Mutual followers reveal part of the user's follower list, so they
+        // follow the same rules as the list itself
+        let visible_ids: Vec<i32> = rows
+            .iter()
+            .filter(|row| {
+                !row.blocking
+                    && !row.blocked_by
+                    && (row.id == viewer_id || row.is_private != Some(true) || row.following)
+            })
+            .map(|row| row.id)
+            .collect();
+        let (mut counts, mut mutuals) = self.mutual_followers(viewer_id, &visible_ids).await?;
+
+        let mut relationships: HashMap<i32, Relationship> = rows
+            .into_iter()
+            .map(|row| {
+                let mutual_followers = mutuals.remove(&row.id).unwrap_or_default();
+                let mutual_follower_count = counts.remove(&row.id).unwrap_or(0);
+                let names: Vec<&str> = mutual_followers
+                    .iter()
+                    .map(|user| user.username.as_str())
+                    .collect();
+                let mutual_followers_summary =
+                    relationships::mutual_followers_summary(&names, mutual_follower_count);
+
+                let relationship = Relationship {
+                    user_id: row.id,
+                    following: row.following,
+                    followed_by: row.followed_by,
+                    blocking: row.blocking,
+                    blocked_by: row.blocked_by,
+                    muting: row.muting,
+                    follow_requested: row.follow_requested,
+                    follow_requested_by: row.follow_requested_by,
+                    mutual_followers,
+                    mutual_follower_count,
+                    mutual_followers_summary,
+                };
+                (row.id, relationship)
+            })
+            .collect();
+
+        Ok(user_ids
+            .iter()
+            .filter_map(|id| relationships.remove(id))
+            .collect())
+    }
+
+    /// For each user, how many accounts the viewer follows also follow them,
+    /// and the most followed few of those accounts
+    async fn mutual_followers(
+        &self,
+        viewer_id: i32,
+        user_ids: &[i32],
+    ) -> Result<(HashMap<i32, i64>, HashMap<i32, Vec<UserProfile>>)> {
+        if user_ids.is_empty() {
+            return Ok((HashMap::new(), HashMap::new()));
+        }
+
+        let counts = sqlx::query!(
+            r#"
+            SELECT theirs.following_id as user_id, COUNT(*) as "total!"
+            FROM follows theirs
+            INNER JOIN follows mine ON mine.following_id = theirs.follower_id
+            WHERE mine.follower_id = $1 AND theirs.following_id = ANY($2)
+            GROUP BY theirs.following_id
+            "#,
+            viewer_id,
+            user_ids
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.user_id, row.total))
+        .collect();
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT target.id as "target_id!", m.id as "id!", m.username as "username!",
+                   m.display_name, m.bio, m.avatar_url, m.location, m.website, m.is_verified,
+                   m.is_private, m.follower_count, m.following_count, m.post_count,
+                   m.created as "created!"
+            FROM UNNEST($2::INT[]) AS target(id)
+            CROSS JOIN LATERAL (
+                SELECT u.id, u.username, u.display_name, u.bio, u.avatar_url, u.location,
+                       u.website, u.is_verified, u.is_private, u.follower_count,
+                       u.following_count, u.post_count, u.created
+                FROM follows theirs
+                INNER JOIN follows mine
+                    ON mine.following_id = theirs.follower_id AND mine.follower_id = $1
+                INNER JOIN users u ON u.id = theirs.follower_id
+                WHERE theirs.following_id = target.id
+                ORDER BY u.follower_count DESC NULLS LAST, u.id
+                LIMIT $3
+            ) m
+            "#,
+            viewer_id,
+            user_ids,
+            relationships::MAX_MUTUAL_FOLLOWERS_SHOWN
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut mutuals: HashMap<i32, Vec<UserProfile>> = HashMap::new();
+        for row in rows {
+            mutuals.entry(row.target_id).or_default().push(UserProfile {
+                id: row.id,
+                username: row.username,
+                display_name: row.display_name,
+                bio: row.bio,
+                avatar_url: row.avatar_url,
+                location: row.location,
+                website: row.website,
+                is_verified: row.is_verified,
+                is_private: row.is_private,
+                follower_count: row.follower_count,
+                following_count: row.following_count,
+                post_count: row.post_count,
+                created: row.created,
+            });
+        }
+
+        Ok((counts, mutuals))
+    }
+}
@@ -0,0 +1,67 @@
+use super::{SocialError, SocialService};
+use crate::db::models::*;
+use anyhow::Result;
+
+impl SocialService {
+    /// Reposts a live post. Returns `None` when the post does not exist or was
+    /// deleted, and fails with `SocialError::AlreadyReposted` on a second repost.
+    pub async fn repost_post(&self, user_id: i32, post_id: i32) -> Result<Option<Repost>> {
+        let repost = sqlx::query_as!(
+            Repost,
+            r#"
+            INSERT INTO reposts (user_id, post_id)
+            SELECT $1, p.id
+            FROM posts p
+            INNER JOIN users u ON u.id = p.user_id
+            WHERE p.id = $2 AND (p.is_deleted IS NULL OR p.is_deleted = FALSE)
+              AND (p.moderation_state IS NULL OR p.user_id = $1)
+              AND (p.user_id = $1 OR u.is_private IS NOT TRUE)
+              AND NOT EXISTS (
+                  SELECT 1 FROM blocks b
+                  WHERE (b.blocker_id = $1 AND b.blocked_id = p.user_id)
+                     OR (b.blocker_id = p.user_id AND b.blocked_id = $1)
+              )
+            ON CONFLICT (user_id, post_id) DO NOTHING
+            RETURNING id, user_id, post_id, created_at
+            "#,
+            user_id,
+            post_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if repost.is_none() {
+            // Posts by private accounts can only be reposted by their author
+            match self.get_post(user_id, post_id).await? {
+                Some(post) if post.user_id != user_id && self.is_private(post.user_id).await? => {
+                    return Err(SocialError::PrivateAccount.into());
+                }
+                Some(_) => return Err(SocialError::AlreadyReposted.into()),
+                None => {}
+            }
+        } else if let Some(author_id) = self.post_author(post_id).await? {
+            self.notify(
+                author_id,
+                user_id,
+                NotificationKind::Repost,
+                Some(post_id),
+                None,
+            )
+            .await;
+        }
+
+        Ok(repost)
+    }
+
+    pub async fn unrepost_post(&self, user_id: i32, post_id: i32) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM reposts WHERE user_id = $1 AND post_id = $2",
+            user_id,
+            post_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,512 @@
+use super::{InsertedPost, SocialError, SocialService};
+use crate::db::models::*;
+use crate::service::{
+    media::{self, MediaError},
+    polls, scheduled_posts,
+};
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use sqlx::{Acquire, Postgres, Transaction};
+use std::collections::HashMap;
+
+/// Row of `scheduled_posts`, combined with its media afterwards
+struct ScheduledPostRow {
+    id: i32,
+    user_id: i32,
+    content: String,
+    image_url: Option<String>,
+    reply_to_post_id: Option<i32>,
+    quote_of_post_id: Option<i32>,
+    poll_options: Option<Vec<String>>,
+    poll_duration_minutes: Option<i32>,
+    status: String,
+    publish_at: Option<NaiveDateTime>,
+    error: Option<String>,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+}
+
+impl ScheduledPostRow {
+    fn poll(&self) -> Option<NewPoll> {
+        Some(NewPoll {
+            options: self.poll_options.clone()?,
+            duration_minutes: self.poll_duration_minutes?,
+        })
+    }
+
+    fn into_scheduled_post(self, media: Vec<PostMedia>) -> Option<ScheduledPost> {
+        let Some(status) = ScheduledPostStatus::parse(&self.status) else {
+            tracing::warn!(
+                scheduled_post_id = self.id,
+                "Skipping scheduled post with unknown status"
+            );
+            return None;
+        };
+        let poll = self.poll();
+
+        Some(ScheduledPost {
+            id: self.id,
+            user_id: self.user_id,
+            content: self.content,
+            image_url: self.image_url,
+            media,
+            reply_to_post_id: self.reply_to_post_id,
+            quote_of_post_id: self.quote_of_post_id,
+            poll,
+            status,
+            publish_at: self.publish_at,
+            error: self.error,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+impl SocialService {
+    /// Saves a draft, or schedules it when `publish_at` is set. The content
+    /// filter, attachments and reply and quote targets are checked now so
+    /// the author hears about problems early, and again on publishing.
+    pub async fn create_scheduled_post(
+        &self,
+        user_id: i32,
+        draft: SaveScheduledPost,
+    ) -> Result<ScheduledPost> {
+        let (status, poll_options) = self.validate_scheduled_post(user_id, &draft).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO scheduled_posts (user_id, content, image_url, reply_to_post_id,
+                                         quote_of_post_id, poll_options, poll_duration_minutes,
+                                         status, publish_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id
+            "#,
+            user_id,
+            draft.post.content,
+            draft.post.image_url,
+            draft.post.reply_to_post_id,
+            draft.post.quote_of_post_id,
+            poll_options.as_deref(),
+            draft.post.poll.as_ref().map(|poll| poll.duration_minutes),
+            status.as_str(),
+            draft.publish_at
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        Self::replace_scheduled_media(&mut tx, id, &draft.post.media).await?;
+        tx.commit().await?;
+
+        self.get_scheduled_post(user_id, id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Scheduled post {} vanished after saving", id))
+    }
+
+    /// Replaces a draft or scheduled post. A failed post is scheduled or
+    /// turned back into a draft. Returns `None` if the user has no such post,
+    /// including one that was published meanwhile.
+    pub async fn update_scheduled_post(
+        &self,
+        user_id: i32,
+        scheduled_post_id: i32,
+        draft: SaveScheduledPost,
+    ) -> Result<Option<ScheduledPost>> {
+        let (status, poll_options) = self.validate_scheduled_post(user_id, &draft).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let updated = sqlx::query_scalar!(
+            r#"
+            UPDATE scheduled_posts
+            SET content = $3, image_url = $4, reply_to_post_id = $5, quote_of_post_id = $6,
+                poll_options = $7, poll_duration_minutes = $8, status = $9, publish_at = $10,
+                error = NULL, updated_at = NOW()
+            WHERE id = $1 AND user_id = $2
+            RETURNING id
+            "#,
+            scheduled_post_id,
+            user_id,
+            draft.post.content,
+            draft.post.image_url,
+            draft.post.reply_to_post_id,
+            draft.post.quote_of_post_id,
+            poll_options.as_deref(),
+            draft.post.poll.as_ref().map(|poll| poll.duration_minutes),
+            status.as_str(),
+            draft.publish_at
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if updated.is_none() {
+            return Ok(None);
+        }
+        Self::replace_scheduled_media(&mut tx, scheduled_post_id, &draft.post.media).await?;
+        tx.commit().await?;
+
+        self.get_scheduled_post(user_id, scheduled_post_id).await
+    }
+
+    /// The user's drafts, scheduled and failed posts: scheduled ones by
+    /// publish time, then the rest by last edit
+    pub async fn get_scheduled_posts(
+        &self,
+        user_id: i32,
+        status: Option<ScheduledPostStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ScheduledPost>> {
+        let rows = sqlx::query_as!(
+            ScheduledPostRow,
+            r#"
+            SELECT id, user_id, content, image_url, reply_to_post_id, quote_of_post_id,
+                   poll_options, poll_duration_minutes, status, publish_at, error, created_at,
+                   updated_at
+            FROM scheduled_posts
+            WHERE user_id = $1 AND ($2::VARCHAR IS NULL OR status = $2)
+            ORDER BY status = 'scheduled' DESC, publish_at ASC NULLS LAST, updated_at DESC, id DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            user_id,
+            status.map(|status| status.as_str()),
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let ids: Vec<i32> = rows.iter().map(|row| row.id).collect();
+        let mut media = self.scheduled_media(&ids).await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let attachments = media.remove(&row.id).unwrap_or_default();
+                row.into_scheduled_post(attachments)
+            })
+            .collect())
+    }
+
+    pub async fn get_scheduled_post(
+        &self,
+        user_id: i32,
+        scheduled_post_id: i32,
+    ) -> Result<Option<ScheduledPost>> {
+        let row = sqlx::query_as!(
+            ScheduledPostRow,
+            r#"
+            SELECT id, user_id, content, image_url, reply_to_post_id, quote_of_post_id,
+                   poll_options, poll_duration_minutes, status, publish_at, error, created_at,
+                   updated_at
+            FROM scheduled_posts
+            WHERE id = $1 AND user_id = $2
+            "#,
+            scheduled_post_id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let media = self
+            .scheduled_media(&[row.id])
+            .await?
+            .remove(&row.id)
+            .unwrap_or_default();
+
+        Ok(row.into_scheduled_post(media))
+    }
+
+    /// Cancels a draft or scheduled post
+    pub async fn delete_scheduled_post(
+        &self,
+        user_id: i32,
+        scheduled_post_id: i32,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM scheduled_posts WHERE id = $1 AND user_id = $2",
+            scheduled_post_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Publishes a draft or scheduled post right away. Returns `None` if the
+    /// user has no such post; publishing errors are returned as they are and
+    /// leave the draft untouched. The post is written and the draft removed
+    /// in one transaction, so a draft is never published twice.
+    pub async fn publish_scheduled_post(
+        &self,
+        user_id: i32,
+        scheduled_post_id: i32,
+    ) -> Result<Option<Post>> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query_as!(
+            ScheduledPostRow,
+            r#"
+            SELECT id, user_id, content, image_url, reply_to_post_id, quote_of_post_id,
+                   poll_options, poll_duration_minutes, status, publish_at, error, created_at,
+                   updated_at
+            FROM scheduled_posts
+            WHERE id = $1 AND user_id = $2
+            FOR UPDATE
+            "#,
+            scheduled_post_id,
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let media = self
+            .scheduled_media(&[row.id])
+            .await?
+            .remove(&row.id)
+            .unwrap_or_default();
+        let inserted = self.publish(&mut tx, row, media).await?;
+
+        sqlx::query!(
+            "DELETE FROM scheduled_posts WHERE id = $1",
+            scheduled_post_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.announce_post(inserted).await.map(Some)
+    }
+
+    /// Publishes up to `batch_size` scheduled posts whose time has come and
+    /// returns how many were published or marked failed. Posts that can never
+    /// be published are marked failed; other errors leave them scheduled for
+    /// the next pass.
+    /// Each post is claimed with `SKIP LOCKED` and published in its own
+    /// transaction, which also removes it from the schedule, so several
+    /// workers can run side by side and a crash never publishes a post twice.
+    pub async fn publish_due_posts(&self, batch_size: i64) -> Result<usize> {
+        let mut handled = 0;
+        // Posts that hit a transient error stay scheduled; skip them until
+        // the next pass
+        let mut skipped: Vec<i32> = Vec::new();
+
+        for _ in 0..batch_size {
+            let mut tx = self.pool.begin().await?;
+            let row = sqlx::query_as!(
+                ScheduledPostRow,
+                r#"
+                SELECT id, user_id, content, image_url, reply_to_post_id, quote_of_post_id,
+                       poll_options, poll_duration_minutes, status, publish_at, error,
+                       created_at, updated_at
+                FROM scheduled_posts
+                WHERE status = 'scheduled' AND publish_at <= NOW() AND id <> ALL($1)
+                ORDER BY publish_at, id
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+                "#,
+                &skipped
+            )
+            .fetch_optional(&mut *tx)
+            .await?;
+            let Some(row) = row else {
+                break;
+            };
+
+            let id = row.id;
+            let media = self
+                .scheduled_media(&[id])
+                .await?
+                .remove(&id)
+                .unwrap_or_default();
+
+            // The savepoint drops a half-written post but keeps the claim,
+            // so a permanent failure can still be recorded
+            let mut publishing = tx.begin().await?;
+            match self.publish(&mut publishing, row, media).await {
+                Ok(inserted) => {
+                    publishing.commit().await?;
+                    sqlx::query!("DELETE FROM scheduled_posts WHERE id = $1", id)
+                        .execute(&mut *tx)
+                        .await?;
+                    tx.commit().await?;
+                    handled += 1;
+
+                    let post_id = inserted.post.id;
+                    if let Err(e) = self.announce_post(inserted).await {
+                        tracing::warn!(
+                            scheduled_post_id = id,
+                            post_id,
+                            "Failed to finish publishing scheduled post: {}",
+                            e
+                        );
+                    }
+                    tracing::info!(scheduled_post_id = id, post_id, "Published scheduled post");
+                }
+                Err(e) if scheduled_posts::is_permanent_failure(&e) => {
+                    publishing.rollback().await?;
+                    sqlx::query!(
+                        r#"
+                        UPDATE scheduled_posts
+                        SET status = 'failed', error = $2, updated_at = NOW()
+                        WHERE id = $1
+                        "#,
+                        id,
+                        e.to_string()
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                    tx.commit().await?;
+                    handled += 1;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        scheduled_post_id = id,
+                        "Failed to publish scheduled post: {}",
+                        e
+                    );
+                    skipped.push(id);
+                }
+            }
+        }
+
+        Ok(handled)
+    }
+
+    /// Checks a draft the way `create_post` would and picks its status.
+    /// Also returns the trimmed poll answers, if there is a poll.
+    async fn validate_scheduled_post(
+        &self,
+        user_id: i32,
+        draft: &SaveScheduledPost,
+    ) -> Result<(ScheduledPostStatus, Option<Vec<String>>)> {
+        let status = scheduled_posts::status_for(draft.publish_at, chrono::Utc::now().naive_utc())
+            .ok_or(SocialError::PublishTimeInPast)?;
+
+        self.screen_content(&draft.post.content)?;
+        let poll_options = draft.post.poll.as_ref().map(polls::validate).transpose()?;
+
+        if let Some(reply_to_post_id) = draft.post.reply_to_post_id {
+            if self.get_post(user_id, reply_to_post_id).await?.is_none() {
+                return Err(SocialError::ReplyTargetNotFound.into());
+            }
+        }
+        if let Some(quote_of_post_id) = draft.post.quote_of_post_id {
+            if self.get_post(user_id, quote_of_post_id).await?.is_none() {
+                return Err(SocialError::QuoteTargetNotFound.into());
+            }
+        }
+
+        media::validate_attachments(&draft.post.media)?;
+        media::validate_image_url(&self.media_base_url, draft.post.image_url.as_deref())?;
+        let media_ids: Vec<i32> = draft.post.media.iter().map(|m| m.media_id).collect();
+        let owned = sqlx::query_scalar!(
+            "SELECT COUNT(*) as \"count!\" FROM media WHERE id = ANY($1) AND user_id = $2",
+            &media_ids,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if owned != media_ids.len() as i64 {
+            return Err(MediaError::NotFound.into());
+        }
+
+        Ok((status, poll_options))
+    }
+
+    async fn replace_scheduled_media(
+        tx: &mut Transaction<'_, Postgres>,
+        scheduled_post_id: i32,
+        attachments: &[NewPostMedia],
+    ) -> Result<()> {
+        let media_ids: Vec<i32> = attachments.iter().map(|m| m.media_id).collect();
+        let alt_texts: Vec<Option<String>> =
+            attachments.iter().map(|m| m.alt_text.clone()).collect();
+
+        sqlx::query!(
+            "DELETE FROM scheduled_post_media WHERE scheduled_post_id = $1",
+            scheduled_post_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO scheduled_post_media (scheduled_post_id, media_id, position, alt_text)
+            SELECT $1, m.media_id, (m.ordinality - 1)::SMALLINT, NULLIF(BTRIM(m.alt_text), '')
+            FROM UNNEST($2::INT4[], $3::TEXT[]) WITH ORDINALITY AS m(media_id, alt_text, ordinality)
+            "#,
+            scheduled_post_id,
+            &media_ids,
+            &alt_texts as &[Option<String>]
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Attachments of the given drafts, in display order
+    async fn scheduled_media(&self, ids: &[i32]) -> Result<HashMap<i32, Vec<PostMedia>>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT sm.scheduled_post_id, sm.media_id, sm.alt_text, m.content_type, m.width,
+                   m.height, m.blurhash, m.storage_key, m.thumbnail_key
+            FROM scheduled_post_media sm
+            INNER JOIN media m ON m.id = sm.media_id
+            WHERE sm.scheduled_post_id = ANY($1)
+            ORDER BY sm.scheduled_post_id, sm.position
+            "#,
+            ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut media: HashMap<i32, Vec<PostMedia>> = HashMap::new();
+        for row in rows {
+            media
+                .entry(row.scheduled_post_id)
+                .or_default()
+                .push(PostMedia {
+                    media_id: row.media_id,
+                    url: media::public_url(&self.media_base_url, &row.storage_key),
+                    thumbnail_url: media::public_url(&self.media_base_url, &row.thumbnail_key),
+                    content_type: row.content_type,
+                    width: row.width,
+                    height: row.height,
+                    blurhash: row.blurhash,
+                    alt_text: row.alt_text,
+                });
+        }
+        Ok(media)
+    }
+
+    /// Turns a draft into a live post in `tx` through `insert_post`, so it is
+    /// checked and counted like any other post. The caller announces it
+    /// once `tx` has committed.
+    async fn publish(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        row: ScheduledPostRow,
+        media: Vec<PostMedia>,
+    ) -> Result<InsertedPost> {
+        let poll = row.poll();
+        let create_post = CreatePost {
+            content: row.content,
+            image_url: row.image_url,
+            media: media
+                .into_iter()
+                .map(|m| NewPostMedia {
+                    media_id: m.media_id,
+                    alt_text: m.alt_text,
+                })
+                .collect(),
+            reply_to_post_id: row.reply_to_post_id,
+            quote_of_post_id: row.quote_of_post_id,
+            poll,
+        };
+        self.insert_post(tx, row.user_id, create_post).await
+    }
+}
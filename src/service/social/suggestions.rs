@@ -0,0 +1,160 @@
+use super::SocialService;
+use crate::db::models::*;
+use crate::service::suggestions;
+use anyhow::Result;
+
+impl SocialService {
+    /// Public accounts the user might want to follow, best first. Candidates
+    /// come from accounts followed by the user's follows, accounts posting
+    /// under the same hashtags, accounts the user exchanged likes or
+    /// comments with, and popular accounts. Accounts the user follows,
+    /// blocked, was blocked by, muted or dismissed are left out.
+    pub async fn get_follow_suggestions(
+        &self,
+        user_id: i32,
+        limit: i64,
+    ) -> Result<Vec<FollowSuggestion>> {
+        let rows = sqlx::query!(
+            r#"
+            WITH excluded AS (
+                SELECT $1 AS user_id
+                UNION SELECT following_id FROM follows WHERE follower_id = $1
+                UNION SELECT blocked_id FROM blocks WHERE blocker_id = $1
+                UNION SELECT blocker_id FROM blocks WHERE blocked_id = $1
+                UNION SELECT muted_id FROM mutes WHERE muter_id = $1
+                UNION SELECT dismissed_user_id FROM dismissed_suggestions WHERE user_id = $1
+            ),
+            mutual AS (
+                SELECT f.following_id AS user_id, COUNT(*) AS total
+                FROM follows f
+                INNER JOIN follows mine ON mine.following_id = f.follower_id AND mine.follower_id = $1
+                WHERE f.following_id NOT IN (SELECT user_id FROM excluded)
+                GROUP BY f.following_id
+                ORDER BY total DESC
+                LIMIT $2
+            ),
+            my_hashtags AS (
+                SELECT DISTINCT ph.hashtag_id
+                FROM post_hashtags ph
+                INNER JOIN posts p ON p.id = ph.post_id
+                WHERE p.user_id = $1 AND p.is_deleted IS NOT TRUE
+                  AND ph.created_at > NOW() - make_interval(days => $3)
+            ),
+            shared_hashtags AS (
+                SELECT p.user_id, COUNT(DISTINCT ph.hashtag_id) AS total
+                FROM post_hashtags ph
+                INNER JOIN posts p ON p.id = ph.post_id
+                WHERE ph.hashtag_id IN (SELECT hashtag_id FROM my_hashtags)
+                  AND ph.created_at > NOW() - make_interval(days => $3)
+                  AND p.is_deleted IS NOT TRUE AND p.moderation_state IS NULL
+                  AND p.user_id NOT IN (SELECT user_id FROM excluded)
+                GROUP BY p.user_id
+                ORDER BY total DESC
+                LIMIT $2
+            ),
+            interactions AS (
+                SELECT i.user_id, COUNT(*) AS total
+                FROM (
+                    SELECT p.user_id FROM likes l
+                    INNER JOIN posts p ON p.id = l.post_id
+                    WHERE l.user_id = $1
+                    UNION ALL
+                    SELECT p.user_id FROM comments c
+                    INNER JOIN posts p ON p.id = c.post_id
+                    WHERE c.user_id = $1 AND c.is_deleted IS NOT TRUE
+                    UNION ALL
+                    SELECT l.user_id FROM likes l
+                    INNER JOIN posts p ON p.id = l.post_id
+                    WHERE p.user_id = $1
+                    UNION ALL
+                    SELECT c.user_id FROM comments c
+                    INNER JOIN posts p ON p.id = c.post_id
+                    WHERE p.user_id = $1 AND c.is_deleted IS NOT TRUE
+                ) i
+                WHERE i.user_id NOT IN (SELECT user_id FROM excluded)
+                GROUP BY i.user_id
+                ORDER BY total DESC
+                LIMIT $2
+            ),
+            popular AS (
+                SELECT u.id AS user_id
+                FROM users u
+                WHERE u.is_private IS NOT TRUE
+                  AND u.id NOT IN (SELECT user_id FROM excluded)
+                ORDER BY u.follower_count DESC NULLS LAST, u.id
+                LIMIT $2
+            ),
+            candidates AS (
+                SELECT user_id FROM mutual
+                UNION SELECT user_id FROM shared_hashtags
+                UNION SELECT user_id FROM interactions
+                UNION SELECT user_id FROM popular
+            )
+            SELECT u.id, u.username, u.display_name, u.bio, u.avatar_url, u.location,
+                   u.website, u.is_verified, u.is_private, u.follower_count, u.following_count,
+                   u.post_count, u.created,
+                   COALESCE(m.total, 0) as "mutual_follow_count!",
+                   COALESCE(h.total, 0) as "shared_hashtag_count!",
+                   COALESCE(i.total, 0) as "interaction_count!"
+            FROM candidates c
+            INNER JOIN users u ON u.id = c.user_id
+            LEFT JOIN mutual m ON m.user_id = u.id
+            LEFT JOIN shared_hashtags h ON h.user_id = u.id
+            LEFT JOIN interactions i ON i.user_id = u.id
+            WHERE u.is_private IS NOT TRUE
+            "#,
+            user_id,
+            suggestions::CANDIDATES_PER_SOURCE,
+            suggestions::HASHTAG_WINDOW_DAYS
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let candidates = rows
+            .into_iter()
+            .map(|row| FollowSuggestion {
+                user: UserProfile {
+                    id: row.id,
+                    username: row.username,
+                    display_name: row.display_name,
+                    bio: row.bio,
+                    avatar_url: row.avatar_url,
+                    location: row.location,
+                    website: row.website,
+                    is_verified: row.is_verified,
+                    is_private: row.is_private,
+                    follower_count: row.follower_count,
+                    following_count: row.following_count,
+                    post_count: row.post_count,
+                    created: row.created,
+                },
+                mutual_follow_count: row.mutual_follow_count,
+                shared_hashtag_count: row.shared_hashtag_count,
+                interaction_count: row.interaction_count,
+            })
+            .collect();
+
+        let mut ranked = suggestions::rank(candidates);
+        ranked.truncate(limit.max(0) as usize);
+        Ok(ranked)
+    }
+
+    /// Keeps `dismissed_id` out of the user's suggestions from now on.
+    /// Returns `false` when there is no such user.
+    pub async fn dismiss_suggestion(&self, user_id: i32, dismissed_id: i32) -> Result<bool> {
+        let dismissed = sqlx::query_scalar!(
+            r#"
+            INSERT INTO dismissed_suggestions (user_id, dismissed_user_id)
+            SELECT $1, id FROM users WHERE id = $2 AND id <> $1
+            ON CONFLICT (user_id, dismissed_user_id) DO UPDATE SET created_at = NOW()
+            RETURNING dismissed_user_id
+            "#,
+            user_id,
+            dismissed_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(dismissed.is_some())
+    }
+}
@@ -0,0 +1,160 @@
+use super::SocialService;
+use crate::db::models::*;
+use crate::service::post_thread;
+use anyhow::Result;
+
+impl SocialService {
+    /// Returns a post with the chain of posts it replies to and a page of its
+    /// replies, each nested up to `max_depth` levels. Deleted replies are left
+    /// out together with everything below them.
+    pub async fn get_post_thread(
+        &self,
+        viewer_id: i32,
+        post_id: i32,
+        limit: i64,
+        offset: i64,
+        max_depth: i32,
+    ) -> Result<Option<PostThread>> {
+        let Some(post) = self.get_post(viewer_id, post_id).await? else {
+            return Ok(None);
+        };
+
+        let ancestors = sqlx::query_as!(
+            Post,
+            r#"
+            WITH RECURSIVE ancestors AS (
+                SELECT id, reply_to_post_id, 0 AS distance
+                FROM posts
+                WHERE id = $1
+                UNION ALL
+                SELECT p.id, p.reply_to_post_id, a.distance + 1
+                FROM posts p
+                INNER JOIN ancestors a ON p.id = a.reply_to_post_id
+            )
+            SELECT p.id, p.user_id, p.content, p.image_url, p.like_count, p.comment_count,
+                   p.repost_count, p.created_at, p.updated_at, p.reply_to_post_id, p.quote_of_post_id, p.is_deleted,
+                   p.deleted_at, p.version, p.moderation_state,
+                   p.edited_at, p.revision_count
+            FROM ancestors a
+            INNER JOIN posts p ON p.id = a.id
+            INNER JOIN users u ON u.id = p.user_id
+            WHERE a.distance > 0 AND (p.is_deleted IS NULL OR p.is_deleted = FALSE)
+              AND (p.moderation_state IS NULL OR p.user_id = $2)
+              AND (p.user_id = $2 OR u.is_private IS NOT TRUE OR EXISTS (
+                  SELECT 1 FROM follows WHERE follower_id = $2 AND following_id = p.user_id
+              ))
+              AND NOT EXISTS (
+                  SELECT 1 FROM blocks b
+                  WHERE (b.blocker_id = $2 AND b.blocked_id = p.user_id)
+                     OR (b.blocker_id = p.user_id AND b.blocked_id = $2)
+              )
+            ORDER BY a.distance DESC
+            "#,
+            post_id,
+            viewer_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let max_depth = max_depth.clamp(1, post_thread::MAX_THREAD_DEPTH);
+        // One extra direct reply tells whether another page exists. Replies
+        // the viewer may not see are dropped along with their subtrees.
+        let rows = sqlx::query!(
+            r#"
+            WITH RECURSIVE page AS (
+                SELECT p.id
+                FROM posts p
+                INNER JOIN users u ON u.id = p.user_id
+                WHERE p.reply_to_post_id = $1 AND (p.is_deleted IS NULL OR p.is_deleted = FALSE)
+                  AND (p.moderation_state IS NULL OR p.user_id = $5)
+                  AND (p.user_id = $5 OR u.is_private IS NOT TRUE OR EXISTS (
+                      SELECT 1 FROM follows WHERE follower_id = $5 AND following_id = p.user_id
+                  ))
+                  AND NOT EXISTS (
+                      SELECT 1 FROM blocks b
+                      WHERE (b.blocker_id = $5 AND b.blocked_id = p.user_id)
+                         OR (b.blocker_id = p.user_id AND b.blocked_id = $5)
+                  )
+                ORDER BY p.created_at ASC, p.id ASC
+                LIMIT $2 OFFSET $3
+            ),
+            tree AS (
+                SELECT p.id, p.user_id, p.content, p.image_url, p.like_count, p.comment_count,
+                       p.repost_count, p.created_at, p.updated_at, p.reply_to_post_id,
+                       p.quote_of_post_id, p.is_deleted, p.deleted_at, p.version, p.moderation_state,
+                       p.edited_at, p.revision_count, 1 AS depth
+                FROM posts p
+                WHERE p.id IN (SELECT id FROM page)
+                UNION ALL
+                SELECT p.id, p.user_id, p.content, p.image_url, p.like_count, p.comment_count,
+                       p.repost_count, p.created_at, p.updated_at, p.reply_to_post_id,
+                       p.quote_of_post_id, p.is_deleted, p.deleted_at, p.version, p.moderation_state,
+                       p.edited_at, p.revision_count, tree.depth + 1
+                FROM posts p
+                INNER JOIN tree ON p.reply_to_post_id = tree.id
+                INNER JOIN users u ON u.id = p.user_id
+                WHERE tree.depth < $4 AND (p.is_deleted IS NULL OR p.is_deleted = FALSE)
+                  AND (p.moderation_state IS NULL OR p.user_id = $5)
+                  AND (p.user_id = $5 OR u.is_private IS NOT TRUE OR EXISTS (
+                      SELECT 1 FROM follows WHERE follower_id = $5 AND following_id = p.user_id
+                  ))
+                  AND NOT EXISTS (
+                      SELECT 1 FROM blocks b
+                      WHERE (b.blocker_id = $5 AND b.blocked_id = p.user_id)
+                         OR (b.blocker_id = p.user_id AND b.blocked_id = $5)
+                  )
+            )
+            SELECT id as "id!", user_id as "user_id!", content as "content!", image_url,
+                   like_count, comment_count, repost_count, created_at as "created_at!",
+                   updated_at as "updated_at!", reply_to_post_id, quote_of_post_id, is_deleted, deleted_at,
+                   version as "version!", moderation_state, edited_at,
+                   revision_count as "revision_count!", depth as "depth!"
+            FROM tree
+            ORDER BY depth, created_at, id
+            "#,
+            post_id,
+            limit + 1,
+            offset,
+            max_depth,
+            viewer_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                let post = Post {
+                    id: row.id,
+                    user_id: row.user_id,
+                    content: row.content,
+                    image_url: row.image_url,
+                    like_count: row.like_count,
+                    comment_count: row.comment_count,
+                    repost_count: row.repost_count,
+                    created_at: row.created_at,
+                    updated_at: row.updated_at,
+                    reply_to_post_id: row.reply_to_post_id,
+                    quote_of_post_id: row.quote_of_post_id,
+                    is_deleted: row.is_deleted,
+                    deleted_at: row.deleted_at,
+                    version: row.version,
+                    moderation_state: row.moderation_state,
+                    edited_at: row.edited_at,
+                    revision_count: row.revision_count,
+                };
+                (post, row.depth)
+            })
+            .collect();
+
+        let (replies, has_more_replies) =
+            post_thread::build_reply_tree(post.id, rows, limit.max(0) as usize);
+
+        Ok(Some(PostThread {
+            ancestors,
+            post,
+            replies,
+            has_more_replies,
+        }))
+    }
+}
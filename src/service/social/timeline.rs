@@ -0,0 +1,757 @@
+use super::SocialService;
+use crate::db::models::*;
+use crate::service::{
+    ranking::{self, Candidate, CandidateSource, RankingWeights},
+    timeline::{self, TimelineCursor, TimelineEventKind},
+};
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use sqlx::{Postgres, Transaction};
+
+/// Flat row of the feed query, split into `FeedEntry` afterwards
+struct FeedRow {
+    id: i32,
+    user_id: i32,
+    content: String,
+    image_url: Option<String>,
+    like_count: Option<i32>,
+    comment_count: Option<i32>,
+    repost_count: Option<i32>,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    reply_to_post_id: Option<i32>,
+    quote_of_post_id: Option<i32>,
+    is_deleted: Option<bool>,
+    deleted_at: Option<NaiveDateTime>,
+    version: i32,
+    moderation_state: Option<String>,
+    edited_at: Option<NaiveDateTime>,
+    revision_count: i32,
+    reposter_id: Option<i32>,
+    reposter_username: Option<String>,
+    reposted_at: Option<NaiveDateTime>,
+}
+
+/// Flat row of the ranking candidate query
+struct CandidateRow {
+    id: i32,
+    user_id: i32,
+    content: String,
+    image_url: Option<String>,
+    like_count: Option<i32>,
+    comment_count: Option<i32>,
+    repost_count: Option<i32>,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    reply_to_post_id: Option<i32>,
+    quote_of_post_id: Option<i32>,
+    is_deleted: Option<bool>,
+    deleted_at: Option<NaiveDateTime>,
+    version: i32,
+    moderation_state: Option<String>,
+    edited_at: Option<NaiveDateTime>,
+    revision_count: i32,
+    source: String,
+    liked_author_posts: i64,
+    commented_author_posts: i64,
+}
+
+impl From<CandidateRow> for Candidate {
+    fn from(row: CandidateRow) -> Self {
+        Self {
+            source: CandidateSource::parse(&row.source).unwrap_or(CandidateSource::Trending),
+            liked_author_posts: row.liked_author_posts,
+            commented_author_posts: row.commented_author_posts,
+            post: Post {
+                id: row.id,
+                user_id: row.user_id,
+                content: row.content,
+                image_url: row.image_url,
+                like_count: row.like_count,
+                comment_count: row.comment_count,
+                repost_count: row.repost_count,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+                reply_to_post_id: row.reply_to_post_id,
+                quote_of_post_id: row.quote_of_post_id,
+                is_deleted: row.is_deleted,
+                deleted_at: row.deleted_at,
+                version: row.version,
+                moderation_state: row.moderation_state,
+                edited_at: row.edited_at,
+                revision_count: row.revision_count,
+            },
+        }
+    }
+}
+
+impl From<FeedRow> for FeedEntry {
+    fn from(row: FeedRow) -> Self {
+        let reposted_by = match (row.reposter_id, row.reposter_username, row.reposted_at) {
+            (Some(user_id), Some(username), Some(reposted_at)) => Some(RepostAttribution {
+                user_id,
+                username,
+                reposted_at,
+            }),
+            _ => None,
+        };
+
+        Self {
+            post: Post {
+                id: row.id,
+                user_id: row.user_id,
+                content: row.content,
+                image_url: row.image_url,
+                like_count: row.like_count,
+                comment_count: row.comment_count,
+                repost_count: row.repost_count,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+                reply_to_post_id: row.reply_to_post_id,
+                quote_of_post_id: row.quote_of_post_id,
+                is_deleted: row.is_deleted,
+                deleted_at: row.deleted_at,
+                version: row.version,
+                moderation_state: row.moderation_state,
+                edited_at: row.edited_at,
+                revision_count: row.revision_count,
+            },
+            reposted_by,
+        }
+    }
+}
+
+impl SocialService {
+    /// Lists posts by and reposts from the accounts the user follows, newest
+    /// activity first. A post reposted by several of them shows up once, for
+    /// the latest repost. With `ReplyMode::Collapse` each conversation appears
+    /// once, as its most recent activity.
+    pub async fn get_feed_posts(
+        &self,
+        user_id: i32,
+        limit: i64,
+        offset: i64,
+        replies: ReplyMode,
+    ) -> Result<Vec<FeedEntry>> {
+        let rows = sqlx::query_as!(
+            FeedRow,
+            r#"
+            WITH activity AS (
+                SELECT p.id AS post_id, p.created_at AS activity_at, NULL::INT4 AS reposter_id
+                FROM posts p
+                INNER JOIN follows f ON p.user_id = f.following_id
+                WHERE f.follower_id = $1
+                UNION ALL
+                SELECT r.post_id, r.created_at, r.user_id
+                FROM reposts r
+                INNER JOIN follows f ON r.user_id = f.following_id
+                WHERE f.follower_id = $1
+            ),
+            latest AS (
+                SELECT DISTINCT ON (CASE WHEN $5 THEN COALESCE(p.root_post_id, p.id) ELSE p.id END)
+                       a.post_id, a.activity_at, a.reposter_id
+                FROM activity a
+                INNER JOIN posts p ON p.id = a.post_id
+                WHERE (p.is_deleted IS NULL OR p.is_deleted = FALSE)
+                  AND (p.moderation_state IS NULL OR p.user_id = $1)
+                  AND (NOT $4 OR p.reply_to_post_id IS NULL)
+                  AND NOT EXISTS (
+                      SELECT 1 FROM blocks b
+                      WHERE (b.blocker_id = $1 AND b.blocked_id = p.user_id)
+                         OR (b.blocker_id = p.user_id AND b.blocked_id = $1)
+                  )
+                  AND NOT EXISTS (
+                      SELECT 1 FROM mutes m
+                      WHERE m.muter_id = $1 AND m.muted_id IN (p.user_id, a.reposter_id)
+                  )
+                ORDER BY CASE WHEN $5 THEN COALESCE(p.root_post_id, p.id) ELSE p.id END,
+                         a.activity_at DESC
+            )
+            SELECT p.id, p.user_id, p.content, p.image_url, p.like_count, p.comment_count,
+                   p.repost_count, p.created_at, p.updated_at, p.reply_to_post_id,
+                   p.quote_of_post_id, p.is_deleted, p.deleted_at, p.version, p.moderation_state,
+                   p.edited_at, p.revision_count,
+                   l.reposter_id as "reposter_id?",
+                   u.username as "reposter_username?",
+                   CASE WHEN l.reposter_id IS NULL THEN NULL ELSE l.activity_at END as "reposted_at?"
+            FROM latest l
+            INNER JOIN posts p ON p.id = l.post_id
+            LEFT JOIN users u ON u.id = l.reposter_id
+            ORDER BY l.activity_at DESC, p.id DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            user_id,
+            limit,
+            offset,
+            replies == ReplyMode::Exclude,
+            replies == ReplyMode::Collapse
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(FeedEntry::from).collect())
+    }
+
+    /// Returns a page of the materialized home timeline, starting after
+    /// `cursor`. Accounts with more than `max_followers` followers are not
+    /// fanned out on write, so their posts and reposts are merged in here.
+    /// Each post sits at its newest activity across both sources, so a post
+    /// that is materialized and also fanned in later shows up on one page
+    /// only. The returned cursor is `None` on the last page.
+    pub async fn get_home_timeline(
+        &self,
+        user_id: i32,
+        cursor: Option<TimelineCursor>,
+        limit: i64,
+        exclude_replies: bool,
+        max_followers: i32,
+    ) -> Result<(Vec<FeedEntry>, Option<TimelineCursor>)> {
+        let before_at = cursor.map(|c| c.activity_at);
+        let before_id = cursor.map(|c| c.post_id);
+
+        let materialized = sqlx::query_as!(
+            FeedRow,
+            r#"
+            WITH large_accounts AS (
+                SELECT f.following_id AS id
+                FROM follows f
+                INNER JOIN users u ON u.id = f.following_id
+                WHERE f.follower_id = $1 AND COALESCE(u.follower_count, 0) > $6
+            ),
+            -- A row pointing at a muted account's repost falls back to the
+            -- post's newest activity from followed accounts that are not
+            -- muted, and is dropped if there is none
+            timeline AS (
+                SELECT h.post_id, a.reposter_id, a.activity_at
+                FROM home_timeline h
+                CROSS JOIN LATERAL (
+                    SELECT h.reposter_id, h.activity_at
+                    WHERE NOT EXISTS (
+                        SELECT 1 FROM mutes m WHERE m.muter_id = $1 AND m.muted_id = h.reposter_id
+                    )
+                    UNION ALL
+                    (SELECT s.reposter_id, s.activity_at
+                     FROM (
+                         SELECT p.user_id AS source_id, NULL::INT4 AS reposter_id,
+                                p.created_at AS activity_at
+                         FROM posts p
+                         WHERE p.id = h.post_id
+                         UNION ALL
+                         SELECT r.user_id, r.user_id, r.created_at
+                         FROM reposts r
+                         WHERE r.post_id = h.post_id
+                     ) s
+                     INNER JOIN follows f ON f.follower_id = $1 AND f.following_id = s.source_id
+                     INNER JOIN users su ON su.id = s.source_id
+                         AND COALESCE(su.follower_count, 0) <= $6
+                     WHERE EXISTS (
+                           SELECT 1 FROM mutes m WHERE m.muter_id = $1 AND m.muted_id = h.reposter_id
+                       )
+                       AND NOT EXISTS (
+                           SELECT 1 FROM mutes m WHERE m.muter_id = $1 AND m.muted_id = s.source_id
+                       )
+                     ORDER BY s.activity_at DESC
+                     LIMIT 1)
+                ) a
+                WHERE h.user_id = $1
+            )
+            SELECT p.id, p.user_id, p.content, p.image_url, p.like_count, p.comment_count,
+                   p.repost_count, p.created_at, p.updated_at, p.reply_to_post_id,
+                   p.quote_of_post_id, p.is_deleted, p.deleted_at, p.version, p.moderation_state,
+                   p.edited_at, p.revision_count,
+                   h.reposter_id as "reposter_id?",
+                   u.username as "reposter_username?",
+                   CASE WHEN h.reposter_id IS NULL THEN NULL ELSE h.activity_at END as "reposted_at?"
+            FROM timeline h
+            INNER JOIN posts p ON p.id = h.post_id
+            LEFT JOIN users u ON u.id = h.reposter_id
+            WHERE p.is_deleted IS NOT TRUE
+              AND (p.moderation_state IS NULL OR p.user_id = $1)
+              AND (NOT $4 OR p.reply_to_post_id IS NULL)
+              AND NOT EXISTS (
+                  SELECT 1 FROM blocks b
+                  WHERE (b.blocker_id = $1 AND b.blocked_id = p.user_id)
+                     OR (b.blocker_id = p.user_id AND b.blocked_id = $1)
+              )
+              AND NOT EXISTS (
+                  SELECT 1 FROM mutes m WHERE m.muter_id = $1 AND m.muted_id = p.user_id
+              )
+              -- A newer repost by a large account moves the post up; it is
+              -- returned by the fan-in query at that position instead
+              AND NOT EXISTS (
+                  SELECT 1 FROM reposts r
+                  WHERE r.post_id = h.post_id AND r.created_at > h.activity_at
+                    AND r.user_id IN (SELECT id FROM large_accounts)
+                    AND NOT EXISTS (
+                        SELECT 1 FROM mutes m WHERE m.muter_id = $1 AND m.muted_id = r.user_id
+                    )
+              )
+              AND ($2::TIMESTAMP IS NULL OR (h.activity_at, h.post_id) < ($2::TIMESTAMP, $3::INT4))
+            ORDER BY h.activity_at DESC, h.post_id DESC
+            LIMIT $5
+            "#,
+            user_id,
+            before_at,
+            before_id,
+            exclude_replies,
+            limit,
+            max_followers
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let fanned_in = sqlx::query_as!(
+            FeedRow,
+            r#"
+            WITH large_accounts AS (
+                SELECT f.following_id AS id
+                FROM follows f
+                INNER JOIN users u ON u.id = f.following_id
+                WHERE f.follower_id = $1 AND COALESCE(u.follower_count, 0) > $6
+            ),
+            activity AS (
+                SELECT p.id AS post_id, p.created_at AS activity_at, NULL::INT4 AS reposter_id
+                FROM posts p
+                WHERE p.user_id IN (SELECT id FROM large_accounts)
+                UNION ALL
+                SELECT r.post_id, r.created_at, r.user_id
+                FROM reposts r
+                WHERE r.user_id IN (SELECT id FROM large_accounts)
+            ),
+            latest AS (
+                SELECT DISTINCT ON (a.post_id) a.post_id, a.activity_at, a.reposter_id
+                FROM activity a
+                INNER JOIN posts p ON p.id = a.post_id
+                WHERE p.is_deleted IS NOT TRUE
+                  AND p.moderation_state IS NULL
+                  AND (NOT $4 OR p.reply_to_post_id IS NULL)
+                  AND NOT EXISTS (
+                      SELECT 1 FROM blocks b
+                      WHERE (b.blocker_id = $1 AND b.blocked_id = p.user_id)
+                         OR (b.blocker_id = p.user_id AND b.blocked_id = $1)
+                  )
+                  AND NOT EXISTS (
+                      SELECT 1 FROM mutes m
+                      WHERE m.muter_id = $1 AND m.muted_id IN (p.user_id, a.reposter_id)
+                  )
+                ORDER BY a.post_id, a.activity_at DESC
+            )
+            SELECT p.id, p.user_id, p.content, p.image_url, p.like_count, p.comment_count,
+                   p.repost_count, p.created_at, p.updated_at, p.reply_to_post_id,
+                   p.quote_of_post_id, p.is_deleted, p.deleted_at, p.version, p.moderation_state,
+                   p.edited_at, p.revision_count,
+                   l.reposter_id as "reposter_id?",
+                   u.username as "reposter_username?",
+                   CASE WHEN l.reposter_id IS NULL THEN NULL ELSE l.activity_at END as "reposted_at?"
+            FROM latest l
+            INNER JOIN posts p ON p.id = l.post_id
+            LEFT JOIN users u ON u.id = l.reposter_id
+            -- Posts with newer materialized activity are returned from there.
+            -- In place of a muted account's repost that is the newest repost
+            -- by a followed account that is not muted and not large.
+            WHERE NOT EXISTS (
+                  SELECT 1 FROM home_timeline h
+                  WHERE h.user_id = $1 AND h.post_id = l.post_id
+                    AND CASE
+                        WHEN EXISTS (
+                            SELECT 1 FROM mutes m
+                            WHERE m.muter_id = $1 AND m.muted_id = h.reposter_id
+                        ) THEN EXISTS (
+                            SELECT 1
+                            FROM reposts r
+                            INNER JOIN follows f
+                                ON f.follower_id = $1 AND f.following_id = r.user_id
+                            INNER JOIN users su ON su.id = r.user_id
+                                AND COALESCE(su.follower_count, 0) <= $6
+                            WHERE r.post_id = l.post_id AND r.created_at > l.activity_at
+                              AND NOT EXISTS (
+                                  SELECT 1 FROM mutes m
+                                  WHERE m.muter_id = $1 AND m.muted_id = r.user_id
+                              )
+                        )
+                        ELSE h.activity_at > l.activity_at
+                    END
+              )
+              AND ($2::TIMESTAMP IS NULL OR (l.activity_at, l.post_id) < ($2::TIMESTAMP, $3::INT4))
+            ORDER BY l.activity_at DESC, l.post_id DESC
+            LIMIT $5
+            "#,
+            user_id,
+            before_at,
+            before_id,
+            exclude_replies,
+            limit,
+            max_followers
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let entries = timeline::merge(
+            materialized.into_iter().map(FeedEntry::from).collect(),
+            fanned_in.into_iter().map(FeedEntry::from).collect(),
+            limit.max(0) as usize,
+        );
+        let next = match entries.last() {
+            Some(last) if entries.len() as i64 == limit => Some(TimelineCursor::after(last)),
+            _ => None,
+        };
+
+        Ok((entries, next))
+    }
+
+    /// Applies up to `batch_size` queued timeline events to `home_timeline`
+    /// and returns how many were handled. Events are claimed with
+    /// `SKIP LOCKED`, so several workers can drain the queue side by side.
+    pub async fn process_timeline_events(
+        &self,
+        batch_size: i64,
+        max_followers: i32,
+    ) -> Result<usize> {
+        let mut tx = self.pool.begin().await?;
+
+        let events = sqlx::query!(
+            r#"
+            SELECT id, kind, actor_id, post_id, target_user_id
+            FROM timeline_events
+            ORDER BY id
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+            "#,
+            batch_size
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for event in &events {
+            match TimelineEventKind::parse(&event.kind) {
+                Some(TimelineEventKind::PostCreated) => {
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO home_timeline (user_id, post_id, reposter_id, activity_at)
+                        SELECT f.follower_id, p.id, NULL, p.created_at
+                        FROM posts p
+                        INNER JOIN users u ON u.id = p.user_id
+                        INNER JOIN follows f ON f.following_id = p.user_id
+                        WHERE p.id = $1
+                          AND p.is_deleted IS NOT TRUE
+                          AND p.moderation_state IS NULL
+                          AND COALESCE(u.follower_count, 0) <= $2
+                        ON CONFLICT (user_id, post_id) DO UPDATE
+                        SET reposter_id = EXCLUDED.reposter_id, activity_at = EXCLUDED.activity_at
+                        WHERE home_timeline.activity_at < EXCLUDED.activity_at
+                        "#,
+                        event.post_id,
+                        max_followers
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                Some(TimelineEventKind::PostDeleted) => {
+                    sqlx::query!(
+                        "DELETE FROM home_timeline WHERE post_id = $1",
+                        event.post_id
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                Some(TimelineEventKind::RepostCreated) => {
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO home_timeline (user_id, post_id, reposter_id, activity_at)
+                        SELECT f.follower_id, r.post_id, r.user_id, r.created_at
+                        FROM reposts r
+                        INNER JOIN posts p ON p.id = r.post_id
+                        INNER JOIN users u ON u.id = r.user_id
+                        INNER JOIN follows f ON f.following_id = r.user_id
+                        WHERE r.user_id = $1 AND r.post_id = $2
+                          AND p.is_deleted IS NOT TRUE
+                          AND p.moderation_state IS NULL
+                          AND COALESCE(u.follower_count, 0) <= $3
+                        ON CONFLICT (user_id, post_id) DO UPDATE
+                        SET reposter_id = EXCLUDED.reposter_id, activity_at = EXCLUDED.activity_at
+                        WHERE home_timeline.activity_at < EXCLUDED.activity_at
+                        "#,
+                        event.actor_id,
+                        event.post_id,
+                        max_followers
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                Some(TimelineEventKind::RepostDeleted) => {
+                    let removed = sqlx::query!(
+                        r#"
+                        DELETE FROM home_timeline
+                        WHERE post_id = $1 AND reposter_id = $2
+                        RETURNING user_id, post_id
+                        "#,
+                        event.post_id,
+                        event.actor_id
+                    )
+                    .fetch_all(&mut *tx)
+                    .await?;
+
+                    let (users, posts) =
+                        removed.iter().map(|row| (row.user_id, row.post_id)).unzip();
+                    Self::refresh_timeline_entries(&mut tx, users, posts, max_followers).await?;
+                }
+                Some(TimelineEventKind::FollowCreated) => {
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO home_timeline (user_id, post_id, reposter_id, activity_at)
+                        SELECT DISTINCT ON (a.post_id) $1::INT4, a.post_id, a.reposter_id, a.activity_at
+                        FROM (
+                            (SELECT p.id AS post_id, NULL::INT4 AS reposter_id, p.created_at AS activity_at
+                             FROM posts p
+                             WHERE p.user_id = $2 AND p.is_deleted IS NOT TRUE
+                               AND p.moderation_state IS NULL
+                             ORDER BY p.created_at DESC
+                             LIMIT $4)
+                            UNION ALL
+                            (SELECT r.post_id, r.user_id, r.created_at
+                             FROM reposts r
+                             INNER JOIN posts p ON p.id = r.post_id
+                             WHERE r.user_id = $2 AND p.is_deleted IS NOT TRUE
+                               AND p.moderation_state IS NULL
+                             ORDER BY r.created_at DESC
+                             LIMIT $4)
+                        ) a
+                        WHERE EXISTS (SELECT 1 FROM follows WHERE follower_id = $1 AND following_id = $2)
+                          AND EXISTS (
+                              SELECT 1 FROM users
+                              WHERE id = $2 AND COALESCE(follower_count, 0) <= $3
+                          )
+                        ORDER BY a.post_id, a.activity_at DESC
+                        ON CONFLICT (user_id, post_id) DO UPDATE
+                        SET reposter_id = EXCLUDED.reposter_id, activity_at = EXCLUDED.activity_at
+                        WHERE home_timeline.activity_at < EXCLUDED.activity_at
+                        "#,
+                        event.actor_id,
+                        event.target_user_id,
+                        max_followers,
+                        timeline::FOLLOW_BACKFILL_LIMIT
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                Some(TimelineEventKind::FollowDeleted) => {
+                    // Rows brought in by the unfollowed account may still be
+                    // reachable through another followed account
+                    let removed = sqlx::query!(
+                        r#"
+                        DELETE FROM home_timeline h
+                        USING posts p
+                        WHERE h.user_id = $1
+                          AND p.id = h.post_id
+                          AND (h.reposter_id = $2 OR (h.reposter_id IS NULL AND p.user_id = $2))
+                          AND NOT EXISTS (
+                              SELECT 1 FROM follows WHERE follower_id = $1 AND following_id = $2
+                          )
+                        RETURNING h.user_id, h.post_id
+                        "#,
+                        event.actor_id,
+                        event.target_user_id
+                    )
+                    .fetch_all(&mut *tx)
+                    .await?;
+
+                    let (users, posts) =
+                        removed.iter().map(|row| (row.user_id, row.post_id)).unzip();
+                    Self::refresh_timeline_entries(&mut tx, users, posts, max_followers).await?;
+                }
+                None => {
+                    tracing::warn!(kind = %event.kind, "Skipping unknown timeline event");
+                }
+            }
+        }
+
+        let ids: Vec<i64> = events.iter().map(|event| event.id).collect();
+        sqlx::query!("DELETE FROM timeline_events WHERE id = ANY($1)", &ids)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(events.len())
+    }
+
+    /// Re-derives the given (reader, post) timeline rows from the posts and
+    /// reposts the reader still follows, keeping the latest one
+    async fn refresh_timeline_entries(
+        tx: &mut Transaction<'_, Postgres>,
+        users: Vec<i32>,
+        posts: Vec<i32>,
+        max_followers: i32,
+    ) -> Result<()> {
+        if users.is_empty() {
+            return Ok(());
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO home_timeline (user_id, post_id, reposter_id, activity_at)
+            SELECT DISTINCT ON (t.user_id, t.post_id) t.user_id, t.post_id, s.reposter_id, s.activity_at
+            FROM UNNEST($1::INT4[], $2::INT4[]) AS t(user_id, post_id)
+            INNER JOIN posts p ON p.id = t.post_id AND p.is_deleted IS NOT TRUE
+                AND p.moderation_state IS NULL
+            INNER JOIN LATERAL (
+                SELECT p.user_id AS source_id, NULL::INT4 AS reposter_id, p.created_at AS activity_at
+                UNION ALL
+                SELECT r.user_id, r.user_id, r.created_at
+                FROM reposts r
+                WHERE r.post_id = t.post_id
+            ) s ON TRUE
+            INNER JOIN follows f ON f.follower_id = t.user_id AND f.following_id = s.source_id
+            INNER JOIN users u ON u.id = s.source_id AND COALESCE(u.follower_count, 0) <= $3
+            ORDER BY t.user_id, t.post_id, s.activity_at DESC
+            ON CONFLICT (user_id, post_id) DO NOTHING
+            "#,
+            &users,
+            &posts,
+            max_followers
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns a page of the ranked "For You" timeline. Candidates are scored
+    /// on every request, so pages are consistent only while scores hold still.
+    pub async fn get_ranked_feed_posts(
+        &self,
+        user_id: i32,
+        weights: &RankingWeights,
+        limit: i64,
+        offset: i64,
+        exclude_replies: bool,
+    ) -> Result<Vec<FeedEntry>> {
+        let candidates = self
+            .get_ranking_candidates(
+                user_id,
+                weights.candidate_window_hours,
+                ranking::CANDIDATES_PER_SOURCE,
+                exclude_replies,
+            )
+            .await?;
+
+        let ranked = ranking::rank(candidates, chrono::Utc::now().naive_utc(), weights);
+
+        Ok(ranked
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .map(|candidate| FeedEntry {
+                post: candidate.post,
+                reposted_by: None,
+            })
+            .collect())
+    }
+
+    /// Collects posts for the ranked timeline from the last `window_hours`:
+    /// posts by followed accounts, by accounts they follow, and the most
+    /// engaging public posts overall. Each source contributes at most
+    /// `per_source_limit` posts, and a post found through several sources
+    /// keeps the closest one.
+    pub async fn get_ranking_candidates(
+        &self,
+        user_id: i32,
+        window_hours: i32,
+        per_source_limit: i64,
+        exclude_replies: bool,
+    ) -> Result<Vec<Candidate>> {
+        let rows = sqlx::query_as!(
+            CandidateRow,
+            r#"
+            WITH followed AS (
+                SELECT following_id AS user_id FROM follows WHERE follower_id = $1
+            ),
+            second_degree AS (
+                SELECT DISTINCT f2.following_id AS user_id
+                FROM follows f1
+                INNER JOIN follows f2 ON f2.follower_id = f1.following_id
+                WHERE f1.follower_id = $1
+                  AND f2.following_id <> $1
+                  AND f2.following_id NOT IN (SELECT user_id FROM followed)
+            ),
+            recent AS (
+                SELECT p.*
+                FROM posts p
+                WHERE p.created_at > NOW() - make_interval(hours => $2)
+                  AND p.user_id <> $1
+                  AND (p.is_deleted IS NULL OR p.is_deleted = FALSE)
+                  AND p.moderation_state IS NULL
+                  AND (NOT $4 OR p.reply_to_post_id IS NULL)
+                  AND NOT EXISTS (
+                      SELECT 1 FROM blocks b
+                      WHERE (b.blocker_id = $1 AND b.blocked_id = p.user_id)
+                         OR (b.blocker_id = p.user_id AND b.blocked_id = $1)
+                  )
+                  AND NOT EXISTS (
+                      SELECT 1 FROM mutes m WHERE m.muter_id = $1 AND m.muted_id = p.user_id
+                  )
+            ),
+            candidates AS (
+                (SELECT r.id, 1 AS priority, 'followed' AS source
+                 FROM recent r
+                 WHERE r.user_id IN (SELECT user_id FROM followed)
+                 ORDER BY r.created_at DESC
+                 LIMIT $3)
+                UNION ALL
+                (SELECT r.id, 2, 'second_degree'
+                 FROM recent r
+                 INNER JOIN users u ON u.id = r.user_id
+                 WHERE r.user_id IN (SELECT user_id FROM second_degree)
+                   AND (u.is_private IS NULL OR u.is_private = FALSE)
+                 ORDER BY r.created_at DESC
+                 LIMIT $3)
+                UNION ALL
+                (SELECT r.id, 3, 'trending'
+                 FROM recent r
+                 INNER JOIN users u ON u.id = r.user_id
+                 WHERE (u.is_private IS NULL OR u.is_private = FALSE)
+                 ORDER BY COALESCE(r.like_count, 0) + 2 * COALESCE(r.comment_count, 0)
+                          + 3 * COALESCE(r.repost_count, 0) DESC, r.created_at DESC
+                 LIMIT $3)
+            ),
+            best AS (
+                SELECT DISTINCT ON (id) id, source
+                FROM candidates
+                ORDER BY id, priority
+            ),
+            liked_authors AS (
+                SELECT lp.user_id AS author_id, COUNT(*) AS total
+                FROM likes l
+                INNER JOIN posts lp ON lp.id = l.post_id
+                WHERE l.user_id = $1
+                GROUP BY lp.user_id
+            ),
+            commented_authors AS (
+                SELECT cp.user_id AS author_id, COUNT(DISTINCT cp.id) AS total
+                FROM comments c
+                INNER JOIN posts cp ON cp.id = c.post_id
+                WHERE c.user_id = $1
+                GROUP BY cp.user_id
+            )
+            SELECT p.id, p.user_id, p.content, p.image_url, p.like_count, p.comment_count,
+                   p.repost_count, p.created_at, p.updated_at, p.reply_to_post_id,
+                   p.quote_of_post_id, p.is_deleted, p.deleted_at, p.version, p.moderation_state,
+                   p.edited_at, p.revision_count,
+                   b.source as "source!",
+                   COALESCE(la.total, 0) as "liked_author_posts!",
+                   COALESCE(ca.total, 0) as "commented_author_posts!"
+            FROM best b
+            INNER JOIN posts p ON p.id = b.id
+            LEFT JOIN liked_authors la ON la.author_id = p.user_id
+            LEFT JOIN commented_authors ca ON ca.author_id = p.user_id
+            "#,
+            user_id,
+            window_hours,
+            per_source_limit,
+            exclude_replies
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Candidate::from).collect())
+    }
+}
@@ -0,0 +1,53 @@
+use std::cmp::Ordering;
+
+use crate::db::models::FollowSuggestion;
+
+/// Suggestions returned unless the client asks otherwise
+pub const DEFAULT_SUGGESTION_PAGE_SIZE: i64 = 10;
+
+/// Most accounts each candidate source contributes before ranking
+pub const CANDIDATES_PER_SOURCE: i64 = 100;
+
+/// How far back hashtag use counts as shared interest
+pub const HASHTAG_WINDOW_DAYS: i32 = 90;
+
+/// Weight of each signal. Counts are log-damped, so a handful of mutual
+/// follows outweighs a large but unrelated following.
+pub const MUTUAL_FOLLOW_WEIGHT: f64 = 3.0;
+pub const INTERACTION_WEIGHT: f64 = 2.0;
+pub const SHARED_HASHTAG_WEIGHT: f64 = 1.5;
+pub const POPULARITY_WEIGHT: f64 = 0.5;
+
+/// Scores a suggestion from its social-graph, interest and popularity signals
+pub fn score(suggestion: &FollowSuggestion) -> f64 {
+    MUTUAL_FOLLOW_WEIGHT * log_count(suggestion.mutual_follow_count)
+        + INTERACTION_WEIGHT * log_count(suggestion.interaction_count)
+        + SHARED_HASHTAG_WEIGHT * log_count(suggestion.shared_hashtag_count)
+        + POPULARITY_WEIGHT * log_count(suggestion.user.follower_count.unwrap_or(0) as i64)
+}
+
+fn log_count(count: i64) -> f64 {
+    (count.max(0) as f64).ln_1p()
+}
+
+/// Orders suggestions by descending score. Ties go to the account with more
+/// followers, then to the older account.
+pub fn rank(suggestions: Vec<FollowSuggestion>) -> Vec<FollowSuggestion> {
+    let mut scored: Vec<(f64, FollowSuggestion)> = suggestions
+        .into_iter()
+        .map(|suggestion| (score(&suggestion), suggestion))
+        .collect();
+
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .partial_cmp(a_score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| b.user.follower_count.cmp(&a.user.follower_count))
+            .then_with(|| a.user.id.cmp(&b.user.id))
+    });
+
+    scored
+        .into_iter()
+        .map(|(_, suggestion)| suggestion)
+        .collect()
+}
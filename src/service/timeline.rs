@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDateTime};
+
+use crate::db::models::FeedEntry;
+
+/// Events the fan-out worker handles per pass
+pub const FANOUT_BATCH_SIZE: i64 = 500;
+
+/// Recent posts and reposts copied into a timeline when a follow is created
+pub const FOLLOW_BACKFILL_LIMIT: i64 = 100;
+
+/// Kind of change queued in `timeline_events`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineEventKind {
+    PostCreated,
+    PostDeleted,
+    RepostCreated,
+    RepostDeleted,
+    FollowCreated,
+    FollowDeleted,
+}
+
+impl TimelineEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimelineEventKind::PostCreated => "post_created",
+            TimelineEventKind::PostDeleted => "post_deleted",
+            TimelineEventKind::RepostCreated => "repost_created",
+            TimelineEventKind::RepostDeleted => "repost_deleted",
+            TimelineEventKind::FollowCreated => "follow_created",
+            TimelineEventKind::FollowDeleted => "follow_deleted",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "post_created" => Some(TimelineEventKind::PostCreated),
+            "post_deleted" => Some(TimelineEventKind::PostDeleted),
+            "repost_created" => Some(TimelineEventKind::RepostCreated),
+            "repost_deleted" => Some(TimelineEventKind::RepostDeleted),
+            "follow_created" => Some(TimelineEventKind::FollowCreated),
+            "follow_deleted" => Some(TimelineEventKind::FollowDeleted),
+            _ => None,
+        }
+    }
+}
+
+/// Moment an entry entered the timeline: the repost if there is one,
+/// otherwise the post itself
+pub fn activity_at(entry: &FeedEntry) -> NaiveDateTime {
+    entry
+        .reposted_by
+        .as_ref()
+        .map(|repost| repost.reposted_at)
+        .unwrap_or(entry.post.created_at)
+}
+
+/// Keyset position in a home timeline, ordered by activity time and then
+/// post id, both descending. Serialized as `<unix micros>_<post id>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimelineCursor {
+    pub activity_at: NaiveDateTime,
+    pub post_id: i32,
+}
+
+impl TimelineCursor {
+    /// Cursor pointing right after `entry`
+    pub fn after(entry: &FeedEntry) -> Self {
+        Self {
+            activity_at: activity_at(entry),
+            post_id: entry.post.id,
+        }
+    }
+}
+
+impl fmt::Display for TimelineCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}_{}",
+            self.activity_at.and_utc().timestamp_micros(),
+            self.post_id
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidTimelineCursor;
+
+impl fmt::Display for InvalidTimelineCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Invalid timeline cursor")
+    }
+}
+
+impl std::error::Error for InvalidTimelineCursor {}
+
+impl FromStr for TimelineCursor {
+    type Err = InvalidTimelineCursor;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (micros, post_id) = value.split_once('_').ok_or(InvalidTimelineCursor)?;
+        let activity_at = micros
+            .parse::<i64>()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or(InvalidTimelineCursor)?
+            .naive_utc();
+        let post_id = post_id.parse::<i32>().map_err(|_| InvalidTimelineCursor)?;
+
+        Ok(Self {
+            activity_at,
+            post_id,
+        })
+    }
+}
+
+/// Merges the materialized page with posts pulled at read time from
+/// accounts too large to fan out. A post present in both keeps its most
+/// recent activity. The result is in timeline order and at most `limit` long.
+pub fn merge(
+    materialized: Vec<FeedEntry>,
+    fanned_in: Vec<FeedEntry>,
+    limit: usize,
+) -> Vec<FeedEntry> {
+    let mut latest: HashMap<i32, FeedEntry> = HashMap::new();
+    for entry in materialized.into_iter().chain(fanned_in) {
+        match latest.get(&entry.post.id) {
+            Some(existing) if activity_at(existing) >= activity_at(&entry) => {}
+            _ => {
+                latest.insert(entry.post.id, entry);
+            }
+        }
+    }
+
+    let mut entries: Vec<FeedEntry> = latest.into_values().collect();
+    entries.sort_by(|a, b| {
+        activity_at(b)
+            .cmp(&activity_at(a))
+            .then(b.post.id.cmp(&a.post.id))
+    });
+    entries.truncate(limit);
+    entries
+}
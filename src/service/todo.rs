@@ -1,12 +1,17 @@
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use thiserror::Error;
+use validator::Validate;
 
 use crate::{
     db::{
-        models::{TodoModel, UpdateTodo, UpdateTodoPartial},
+        models::{TodoModel, TodoRevisionModel, TodoTombstoneModel, UpdateTodo, UpdateTodoPartial},
         DbConnectionPoolError,
     },
-    handlers::todo::models::CreateTodoRequest,
+    handlers::todo::models::{CreateTodoRequest, PartialUpdateTodoRequest, SyncChange},
+    service::{
+        todo_history::{self, RevisionAction},
+        todo_sync::{SyncOutcome, SyncToken, TodoChanges},
+    },
 };
 
 #[derive(Error, Debug)]
@@ -17,6 +22,10 @@ pub enum Error {
     Sqlx(#[from] sqlx::Error),
     #[error("Todo not found")]
     TodoNotFound,
+    #[error("Todo was modified by another request")]
+    PreconditionFailed,
+    #[error("Revision not found")]
+    RevisionNotFound,
 }
 
 pub struct Service {
@@ -38,30 +47,38 @@ impl Service {
         user_id: i32,
         request: CreateTodoRequest,
     ) -> Result<TodoModel, Error> {
+        let mut tx = self.db_pool.begin().await?;
+
         let todo = sqlx::query_as!(
             TodoModel,
             r#"
             INSERT INTO todos (title, description, user_id)
             VALUES ($1, $2, $3)
-            RETURNING id, title, description, created, updated, user_id
+            RETURNING id, title, description, created, updated, user_id,
+                      is_archived, archived_at, is_deleted, deleted_at, version
             "#,
             request.title,
             request.description,
             user_id
         )
-        .fetch_one(&self.db_pool)
+        .fetch_one(&mut *tx)
         .await?;
 
+        Self::record_revision(&mut tx, user_id, RevisionAction::Create, None, &todo, None).await?;
+        tx.commit().await?;
+
         Ok(todo)
     }
 
+    /// Lists the user's active todos, leaving out archived and trashed ones
     pub async fn list(&self, user_id: i32) -> Result<Vec<TodoModel>, Error> {
         let todos = sqlx::query_as!(
             TodoModel,
             r#"
-            SELECT id, title, description, created, updated, user_id
+            SELECT id, title, description, created, updated, user_id,
+                   is_archived, archived_at, is_deleted, deleted_at, version
             FROM todos
-            WHERE user_id = $1
+            WHERE user_id = $1 AND is_deleted = FALSE AND is_archived = FALSE
             ORDER BY created DESC
             "#,
             user_id
@@ -72,13 +89,50 @@ impl Service {
         Ok(todos)
     }
 
+    pub async fn list_archived(&self, user_id: i32) -> Result<Vec<TodoModel>, Error> {
+        let todos = sqlx::query_as!(
+            TodoModel,
+            r#"
+            SELECT id, title, description, created, updated, user_id,
+                   is_archived, archived_at, is_deleted, deleted_at, version
+            FROM todos
+            WHERE user_id = $1 AND is_deleted = FALSE AND is_archived = TRUE
+            ORDER BY archived_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(todos)
+    }
+
+    pub async fn list_trash(&self, user_id: i32) -> Result<Vec<TodoModel>, Error> {
+        let todos = sqlx::query_as!(
+            TodoModel,
+            r#"
+            SELECT id, title, description, created, updated, user_id,
+                   is_archived, archived_at, is_deleted, deleted_at, version
+            FROM todos
+            WHERE user_id = $1 AND is_deleted = TRUE
+            ORDER BY deleted_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(todos)
+    }
+
     pub async fn get(&self, user_id: i32, id: i32) -> Result<TodoModel, Error> {
         let todo = sqlx::query_as!(
             TodoModel,
             r#"
-            SELECT id, title, description, created, updated, user_id
+            SELECT id, title, description, created, updated, user_id,
+                   is_archived, archived_at, is_deleted, deleted_at, version
             FROM todos
-            WHERE id = $1 AND user_id = $2
+            WHERE id = $1 AND user_id = $2 AND is_deleted = FALSE
             "#,
             id,
             user_id
@@ -92,95 +146,561 @@ impl Service {
         }
     }
 
-    pub async fn delete(&self, user_id: i32, id: i32) -> Result<(), Error> {
+    /// Moves a todo to the trash. Trashed todos can be restored until they are purged.
+    ///
+    /// `if_match` works as for [`Service::partial_update`].
+    pub async fn delete(
+        &self,
+        user_id: i32,
+        id: i32,
+        if_match: Option<Vec<i32>>,
+    ) -> Result<(), Error> {
+        let mut tx = self.db_pool.begin().await?;
+        let before = Self::lock(&mut tx, user_id, id, false).await?;
+        Self::check_version(&before, &if_match)?;
+
+        let todo = sqlx::query_as!(
+            TodoModel,
+            r#"
+            UPDATE todos
+            SET is_deleted = TRUE, deleted_at = NOW()
+            WHERE id = $1
+            RETURNING id, title, description, created, updated, user_id,
+                      is_archived, archived_at, is_deleted, deleted_at, version
+            "#,
+            before.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::record_revision(
+            &mut tx,
+            user_id,
+            RevisionAction::Delete,
+            Some(&before),
+            &todo,
+            None,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn restore(&self, user_id: i32, id: i32) -> Result<TodoModel, Error> {
+        let mut tx = self.db_pool.begin().await?;
+        let before = Self::lock(&mut tx, user_id, id, true).await?;
+
+        let todo = sqlx::query_as!(
+            TodoModel,
+            r#"
+            UPDATE todos
+            SET is_deleted = FALSE, deleted_at = NULL
+            WHERE id = $1
+            RETURNING id, title, description, created, updated, user_id,
+                      is_archived, archived_at, is_deleted, deleted_at, version
+            "#,
+            before.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::record_revision(
+            &mut tx,
+            user_id,
+            RevisionAction::Restore,
+            Some(&before),
+            &todo,
+            None,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(todo)
+    }
+
+    /// Archives a todo, typically once it is completed. Archiving an already
+    /// archived todo keeps its original `archived_at`.
+    pub async fn archive(&self, user_id: i32, id: i32) -> Result<TodoModel, Error> {
+        let mut tx = self.db_pool.begin().await?;
+        let before = Self::lock(&mut tx, user_id, id, false).await?;
+        if before.is_archived {
+            return Ok(before);
+        }
+
+        let todo = sqlx::query_as!(
+            TodoModel,
+            r#"
+            UPDATE todos
+            SET is_archived = TRUE, archived_at = NOW()
+            WHERE id = $1
+            RETURNING id, title, description, created, updated, user_id,
+                      is_archived, archived_at, is_deleted, deleted_at, version
+            "#,
+            before.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::record_revision(
+            &mut tx,
+            user_id,
+            RevisionAction::Archive,
+            Some(&before),
+            &todo,
+            None,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(todo)
+    }
+
+    pub async fn unarchive(&self, user_id: i32, id: i32) -> Result<TodoModel, Error> {
+        let mut tx = self.db_pool.begin().await?;
+        let before = Self::lock(&mut tx, user_id, id, false).await?;
+        if !before.is_archived {
+            return Ok(before);
+        }
+
+        let todo = sqlx::query_as!(
+            TodoModel,
+            r#"
+            UPDATE todos
+            SET is_archived = FALSE, archived_at = NULL
+            WHERE id = $1
+            RETURNING id, title, description, created, updated, user_id,
+                      is_archived, archived_at, is_deleted, deleted_at, version
+            "#,
+            before.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::record_revision(
+            &mut tx,
+            user_id,
+            RevisionAction::Unarchive,
+            Some(&before),
+            &todo,
+            None,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(todo)
+    }
+
+    /// Permanently removes todos that have been in the trash for longer than
+    /// `retention_days`. Returns the number of purged rows.
+    pub async fn purge_trash(&self, retention_days: i32) -> Result<u64, Error> {
         let result = sqlx::query!(
             r#"
             DELETE FROM todos
-            WHERE id = $1 AND user_id = $2
+            WHERE is_deleted = TRUE AND deleted_at < NOW() - make_interval(days => $1)
+            "#,
+            retention_days
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Applies a partial update and returns the todo's new version.
+    ///
+    /// When `if_match` is given, the update only happens if the stored version
+    /// is one of the listed versions; otherwise `Error::PreconditionFailed` is returned.
+    pub async fn partial_update(
+        &self,
+        user_id: i32,
+        id: i32,
+        request: UpdateTodoPartial,
+        if_match: Option<Vec<i32>>,
+    ) -> Result<i32, Error> {
+        let mut tx = self.db_pool.begin().await?;
+        let before = Self::lock(&mut tx, user_id, id, false).await?;
+        Self::check_version(&before, &if_match)?;
+
+        if request.title.is_none() && request.description.is_none() {
+            return Ok(before.version);
+        }
+
+        let todo = sqlx::query_as!(
+            TodoModel,
+            r#"
+            UPDATE todos
+            SET title = COALESCE($1, title),
+                description = COALESCE($2, description),
+                version = version + 1,
+                updated = NOW()
+            WHERE id = $3
+            RETURNING id, title, description, created, updated, user_id,
+                      is_archived, archived_at, is_deleted, deleted_at, version
+            "#,
+            request.title,
+            request.description,
+            before.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::record_revision(
+            &mut tx,
+            user_id,
+            RevisionAction::Update,
+            Some(&before),
+            &todo,
+            None,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(todo.version)
+    }
+
+    /// Replaces a todo and returns its new version. See [`Service::partial_update`]
+    /// for how `if_match` is handled.
+    pub async fn update(
+        &self,
+        user_id: i32,
+        id: i32,
+        request: UpdateTodo,
+        if_match: Option<Vec<i32>>,
+    ) -> Result<i32, Error> {
+        let mut tx = self.db_pool.begin().await?;
+        let before = Self::lock(&mut tx, user_id, id, false).await?;
+        Self::check_version(&before, &if_match)?;
+
+        let todo = sqlx::query_as!(
+            TodoModel,
+            r#"
+            UPDATE todos
+            SET title = $1, description = $2, version = version + 1, updated = NOW()
+            WHERE id = $3
+            RETURNING id, title, description, created, updated, user_id,
+                      is_archived, archived_at, is_deleted, deleted_at, version
             "#,
+            request.title,
+            request.description,
+            before.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::record_revision(
+            &mut tx,
+            user_id,
+            RevisionAction::Update,
+            Some(&before),
+            &todo,
+            None,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(todo.version)
+    }
+
+    /// Returns the change history of a todo, newest first. Trashed todos keep
+    /// their history until they are purged.
+    pub async fn history(&self, user_id: i32, id: i32) -> Result<Vec<TodoRevisionModel>, Error> {
+        let owned = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM todos WHERE id = $1 AND user_id = $2) as "exists!""#,
             id,
             user_id
         )
-        .execute(&self.db_pool)
+        .fetch_one(&self.db_pool)
         .await?;
 
-        if result.rows_affected() == 0 {
+        if !owned {
             return Err(Error::TodoNotFound);
         }
 
-        Ok(())
+        let revisions = sqlx::query_as!(
+            TodoRevisionModel,
+            r#"
+            SELECT id, todo_id, actor_id, action, changes, reverted_to_revision_id, created_at
+            FROM todo_revisions
+            WHERE todo_id = $1
+            ORDER BY id DESC
+            "#,
+            id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(revisions)
     }
 
-    pub async fn partial_update(
+    /// Restores the title and description a todo had right after `revision_id`.
+    /// The revert itself is recorded as a new revision.
+    pub async fn revert(
         &self,
         user_id: i32,
         id: i32,
-        request: UpdateTodoPartial,
-    ) -> Result<(), Error> {
-        if request.title.is_none() && request.description.is_none() {
-            return Ok(());
+        revision_id: i32,
+    ) -> Result<TodoModel, Error> {
+        let mut tx = self.db_pool.begin().await?;
+        let before = Self::lock(&mut tx, user_id, id, false).await?;
+
+        let revisions = sqlx::query_as!(
+            TodoRevisionModel,
+            r#"
+            SELECT id, todo_id, actor_id, action, changes, reverted_to_revision_id, created_at
+            FROM todo_revisions
+            WHERE todo_id = $1 AND id <= $2
+            ORDER BY id ASC
+            "#,
+            id,
+            revision_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if revisions.last().map(|revision| revision.id) != Some(revision_id) {
+            return Err(Error::RevisionNotFound);
         }
+        let snapshot = todo_history::replay(&revisions).ok_or(Error::RevisionNotFound)?;
+
+        let todo = sqlx::query_as!(
+            TodoModel,
+            r#"
+            UPDATE todos
+            SET title = $1, description = $2, version = version + 1, updated = NOW()
+            WHERE id = $3
+            RETURNING id, title, description, created, updated, user_id,
+                      is_archived, archived_at, is_deleted, deleted_at, version
+            "#,
+            snapshot.title,
+            snapshot.description,
+            before.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::record_revision(
+            &mut tx,
+            user_id,
+            RevisionAction::Revert,
+            Some(&before),
+            &todo,
+            Some(revision_id),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(todo)
+    }
+
+    /// Returns the todos created, updated and deleted since `since`, or every
+    /// todo when `since` is `None`, along with the token for the next sync.
+    pub async fn sync_changes(
+        &self,
+        user_id: i32,
+        since: Option<SyncToken>,
+    ) -> Result<TodoChanges, Error> {
+        // All reads share one snapshot so the token matches what was returned
+        let mut tx = self.db_pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ")
+            .execute(&mut *tx)
+            .await?;
+
+        let sync_token = Self::snapshot_token(&mut tx).await?;
+        let since = since.map(|token| token.xid());
+
+        let created = sqlx::query_as!(
+            TodoModel,
+            r#"
+            SELECT id, title, description, created, updated, user_id,
+                   is_archived, archived_at, is_deleted, deleted_at, version
+            FROM todos
+            WHERE user_id = $1 AND is_deleted = FALSE AND ($2::INT8 IS NULL OR created_xid >= $2)
+            ORDER BY id
+            "#,
+            user_id,
+            since
+        )
+        .fetch_all(&mut *tx)
+        .await?;
 
-        let result = match (&request.title, &request.description) {
-            (Some(title), Some(description)) => {
-                sqlx::query!(
-                    "UPDATE todos SET title = $1, description = $2, updated = NOW() WHERE id = $3 AND user_id = $4",
-                    title,
-                    description,
-                    id,
-                    user_id
+        let (updated, deleted) = match since {
+            Some(since) => {
+                let updated = sqlx::query_as!(
+                    TodoModel,
+                    r#"
+                    SELECT id, title, description, created, updated, user_id,
+                           is_archived, archived_at, is_deleted, deleted_at, version
+                    FROM todos
+                    WHERE user_id = $1 AND is_deleted = FALSE
+                      AND created_xid < $2 AND sync_xid >= $2
+                    ORDER BY id
+                    "#,
+                    user_id,
+                    since
                 )
-                .execute(&self.db_pool)
-                .await?
-            }
-            (Some(title), None) => {
-                sqlx::query!(
-                    "UPDATE todos SET title = $1, updated = NOW() WHERE id = $2 AND user_id = $3",
-                    title,
-                    id,
-                    user_id
+                .fetch_all(&mut *tx)
+                .await?;
+
+                // Trashed todos are gone from the client's point of view, as are
+                // todos that were purged for good
+                let deleted = sqlx::query_as!(
+                    TodoTombstoneModel,
+                    r#"
+                    SELECT id as "todo_id!", deleted_at as "deleted_at!"
+                    FROM todos
+                    WHERE user_id = $1 AND is_deleted = TRUE AND sync_xid >= $2
+                    UNION ALL
+                    SELECT todo_id, deleted_at
+                    FROM todo_tombstones
+                    WHERE user_id = $1 AND sync_xid >= $2
+                    ORDER BY 1
+                    "#,
+                    user_id,
+                    since
                 )
-                .execute(&self.db_pool)
-                .await?
+                .fetch_all(&mut *tx)
+                .await?;
+
+                (updated, deleted)
             }
-            (None, Some(description)) => {
-                sqlx::query!(
-                    "UPDATE todos SET description = $1, updated = NOW() WHERE id = $2 AND user_id = $3",
-                    description,
-                    id,
-                    user_id
-                )
-                .execute(&self.db_pool)
-                .await?
+            None => (Vec::new(), Vec::new()),
+        };
+
+        tx.commit().await?;
+
+        Ok(TodoChanges {
+            created,
+            updated,
+            deleted,
+            sync_token,
+        })
+    }
+
+    /// Applies a change made by an offline client. Updates and deletes only go
+    /// through if the todo is still at the client's `base_version`.
+    pub async fn apply_sync_change(
+        &self,
+        user_id: i32,
+        change: SyncChange,
+    ) -> Result<SyncOutcome, Error> {
+        let (id, result) = match change {
+            SyncChange::Create {
+                title, description, ..
+            } => {
+                let request = CreateTodoRequest { title, description };
+                if let Err(validation_errors) = request.validate() {
+                    return Ok(SyncOutcome::Invalid(format!(
+                        "Validation error: {}",
+                        validation_errors
+                    )));
+                }
+                let todo = self.create(user_id, request).await?;
+                return Ok(SyncOutcome::Applied(Some(todo)));
+            }
+            SyncChange::Update {
+                id,
+                base_version,
+                title,
+                description,
+            } => {
+                let request = PartialUpdateTodoRequest { title, description };
+                if let Err(validation_errors) = request.validate() {
+                    return Ok(SyncOutcome::Invalid(format!(
+                        "Validation error: {}",
+                        validation_errors
+                    )));
+                }
+                let result = self
+                    .partial_update(user_id, id as i32, request.into(), Some(vec![base_version]))
+                    .await;
+                (id as i32, result.map(Some))
+            }
+            SyncChange::Delete { id, base_version } => {
+                let result = self
+                    .delete(user_id, id as i32, Some(vec![base_version]))
+                    .await;
+                (id as i32, result.map(|_| None))
             }
-            (None, None) => return Ok(()),
         };
 
-        if result.rows_affected() == 0 {
-            return Err(Error::TodoNotFound);
+        match result {
+            Ok(Some(_)) => Ok(SyncOutcome::Applied(Some(self.get(user_id, id).await?))),
+            Ok(None) => Ok(SyncOutcome::Applied(None)),
+            Err(Error::PreconditionFailed) => match self.get(user_id, id).await {
+                Ok(todo) => Ok(SyncOutcome::Conflict(todo)),
+                Err(Error::TodoNotFound) => Ok(SyncOutcome::NotFound),
+                Err(error) => Err(error),
+            },
+            Err(Error::TodoNotFound) => Ok(SyncOutcome::NotFound),
+            Err(error) => Err(error),
         }
+    }
 
-        Ok(())
+    async fn snapshot_token(tx: &mut Transaction<'_, Postgres>) -> Result<SyncToken, Error> {
+        let xid = sqlx::query_scalar!(
+            r#"SELECT pg_snapshot_xmin(pg_current_snapshot())::text::INT8 as "xid!""#
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(SyncToken::new(xid))
     }
 
-    pub async fn update(&self, user_id: i32, id: i32, request: UpdateTodo) -> Result<(), Error> {
-        let result = sqlx::query!(
+    /// Loads a todo and locks its row for the rest of the transaction
+    async fn lock(
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+        id: i32,
+        is_deleted: bool,
+    ) -> Result<TodoModel, Error> {
+        let todo = sqlx::query_as!(
+            TodoModel,
             r#"
-            UPDATE todos 
-            SET title = $1, description = $2, updated = NOW()
-            WHERE id = $3 AND user_id = $4
+            SELECT id, title, description, created, updated, user_id,
+                   is_archived, archived_at, is_deleted, deleted_at, version
+            FROM todos
+            WHERE id = $1 AND user_id = $2 AND is_deleted = $3
+            FOR UPDATE
             "#,
-            request.title,
-            request.description,
             id,
-            user_id
+            user_id,
+            is_deleted
         )
-        .execute(&self.db_pool)
+        .fetch_optional(&mut **tx)
         .await?;
 
-        if result.rows_affected() == 0 {
-            return Err(Error::TodoNotFound);
+        todo.ok_or(Error::TodoNotFound)
+    }
+
+    fn check_version(todo: &TodoModel, if_match: &Option<Vec<i32>>) -> Result<(), Error> {
+        match if_match {
+            Some(versions) if !versions.contains(&todo.version) => Err(Error::PreconditionFailed),
+            _ => Ok(()),
         }
+    }
+
+    async fn record_revision(
+        tx: &mut Transaction<'_, Postgres>,
+        actor_id: i32,
+        action: RevisionAction,
+        before: Option<&TodoModel>,
+        after: &TodoModel,
+        reverted_to_revision_id: Option<i32>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO todo_revisions (todo_id, actor_id, action, changes, reverted_to_revision_id)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            after.id,
+            actor_id,
+            action.as_str(),
+            todo_history::diff(before, Some(after)),
+            reverted_to_revision_id
+        )
+        .execute(&mut **tx)
+        .await?;
 
         Ok(())
     }
@@ -0,0 +1,91 @@
+use serde_json::{json, Map, Value};
+
+use crate::db::models::{TodoModel, TodoRevisionModel};
+
+/// Kind of change recorded in `todo_revisions`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevisionAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+    Archive,
+    Unarchive,
+    Revert,
+}
+
+impl RevisionAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RevisionAction::Create => "create",
+            RevisionAction::Update => "update",
+            RevisionAction::Delete => "delete",
+            RevisionAction::Restore => "restore",
+            RevisionAction::Archive => "archive",
+            RevisionAction::Unarchive => "unarchive",
+            RevisionAction::Revert => "revert",
+        }
+    }
+}
+
+fn tracked_fields(todo: Option<&TodoModel>) -> [(&'static str, Value); 4] {
+    [
+        ("title", todo.map(|t| json!(t.title)).unwrap_or(Value::Null)),
+        (
+            "description",
+            todo.map(|t| json!(t.description)).unwrap_or(Value::Null),
+        ),
+        (
+            "is_archived",
+            todo.map(|t| json!(t.is_archived)).unwrap_or(Value::Null),
+        ),
+        (
+            "is_deleted",
+            todo.map(|t| json!(t.is_deleted)).unwrap_or(Value::Null),
+        ),
+    ]
+}
+
+/// Builds the field-level diff stored with a revision, shaped as
+/// `{"field": {"from": old, "to": new}}`. Only changed fields are included;
+/// `before` is `None` for a newly created todo.
+pub fn diff(before: Option<&TodoModel>, after: Option<&TodoModel>) -> Value {
+    let mut changes = Map::new();
+    for ((field, from), (_, to)) in tracked_fields(before)
+        .into_iter()
+        .zip(tracked_fields(after))
+    {
+        if from != to {
+            changes.insert(field.to_string(), json!({ "from": from, "to": to }));
+        }
+    }
+    Value::Object(changes)
+}
+
+/// Title and description of a todo as of a given revision
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentSnapshot {
+    pub title: String,
+    pub description: String,
+}
+
+/// Replays revision diffs oldest-first and returns the content as it was
+/// right after the last revision in `revisions`
+pub fn replay(revisions: &[TodoRevisionModel]) -> Option<ContentSnapshot> {
+    let mut title = None;
+    let mut description = None;
+
+    for revision in revisions {
+        if let Some(value) = revision.changes["title"]["to"].as_str() {
+            title = Some(value.to_string());
+        }
+        if let Some(value) = revision.changes["description"]["to"].as_str() {
+            description = Some(value.to_string());
+        }
+    }
+
+    Some(ContentSnapshot {
+        title: title?,
+        description: description?,
+    })
+}
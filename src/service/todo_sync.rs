@@ -0,0 +1,75 @@
+use std::{fmt, str::FromStr};
+
+use crate::db::models::{TodoModel, TodoTombstoneModel};
+
+/// Cursor handed out to sync clients.
+///
+/// Wraps the oldest transaction id that was still in flight when the changes
+/// were read. Rows are stamped with the id of the transaction that wrote them,
+/// so asking for everything stamped at or after the token never misses a
+/// change that committed late; at worst a change is delivered twice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncToken(i64);
+
+impl SyncToken {
+    pub fn new(xid: i64) -> Self {
+        Self(xid)
+    }
+
+    pub fn xid(&self) -> i64 {
+        self.0
+    }
+}
+
+impl fmt::Display for SyncToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidSyncToken;
+
+impl fmt::Display for InvalidSyncToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Invalid sync token")
+    }
+}
+
+impl std::error::Error for InvalidSyncToken {}
+
+impl FromStr for SyncToken {
+    type Err = InvalidSyncToken;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.len() != 16 {
+            return Err(InvalidSyncToken);
+        }
+        i64::from_str_radix(value, 16)
+            .ok()
+            .filter(|xid| *xid >= 0)
+            .map(SyncToken)
+            .ok_or(InvalidSyncToken)
+    }
+}
+
+/// Todos that changed since a sync token
+#[derive(Debug)]
+pub struct TodoChanges {
+    pub created: Vec<TodoModel>,
+    pub updated: Vec<TodoModel>,
+    pub deleted: Vec<TodoTombstoneModel>,
+    pub sync_token: SyncToken,
+}
+
+/// What happened to a single change pushed by a client
+#[derive(Debug)]
+pub enum SyncOutcome {
+    /// The change was applied. Carries the todo as stored, or `None` for deletions.
+    Applied(Option<TodoModel>),
+    /// The todo changed on the server since the client's base version.
+    /// Carries the server copy so the client can merge.
+    Conflict(TodoModel),
+    NotFound,
+    Invalid(String),
+}
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::service::{self, events, media, scheduled_posts, timeline};
+
+/// How often the trash purge worker wakes up
+const TRASH_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Spawns a background task that periodically deletes todos which have been
+/// in the trash for longer than `retention_days`
+pub fn spawn_todo_trash_purger(
+    todo_service: Arc<service::todo::Service>,
+    retention_days: i32,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TRASH_PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match todo_service.purge_trash(retention_days).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!(purged, "Purged expired todos from trash"),
+                Err(e) => tracing::error!("Failed to purge todo trash: {}", e),
+            }
+        }
+    })
+}
+
+/// How often the timeline fan-out worker polls for new events
+const TIMELINE_FANOUT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Spawns a background task that applies queued post, repost and follow
+/// events to the materialized home timelines
+pub fn spawn_timeline_fanout_worker(
+    social_service: Arc<service::social::SocialService>,
+    max_followers: i32,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TIMELINE_FANOUT_INTERVAL);
+        loop {
+            interval.tick().await;
+            // Keep draining while batches come back full
+            loop {
+                match social_service
+                    .process_timeline_events(timeline::FANOUT_BATCH_SIZE, max_followers)
+                    .await
+                {
+                    Ok(handled) if (handled as i64) < timeline::FANOUT_BATCH_SIZE => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::error!("Failed to fan out timeline events: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Delay before reconnecting a failed event listener
+const EVENT_LISTENER_RETRY: Duration = Duration::from_secs(5);
+
+/// How often old realtime events are pruned
+const EVENT_PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Spawns a background task that relays realtime events from Postgres to the
+/// streams connected to this instance, reconnecting whenever it fails
+pub fn spawn_event_listener(
+    event_service: Arc<service::events::EventService>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = event_service.listen().await {
+                tracing::error!("Event listener failed: {}", e);
+            }
+            tokio::time::sleep(EVENT_LISTENER_RETRY).await;
+        }
+    })
+}
+
+/// Spawns a background task that deletes realtime events too old to replay
+pub fn spawn_event_pruner(
+    event_service: Arc<service::events::EventService>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(EVENT_PRUNE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match event_service.prune(events::EVENT_RETENTION_HOURS).await {
+                Ok(0) => {}
+                Ok(pruned) => tracing::info!(pruned, "Pruned expired stream events"),
+                Err(e) => tracing::error!("Failed to prune stream events: {}", e),
+            }
+        }
+    })
+}
+
+/// How often unattached uploads and deleted files are cleaned up
+const MEDIA_CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Spawns a background task that deletes uploads no post or profile uses
+/// any more, for example after their post was hard-deleted, and removes the
+/// files of deleted media from the blob store
+pub fn spawn_media_cleanup(
+    media_service: Arc<service::media::MediaService>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(MEDIA_CLEANUP_INTERVAL);
+        loop {
+            interval.tick().await;
+            match media_service
+                .delete_orphans(media::ORPHAN_GRACE_HOURS)
+                .await
+            {
+                Ok(0) => {}
+                Ok(deleted) => tracing::info!(deleted, "Deleted unattached media"),
+                Err(e) => tracing::error!("Failed to delete unattached media: {}", e),
+            }
+            // Keep draining while batches come back full
+            loop {
+                match media_service
+                    .process_deletions(media::DELETION_BATCH_SIZE)
+                    .await
+                {
+                    Ok(removed) if (removed as i64) < media::DELETION_BATCH_SIZE => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::error!("Failed to remove deleted media files: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// How often the publishing worker looks for scheduled posts that are due
+const SCHEDULED_POST_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Spawns a background task that publishes scheduled posts once their
+/// publish time has passed
+pub fn spawn_scheduled_post_publisher(
+    social_service: Arc<service::social::SocialService>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SCHEDULED_POST_INTERVAL);
+        loop {
+            interval.tick().await;
+            // Keep draining while batches come back full
+            loop {
+                match social_service
+                    .publish_due_posts(scheduled_posts::PUBLISH_BATCH_SIZE)
+                    .await
+                {
+                    Ok(handled) if (handled as i64) < scheduled_posts::PUBLISH_BATCH_SIZE => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::error!("Failed to publish scheduled posts: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
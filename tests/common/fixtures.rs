@@ -1,10 +1,16 @@
-use fake::{Fake, Faker};
+// Each test binary uses a different subset of these fixtures
+#![allow(dead_code)]
+
+use chrono::Utc;
 use fake::faker::internet::en::Username;
-use fake::faker::lorem::en::{Sentence, Paragraph};
-use fake::faker::name::en::Name;
+use fake::faker::lorem::en::{Paragraph, Sentence};
+use fake::Fake;
 use serde_json::{json, Value};
+use todo_api::db::models::{Comment, Post, UserProfile};
 use todo_api::handlers::auth::models::{LoginRequest, RegistrationRequest};
-use todo_api::handlers::todo::models::{CreateTodoRequest, UpdateTodoRequest, PartialUpdateTodoRequest};
+use todo_api::handlers::todo::models::{
+    CreateTodoRequest, PartialUpdateTodoRequest, UpdateTodoRequest,
+};
 
 /// Generate test user data
 pub struct UserFixture;
@@ -133,7 +139,10 @@ pub struct DatabaseFixture;
 
 impl DatabaseFixture {
     pub fn sample_user_data() -> (String, String) {
-        ("test_user_123".to_string(), "hashed_password_123".to_string())
+        (
+            "test_user_123".to_string(),
+            "hashed_password_123".to_string(),
+        )
     }
 
     pub fn sample_todo_data(user_id: i32) -> (String, String, i32) {
@@ -154,10 +163,12 @@ impl PerformanceFixture {
     }
 
     pub fn concurrent_users(count: usize) -> Vec<RegistrationRequest> {
-        (0..count).map(|i| RegistrationRequest {
-            username: format!("user_{}", i),
-            password: "test_password123".to_string(),
-        }).collect()
+        (0..count)
+            .map(|i| RegistrationRequest {
+                username: format!("user_{}", i),
+                password: "test_password123".to_string(),
+            })
+            .collect()
     }
 }
 
@@ -188,4 +199,69 @@ impl SecurityFixture {
             description: "B".repeat(100000),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Social model fixtures. Tests override the fields they care about with
+/// struct update syntax, so new columns only need a default here.
+pub struct SocialFixture;
+
+impl SocialFixture {
+    pub fn post(id: i32, user_id: i32) -> Post {
+        let now = Utc::now().naive_utc();
+        Post {
+            id,
+            user_id,
+            content: format!("post {}", id),
+            image_url: None,
+            like_count: Some(0),
+            comment_count: Some(0),
+            repost_count: Some(0),
+            created_at: now,
+            updated_at: now,
+            reply_to_post_id: None,
+            quote_of_post_id: None,
+            is_deleted: Some(false),
+            deleted_at: None,
+            version: 1,
+            moderation_state: None,
+            edited_at: None,
+            revision_count: 0,
+        }
+    }
+
+    pub fn comment(id: i32, post_id: i32, user_id: i32) -> Comment {
+        let now = Utc::now().naive_utc();
+        Comment {
+            id,
+            user_id,
+            post_id,
+            content: format!("comment {}", id),
+            like_count: Some(0),
+            created_at: now,
+            updated_at: now,
+            reply_to_comment_id: None,
+            is_deleted: Some(false),
+            deleted_at: None,
+            version: 1,
+            moderation_state: None,
+        }
+    }
+
+    pub fn user(id: i32) -> UserProfile {
+        UserProfile {
+            id,
+            username: format!("user{}", id),
+            display_name: None,
+            bio: None,
+            avatar_url: None,
+            location: None,
+            website: None,
+            is_verified: Some(false),
+            is_private: Some(false),
+            follower_count: Some(0),
+            following_count: Some(0),
+            post_count: Some(0),
+            created: Utc::now().naive_utc(),
+        }
+    }
+}
@@ -44,6 +44,11 @@ fn create_test_todo_model() -> TodoModel {
         created: Utc::now().naive_utc(),
         updated: Utc::now().naive_utc(),
         user_id: 1,
+        is_archived: false,
+        archived_at: None,
+        is_deleted: false,
+        deleted_at: None,
+        version: 1,
     }
 }
 
@@ -215,6 +220,11 @@ async fn test_complete_todo_lifecycle() {
         created: Utc::now().naive_utc(),
         updated: Utc::now().naive_utc(),
         user_id: context_user.sub,
+        is_archived: false,
+        archived_at: None,
+        is_deleted: false,
+        deleted_at: None,
+        version: 1,
     };
 
     // 5. Convert to response model
@@ -0,0 +1,824 @@
+//! Exercises `SocialService` against a real database: the SQL triggers, the
+//! concurrent paths and the queries that only make sense with real rows.
+//! Each test is skipped when `DATABASE_URL` is unset.
+
+use futures::future::join_all;
+use serial_test::serial;
+use sqlx::PgPool;
+use std::sync::Arc;
+use todo_api::db::models::{
+    CreateComment, CreatePost, FeedEntry, NewPoll, NotificationKind, ReplyMode,
+};
+use todo_api::db::schema;
+use todo_api::service::moderation::RuleFilter;
+use todo_api::service::ranking::RankingWeights;
+use todo_api::service::social::{SocialError, SocialService};
+
+/// Connects to `DATABASE_URL` and brings the schema up to date, or returns
+/// `None` when no database is configured
+async fn setup() -> Option<SocialService> {
+    let Ok(database_url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set, skipping");
+        return None;
+    };
+
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to DATABASE_URL");
+    schema::initialize_schema(&pool)
+        .await
+        .expect("Failed to initialize the schema");
+
+    Some(SocialService::new(
+        pool,
+        "http://localhost:8080/media".to_string(),
+        Arc::new(RuleFilter::default()),
+    ))
+}
+
+/// Creates `count` users with unique names and returns their ids
+async fn create_users(pool: &PgPool, count: usize) -> Vec<i32> {
+    let prefix = uuid::Uuid::new_v4().simple().to_string();
+    let mut ids = Vec::with_capacity(count);
+    for i in 0..count {
+        let id = sqlx::query_scalar(
+            "INSERT INTO users (username, password) VALUES ($1, 'hashed_password') RETURNING id",
+        )
+        .bind(format!("test_{}_{}", &prefix[..12], i))
+        .fetch_one(pool)
+        .await
+        .expect("Failed to create a user");
+        ids.push(id);
+    }
+    ids
+}
+
+/// Removes the users and, through the cascades, everything they created
+async fn cleanup(pool: &PgPool, user_ids: &[i32]) {
+    sqlx::query("DELETE FROM users WHERE id = ANY($1)")
+        .bind(user_ids)
+        .execute(pool)
+        .await
+        .expect("Failed to clean up users");
+}
+
+fn new_post(content: &str) -> CreatePost {
+    CreatePost {
+        content: content.to_string(),
+        image_url: None,
+        media: Vec::new(),
+        reply_to_post_id: None,
+        quote_of_post_id: None,
+        poll: None,
+    }
+}
+
+async fn comment_count(pool: &PgPool, post_id: i32) -> i32 {
+    sqlx::query_scalar("SELECT COALESCE(comment_count, 0) FROM posts WHERE id = $1")
+        .bind(post_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn test_comment_count_trigger_follows_concurrent_comments_and_deletes() {
+    let Some(service) = setup().await else {
+        return;
+    };
+    let users = create_users(&service.pool, 6).await;
+    let author = users[0];
+    let post = service
+        .create_post(author, new_post("hello"))
+        .await
+        .unwrap();
+
+    let comments = join_all(users[1..].iter().map(|&user_id| {
+        service.create_comment(
+            user_id,
+            CreateComment {
+                post_id: post.id,
+                content: "first!".to_string(),
+                reply_to_comment_id: None,
+            },
+        )
+    }))
+    .await;
+    let comments: Vec<_> = comments
+        .into_iter()
+        .map(|comment| comment.unwrap().expect("The post is visible"))
+        .collect();
+    assert_eq!(comment_count(&service.pool, post.id).await, 5);
+
+    // Soft deletes decrement once; deleting again changes nothing
+    assert!(service
+        .delete_comment(comments[0].id, comments[0].user_id)
+        .await
+        .unwrap());
+    assert!(!service
+        .delete_comment(comments[0].id, comments[0].user_id)
+        .await
+        .unwrap());
+    assert_eq!(comment_count(&service.pool, post.id).await, 4);
+
+    // Restoring a comment counts it again
+    sqlx::query("UPDATE comments SET is_deleted = FALSE, deleted_at = NULL WHERE id = $1")
+        .bind(comments[0].id)
+        .execute(&service.pool)
+        .await
+        .unwrap();
+    assert_eq!(comment_count(&service.pool, post.id).await, 5);
+
+    // Hard deletes of soft-deleted comments are not counted twice
+    service
+        .delete_comment(comments[1].id, comments[1].user_id)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM comments WHERE id = ANY($1)")
+        .bind(vec![comments[1].id, comments[2].id])
+        .execute(&service.pool)
+        .await
+        .unwrap();
+    assert_eq!(comment_count(&service.pool, post.id).await, 3);
+
+    cleanup(&service.pool, &users).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_poll_tallies_count_concurrent_votes_once() {
+    let Some(service) = setup().await else {
+        return;
+    };
+    let users = create_users(&service.pool, 9).await;
+    let author = users[0];
+    let post = service
+        .create_post(
+            author,
+            CreatePost {
+                poll: Some(NewPoll {
+                    options: vec!["Tea".to_string(), "Coffee".to_string()],
+                    duration_minutes: 60,
+                }),
+                ..new_post("Tea or coffee?")
+            },
+        )
+        .await
+        .unwrap();
+    let option_ids: Vec<i32> = sqlx::query_scalar(
+        "SELECT o.id FROM poll_options o INNER JOIN polls p ON p.id = o.poll_id \
+         WHERE p.post_id = $1 ORDER BY o.position",
+    )
+    .bind(post.id)
+    .fetch_all(&service.pool)
+    .await
+    .unwrap();
+
+    // Voters 1-6 pick tea and 7-8 coffee, all at once
+    let voters = &users[1..];
+    let results = join_all(voters.iter().enumerate().map(|(i, &user_id)| {
+        let option_id = if i < 6 { option_ids[0] } else { option_ids[1] };
+        service.vote_in_poll(user_id, post.id, option_id)
+    }))
+    .await;
+    for result in results {
+        assert!(result.unwrap().is_some());
+    }
+
+    // A second vote, even racing with itself, is rejected and not counted
+    let repeats =
+        join_all((0..3).map(|_| service.vote_in_poll(voters[0], post.id, option_ids[1]))).await;
+    for repeat in repeats {
+        let error = repeat.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<SocialError>(),
+            Some(SocialError::AlreadyVoted)
+        ));
+    }
+
+    let poll = service
+        .vote_in_poll(author, post.id, option_ids[1])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(poll.vote_count, 9);
+    let tallies: Vec<Option<i32>> = poll.options.iter().map(|o| o.vote_count).collect();
+    assert_eq!(tallies, vec![Some(6), Some(3)]);
+
+    cleanup(&service.pool, &users).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_concurrent_likes_share_one_notification_group() {
+    let Some(service) = setup().await else {
+        return;
+    };
+    let users = create_users(&service.pool, 6).await;
+    let author = users[0];
+    let post = service
+        .create_post(author, new_post("like me"))
+        .await
+        .unwrap();
+
+    let likes = join_all(
+        users[1..]
+            .iter()
+            .map(|&user_id| service.like_post(user_id, post.id)),
+    )
+    .await;
+    for like in likes {
+        assert!(like.unwrap().is_some());
+    }
+    // The author liking their own post is not notified
+    service.like_post(author, post.id).await.unwrap();
+
+    let (groups, _) = service
+        .get_notifications(author, None, 20, true)
+        .await
+        .unwrap();
+    assert_eq!(groups.len(), 1);
+    let group = &groups[0];
+    assert_eq!(group.notification.kind, NotificationKind::Like);
+    assert_eq!(group.notification.post_id, Some(post.id));
+    assert_eq!(group.notification.actor_count, 5);
+    assert_eq!(
+        service.get_unread_notification_count(author).await.unwrap(),
+        1
+    );
+
+    // Once read, the next like opens a new group
+    service
+        .mark_notification_read(author, group.notification.id)
+        .await
+        .unwrap();
+    service.unlike_post(users[1], post.id).await.unwrap();
+    service.like_post(users[1], post.id).await.unwrap();
+
+    let (groups, _) = service
+        .get_notifications(author, None, 20, false)
+        .await
+        .unwrap();
+    assert_eq!(groups.len(), 2);
+    assert!(!groups[0].notification.is_read);
+    assert_eq!(groups[0].notification.actor_count, 1);
+    assert!(groups[1].notification.is_read);
+
+    cleanup(&service.pool, &users).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_fan_out_workers_share_the_queue_and_large_accounts_are_fanned_in() {
+    let Some(service) = setup().await else {
+        return;
+    };
+    let users = create_users(&service.pool, 4).await;
+    let (author, large, reader) = (users[0], users[1], users[2]);
+    // The large account has two followers, more than the limit used below
+    for (follower, following) in [(reader, author), (reader, large), (users[3], large)] {
+        service.follow_user(follower, following).await.unwrap();
+    }
+    let max_followers = 1;
+
+    let mut posts = Vec::new();
+    for i in 0..5 {
+        posts.push(
+            service
+                .create_post(author, new_post(&format!("post {}", i)))
+                .await
+                .unwrap()
+                .id,
+        );
+    }
+    let large_post = service
+        .create_post(large, new_post("from a large account"))
+        .await
+        .unwrap()
+        .id;
+
+    // Workers claim events with SKIP LOCKED, so two of them running side by
+    // side drain the queue without applying an event twice
+    loop {
+        let handled = join_all((0..2).map(|_| service.process_timeline_events(2, max_followers)))
+            .await
+            .into_iter()
+            .map(|handled| handled.unwrap())
+            .sum::<usize>();
+        if handled == 0 {
+            break;
+        }
+    }
+
+    let materialized: Vec<i32> =
+        sqlx::query_scalar("SELECT post_id FROM home_timeline WHERE user_id = $1 ORDER BY post_id")
+            .bind(reader)
+            .fetch_all(&service.pool)
+            .await
+            .unwrap();
+    assert_eq!(materialized, posts);
+
+    // The large account's post is read at query time instead
+    let (entries, next) = service
+        .get_home_timeline(reader, None, 4, false, max_followers)
+        .await
+        .unwrap();
+    let ids: Vec<i32> = entries.iter().map(|entry| entry.post.id).collect();
+    assert_eq!(ids, vec![large_post, posts[4], posts[3], posts[2]]);
+
+    let (entries, next) = service
+        .get_home_timeline(reader, next, 4, false, max_followers)
+        .await
+        .unwrap();
+    let ids: Vec<i32> = entries.iter().map(|entry| entry.post.id).collect();
+    assert_eq!(ids, vec![posts[1], posts[0]]);
+    assert!(next.is_none());
+
+    cleanup(&service.pool, &users).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_due_posts_are_published_once_and_removed_with_the_same_commit() {
+    let Some(service) = setup().await else {
+        return;
+    };
+    let users = create_users(&service.pool, 1).await;
+    let author = users[0];
+    let deleted = service.create_post(author, new_post("gone")).await.unwrap();
+    service.delete_post(deleted.id, author).await.unwrap();
+
+    for i in 0..5 {
+        sqlx::query(
+            "INSERT INTO scheduled_posts (user_id, content, status, publish_at) \
+             VALUES ($1, $2, 'scheduled', NOW() - INTERVAL '1 minute')",
+        )
+        .bind(author)
+        .bind(format!("scheduled {}", i))
+        .execute(&service.pool)
+        .await
+        .unwrap();
+    }
+    // Replying to a deleted post can never succeed
+    let failing: i32 = sqlx::query_scalar(
+        "INSERT INTO scheduled_posts (user_id, content, reply_to_post_id, status, publish_at) \
+         VALUES ($1, 'reply', $2, 'scheduled', NOW() - INTERVAL '1 minute') RETURNING id",
+    )
+    .bind(author)
+    .bind(deleted.id)
+    .fetch_one(&service.pool)
+    .await
+    .unwrap();
+
+    for handled in join_all((0..2).map(|_| service.publish_due_posts(10))).await {
+        handled.unwrap();
+    }
+
+    let published: Vec<String> = sqlx::query_scalar(
+        "SELECT content FROM posts WHERE user_id = $1 AND id <> $2 ORDER BY content",
+    )
+    .bind(author)
+    .bind(deleted.id)
+    .fetch_all(&service.pool)
+    .await
+    .unwrap();
+    let expected: Vec<String> = (0..5).map(|i| format!("scheduled {}", i)).collect();
+    assert_eq!(published, expected);
+
+    let remaining: Vec<(i32, String)> =
+        sqlx::query_as("SELECT id, status FROM scheduled_posts WHERE user_id = $1")
+            .bind(author)
+            .fetch_all(&service.pool)
+            .await
+            .unwrap();
+    assert_eq!(remaining, vec![(failing, "failed".to_string())]);
+
+    let post_count: i32 = sqlx::query_scalar("SELECT post_count FROM users WHERE id = $1")
+        .bind(author)
+        .fetch_one(&service.pool)
+        .await
+        .unwrap();
+    assert_eq!(post_count, 5);
+
+    cleanup(&service.pool, &users).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_bookmarked_posts_turn_into_tombstones_once_deleted() {
+    let Some(service) = setup().await else {
+        return;
+    };
+    let users = create_users(&service.pool, 2).await;
+    let (author, reader) = (users[0], users[1]);
+    let kept = service.create_post(author, new_post("kept")).await.unwrap();
+    let removed = service
+        .create_post(author, new_post("removed"))
+        .await
+        .unwrap();
+
+    for post_id in [kept.id, removed.id] {
+        let (entry, created) = service
+            .bookmark_post(reader, post_id, None)
+            .await
+            .unwrap()
+            .expect("The post is visible");
+        assert!(created);
+        assert!(!entry.tombstone);
+    }
+    assert!(service.delete_post(removed.id, author).await.unwrap());
+
+    let (entries, _) = service
+        .get_bookmarks(reader, None, None, 10)
+        .await
+        .unwrap()
+        .unwrap();
+    let listed: Vec<(i32, bool, bool)> = entries
+        .iter()
+        .map(|entry| (entry.post_id, entry.tombstone, entry.post.is_some()))
+        .collect();
+    assert_eq!(
+        listed,
+        vec![(removed.id, true, false), (kept.id, false, true)]
+    );
+
+    // Saving the deleted post again keeps the bookmark, still as a tombstone
+    let (entry, created) = service
+        .bookmark_post(reader, removed.id, None)
+        .await
+        .unwrap()
+        .expect("Bookmarked posts stay reachable as tombstones");
+    assert!(!created);
+    assert!(entry.tombstone);
+    assert!(entry.post.is_none());
+
+    cleanup(&service.pool, &users).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_bookmark_pages_are_stable_across_ties_and_new_bookmarks() {
+    let Some(service) = setup().await else {
+        return;
+    };
+    let users = create_users(&service.pool, 2).await;
+    let (author, reader) = (users[0], users[1]);
+    let mut post_ids = Vec::new();
+    for i in 0..8 {
+        let post = service
+            .create_post(author, new_post(&format!("post {}", i)))
+            .await
+            .unwrap();
+        service.bookmark_post(reader, post.id, None).await.unwrap();
+        post_ids.push(post.id);
+    }
+    // Bookmarks saved in the same instant are ordered by id
+    sqlx::query(
+        "UPDATE bookmarks SET created_at = NOW() - INTERVAL '1 hour' \
+         WHERE user_id = $1 AND post_id = ANY($2)",
+    )
+    .bind(reader)
+    .bind(&post_ids[2..6])
+    .execute(&service.pool)
+    .await
+    .unwrap();
+
+    let (all, next) = service
+        .get_bookmarks(reader, None, None, 20)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(next.is_none());
+    let expected: Vec<i32> = all.iter().map(|entry| entry.id).collect();
+    assert_eq!(expected.len(), 8);
+
+    let mut paged = Vec::new();
+    let mut cursor = None;
+    loop {
+        let (page, next) = service
+            .get_bookmarks(reader, None, cursor, 3)
+            .await
+            .unwrap()
+            .unwrap();
+        paged.extend(page.iter().map(|entry| entry.id));
+        // A bookmark saved while paging lands before the cursor and does
+        // not shift the pages that follow
+        if cursor.is_none() {
+            let late = service.create_post(author, new_post("late")).await.unwrap();
+            service.bookmark_post(reader, late.id, None).await.unwrap();
+        }
+        match next {
+            Some(next) => cursor = Some(next.to_string().parse().unwrap()),
+            None => break,
+        }
+    }
+    assert_eq!(paged, expected);
+
+    cleanup(&service.pool, &users).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_bookmark_collections_belong_to_their_owner() {
+    let Some(service) = setup().await else {
+        return;
+    };
+    let users = create_users(&service.pool, 2).await;
+    let (owner, other) = (users[0], users[1]);
+    let post = service.create_post(owner, new_post("saved")).await.unwrap();
+    let collection = service
+        .create_bookmark_collection(owner, "Recipes")
+        .await
+        .unwrap();
+    service
+        .bookmark_post(owner, post.id, Some(collection.id))
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Someone else can neither file into, list, rename nor delete it
+    let error = service
+        .bookmark_post(other, post.id, Some(collection.id))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SocialError>(),
+        Some(SocialError::CollectionNotFound)
+    ));
+    assert!(service
+        .get_bookmarks(other, Some(collection.id), None, 10)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(service
+        .rename_bookmark_collection(other, collection.id, "Mine now")
+        .await
+        .unwrap()
+        .is_none());
+    assert!(!service
+        .delete_bookmark_collection(other, collection.id)
+        .await
+        .unwrap());
+
+    let collections = service.get_bookmark_collections(owner).await.unwrap();
+    assert_eq!(collections.len(), 1);
+    assert_eq!(collections[0].name, "Recipes");
+    assert_eq!(collections[0].bookmark_count, 1);
+    assert!(service
+        .get_bookmark_collections(other)
+        .await
+        .unwrap()
+        .is_empty());
+
+    cleanup(&service.pool, &users).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_blocking_drops_follows_and_follow_requests_both_ways() {
+    let Some(service) = setup().await else {
+        return;
+    };
+    let users = create_users(&service.pool, 4).await;
+    let (blocker, blocked) = (users[0], users[1]);
+    let (private_blocker, private_blocked) = (users[2], users[3]);
+    sqlx::query("UPDATE users SET is_private = TRUE WHERE id = ANY($1)")
+        .bind(&users[2..])
+        .execute(&service.pool)
+        .await
+        .unwrap();
+
+    for (follower, following) in [
+        (blocker, blocked),
+        (blocked, blocker),
+        (private_blocker, private_blocked),
+        (private_blocked, private_blocker),
+    ] {
+        assert!(service
+            .follow_user(follower, following)
+            .await
+            .unwrap()
+            .is_some());
+    }
+    assert!(service.is_following(blocked, blocker).await.unwrap());
+
+    service.block_user(blocker, blocked).await.unwrap().unwrap();
+    service
+        .block_user(private_blocker, private_blocked)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert!(!service.is_following(blocker, blocked).await.unwrap());
+    assert!(!service.is_following(blocked, blocker).await.unwrap());
+    let requests: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM follow_requests WHERE requester_id = ANY($1) OR target_id = ANY($1)",
+    )
+    .bind(&users[2..])
+    .fetch_one(&service.pool)
+    .await
+    .unwrap();
+    assert_eq!(requests, 0);
+    let counts: Vec<(Option<i32>, Option<i32>)> = sqlx::query_as(
+        "SELECT follower_count, following_count FROM users WHERE id = ANY($1) ORDER BY id",
+    )
+    .bind(&users[..2])
+    .fetch_all(&service.pool)
+    .await
+    .unwrap();
+    assert_eq!(counts, vec![(Some(0), Some(0)), (Some(0), Some(0))]);
+
+    cleanup(&service.pool, &users).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_blocking_rejects_follows_likes_and_comments() {
+    let Some(service) = setup().await else {
+        return;
+    };
+    let users = create_users(&service.pool, 3).await;
+    let (blocker, blocked, bystander) = (users[0], users[1], users[2]);
+    let blocker_post = service
+        .create_post(blocker, new_post("not for you"))
+        .await
+        .unwrap();
+    let bystander_post = service
+        .create_post(bystander, new_post("open to all"))
+        .await
+        .unwrap();
+    let reply_to = |user_id, post_id, reply_to_comment_id| {
+        service.create_comment(
+            user_id,
+            CreateComment {
+                post_id,
+                content: "hi".to_string(),
+                reply_to_comment_id,
+            },
+        )
+    };
+    let blocker_comment = reply_to(blocker, bystander_post.id, None)
+        .await
+        .unwrap()
+        .unwrap();
+    let blocked_comment = reply_to(blocked, bystander_post.id, None)
+        .await
+        .unwrap()
+        .unwrap();
+
+    service.block_user(blocker, blocked).await.unwrap().unwrap();
+
+    let is_blocked = |error: anyhow::Error| {
+        matches!(
+            error.downcast_ref::<SocialError>(),
+            Some(SocialError::Blocked)
+        )
+    };
+
+    // Neither side can follow the other
+    assert!(is_blocked(
+        service.follow_user(blocked, blocker).await.unwrap_err()
+    ));
+    assert!(is_blocked(
+        service.follow_user(blocker, blocked).await.unwrap_err()
+    ));
+
+    // The blocker's posts are hidden from the blocked user, so they cannot
+    // be liked or commented on
+    assert!(service
+        .like_post(blocked, blocker_post.id)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(reply_to(blocked, blocker_post.id, None)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Where the post is someone else's, replies to and likes of the other
+    // side's comments are refused in both directions
+    assert!(is_blocked(
+        reply_to(blocked, bystander_post.id, Some(blocker_comment.id))
+            .await
+            .unwrap_err()
+    ));
+    assert!(is_blocked(
+        reply_to(blocker, bystander_post.id, Some(blocked_comment.id))
+            .await
+            .unwrap_err()
+    ));
+    assert!(is_blocked(
+        service
+            .like_comment(blocked, blocker_comment.id)
+            .await
+            .unwrap_err()
+    ));
+    assert!(is_blocked(
+        service
+            .like_comment(blocker, blocked_comment.id)
+            .await
+            .unwrap_err()
+    ));
+
+    // Replying to the blocked user's post is refused too
+    let blocked_post = service
+        .create_post(blocked, new_post("mine"))
+        .await
+        .unwrap();
+    let error = service
+        .create_post(
+            blocker,
+            CreatePost {
+                reply_to_post_id: Some(blocked_post.id),
+                ..new_post("reply")
+            },
+        )
+        .await
+        .unwrap_err();
+    assert!(is_blocked(error));
+
+    cleanup(&service.pool, &users).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_muted_posts_and_reposts_stay_out_of_every_feed() {
+    let Some(service) = setup().await else {
+        return;
+    };
+    let users = create_users(&service.pool, 4).await;
+    let (reader, muted, kept, stranger) = (users[0], users[1], users[2], users[3]);
+    for following in [muted, kept] {
+        service.follow_user(reader, following).await.unwrap();
+    }
+
+    let muted_post = service
+        .create_post(muted, new_post("muted post"))
+        .await
+        .unwrap();
+    let kept_post = service
+        .create_post(kept, new_post("kept post"))
+        .await
+        .unwrap();
+    let stranger_post = service
+        .create_post(stranger, new_post("stranger post"))
+        .await
+        .unwrap();
+    // A kept account reposting a muted one, and a muted account reposting
+    // the others
+    service
+        .repost_post(kept, muted_post.id)
+        .await
+        .unwrap()
+        .unwrap();
+    for post_id in [kept_post.id, stranger_post.id] {
+        service.repost_post(muted, post_id).await.unwrap().unwrap();
+    }
+    while service.process_timeline_events(100, 10_000).await.unwrap() > 0 {}
+
+    service.mute_user(reader, muted).await.unwrap().unwrap();
+
+    let unattributed = |entries: Vec<FeedEntry>| {
+        entries
+            .into_iter()
+            .map(|entry| (entry.post.id, entry.reposted_by.map(|r| r.user_id)))
+            .collect::<Vec<_>>()
+    };
+    let expected = vec![(kept_post.id, None)];
+
+    let feed = service
+        .get_feed_posts(reader, 20, 0, ReplyMode::Include)
+        .await
+        .unwrap();
+    assert_eq!(unattributed(feed), expected);
+
+    // Materialized, and merged in at read time for large accounts
+    for max_followers in [10_000, 0] {
+        let (timeline, _) = service
+            .get_home_timeline(reader, None, 20, false, max_followers)
+            .await
+            .unwrap();
+        assert_eq!(unattributed(timeline), expected);
+    }
+
+    let ranked = service
+        .get_ranked_feed_posts(reader, &RankingWeights::default(), 100, 0, false)
+        .await
+        .unwrap();
+    let ranked: Vec<i32> = ranked.iter().map(|entry| entry.post.id).collect();
+    assert!(ranked.contains(&kept_post.id));
+    assert!(!ranked.contains(&muted_post.id));
+
+    // Unmuting brings the posts back
+    assert!(service.unmute_user(reader, muted).await.unwrap());
+    let feed = service
+        .get_feed_posts(reader, 20, 0, ReplyMode::Include)
+        .await
+        .unwrap();
+    let ids: Vec<i32> = feed.iter().map(|entry| entry.post.id).collect();
+    assert!(ids.contains(&muted_post.id));
+    assert!(ids.contains(&stranger_post.id));
+
+    cleanup(&service.pool, &users).await;
+}
@@ -0,0 +1,115 @@
+//! Checks the sync tokens handed out by pushes against a real database. Each
+//! test is skipped when `DATABASE_URL` is unset.
+
+use serial_test::serial;
+use sqlx::PgPool;
+use todo_api::db::schema;
+use todo_api::handlers::todo::models::{CreateTodoRequest, SyncChange};
+use todo_api::service::todo::Service;
+use todo_api::service::todo_sync::SyncOutcome;
+
+/// Connects to `DATABASE_URL` and brings the schema up to date, or returns
+/// `None` when no database is configured
+async fn setup() -> Option<Service> {
+    let Ok(database_url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set, skipping");
+        return None;
+    };
+
+    let pool = PgPool::connect(&database_url)
+        .await
+        .expect("Failed to connect to DATABASE_URL");
+    schema::initialize_schema(&pool)
+        .await
+        .expect("Failed to initialize the schema");
+
+    Some(Service::new(pool).expect("Failed to create the todo service"))
+}
+
+async fn create_user(pool: &PgPool) -> i32 {
+    sqlx::query_scalar(
+        "INSERT INTO users (username, password) VALUES ($1, 'hashed_password') RETURNING id",
+    )
+    .bind(format!("test_{}", uuid::Uuid::new_v4().simple()))
+    .fetch_one(pool)
+    .await
+    .expect("Failed to create a user")
+}
+
+#[tokio::test]
+#[serial]
+async fn test_push_token_keeps_changes_another_client_is_committing() {
+    let Some(service) = setup().await else {
+        return;
+    };
+    let pool = service.get_pool().clone();
+    let user_id = create_user(&pool).await;
+
+    let shared = service
+        .create(
+            user_id,
+            CreateTodoRequest {
+                title: "Shared".to_string(),
+                description: "Edited on two devices".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    let since = service
+        .sync_changes(user_id, None)
+        .await
+        .unwrap()
+        .sync_token;
+
+    // Another device's push is still in flight while this one is handled
+    let mut other_device = pool.begin().await.unwrap();
+    sqlx::query("UPDATE todos SET title = 'Edited elsewhere', version = version + 1 WHERE id = $1")
+        .bind(shared.id)
+        .execute(&mut *other_device)
+        .await
+        .unwrap();
+
+    // The push handler applies the changes, then reads what changed since
+    // the client's token
+    let outcome = service
+        .apply_sync_change(
+            user_id,
+            SyncChange::Create {
+                client_id: Some("local-1".to_string()),
+                title: "Pushed".to_string(),
+                description: "Created offline".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    let Some(pushed) = (match outcome {
+        SyncOutcome::Applied(todo) => todo,
+        other => panic!("Unexpected outcome {:?}", other),
+    }) else {
+        panic!("A created todo is returned");
+    };
+    let after_push = service.sync_changes(user_id, Some(since)).await.unwrap();
+    let created: Vec<i32> = after_push.created.iter().map(|todo| todo.id).collect();
+    assert_eq!(created, vec![pushed.id]);
+    assert!(after_push.updated.is_empty());
+
+    other_device.commit().await.unwrap();
+
+    // The edit committed after the token was taken comes with the next sync
+    let next = service
+        .sync_changes(user_id, Some(after_push.sync_token))
+        .await
+        .unwrap();
+    let updated: Vec<(i32, String)> = next
+        .updated
+        .into_iter()
+        .map(|todo| (todo.id, todo.title))
+        .collect();
+    assert_eq!(updated, vec![(shared.id, "Edited elsewhere".to_string())]);
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,88 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+};
+use sqlx::postgres::PgPoolOptions;
+use std::path::PathBuf;
+use std::sync::Arc;
+use todo_api::config::Config;
+use todo_api::handlers::models::Claims;
+use todo_api::handlers::social::blocks;
+use todo_api::service::{
+    self, blob_store::BlobStoreConfig, moderation::RuleFilter, ranking::RankingWeights,
+};
+use todo_api::AppState;
+
+/// App state whose pool never connects, for handlers that must answer
+/// before reaching the database
+fn app_state() -> AppState {
+    let pool = PgPoolOptions::new()
+        .connect_lazy("postgres://postgres@localhost:1/unused")
+        .unwrap();
+    let config = Config {
+        database_url: String::new(),
+        hashing_secret_key: "secret".to_string(),
+        jwt_secret: "dGVzdC1zZWNyZXQ=".to_string(),
+        server_port: 8080,
+        server_host: "127.0.0.1".to_string(),
+        todo_trash_retention_days: 30,
+        require_if_match: false,
+        ranking: RankingWeights::default(),
+        timeline_fanout_max_followers: 10_000,
+        media_storage: BlobStoreConfig::Local {
+            root: PathBuf::from("./media"),
+        },
+        media_public_base_url: "http://localhost:8080/media/files".to_string(),
+        media_max_upload_bytes: 1024,
+        content_filter: RuleFilter::default(),
+        post_edit_window_minutes: 0,
+    };
+    let jwt_service = Arc::new(service::jwt::Service::new(&config.jwt_secret).unwrap());
+
+    AppState {
+        todo_service: Arc::new(service::todo::Service::new(pool.clone()).unwrap()),
+        auth_service: Arc::new(
+            service::auth::Service::new(
+                jwt_service.clone(),
+                pool.clone(),
+                config.hashing_secret_key.clone(),
+            )
+            .unwrap(),
+        ),
+        jwt_service,
+        social_service: Arc::new(service::social::SocialService::new(
+            pool.clone(),
+            config.media_public_base_url.clone(),
+            Arc::new(RuleFilter::default()),
+        )),
+        event_service: Arc::new(service::events::EventService::new(pool.clone())),
+        media_service: Arc::new(service::media::MediaService::new(
+            pool,
+            config.media_storage.build().unwrap(),
+            config.media_public_base_url.clone(),
+            config.media_max_upload_bytes,
+        )),
+        config: Arc::new(config),
+    }
+}
+
+fn claims(user_id: i32) -> Claims {
+    Claims {
+        sub: user_id,
+        username: "blocker".to_string(),
+        exp: usize::MAX,
+    }
+}
+
+#[tokio::test]
+async fn test_blocking_yourself_is_a_bad_request() {
+    let result = blocks::block_user(State(app_state()), Extension(claims(7)), Path(7)).await;
+    assert_eq!(result.err(), Some(StatusCode::BAD_REQUEST));
+}
+
+#[tokio::test]
+async fn test_muting_yourself_is_a_bad_request() {
+    let result = blocks::mute_user(State(app_state()), Extension(claims(7)), Path(7)).await;
+    assert_eq!(result.err(), Some(StatusCode::BAD_REQUEST));
+}
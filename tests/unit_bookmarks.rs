@@ -0,0 +1,95 @@
+#[path = "common/fixtures.rs"]
+mod fixtures;
+
+use chrono::{DateTime, Utc};
+use fixtures::SocialFixture;
+use todo_api::db::models::{Bookmark, FeedEntry};
+use todo_api::service::bookmarks::{
+    assemble, normalize_collection_name, BookmarkCursor, InvalidBookmarkCursor,
+    MAX_COLLECTION_NAME_LENGTH,
+};
+use todo_api::service::hydrate::{self, HydrationData};
+
+fn bookmark(id: i32, post_id: i32, collection_id: Option<i32>) -> Bookmark {
+    Bookmark {
+        id,
+        user_id: 1,
+        post_id,
+        collection_id,
+        created_at: Utc::now().naive_utc(),
+    }
+}
+
+#[test]
+fn test_assemble_keeps_order_and_tombstones_missing_posts() {
+    let mut data = HydrationData::default();
+    data.users.insert(2, SocialFixture::user(2));
+    let posts = hydrate::assemble(
+        vec![
+            FeedEntry {
+                post: SocialFixture::post(10, 2),
+                reposted_by: None,
+            },
+            FeedEntry {
+                post: SocialFixture::post(30, 2),
+                reposted_by: None,
+            },
+        ],
+        data,
+    );
+
+    let entries = assemble(
+        vec![
+            bookmark(3, 30, Some(7)),
+            bookmark(2, 20, None),
+            bookmark(1, 10, None),
+        ],
+        posts,
+    );
+
+    let ids: Vec<i32> = entries.iter().map(|entry| entry.post_id).collect();
+    assert_eq!(ids, vec![30, 20, 10]);
+    assert!(!entries[0].tombstone);
+    assert_eq!(entries[0].collection_id, Some(7));
+    assert_eq!(entries[0].post.as_ref().unwrap().post.post.id, 30);
+
+    assert!(entries[1].tombstone);
+    assert!(entries[1].post.is_none());
+    let json = serde_json::to_value(&entries[1]).unwrap();
+    assert_eq!(json["tombstone"], true);
+    assert!(json["post"].is_null());
+}
+
+#[test]
+fn test_collection_names_are_trimmed_and_limited() {
+    assert_eq!(
+        normalize_collection_name("  Recipes "),
+        Some("Recipes".to_string())
+    );
+    assert_eq!(normalize_collection_name("   "), None);
+
+    let longest = "é".repeat(MAX_COLLECTION_NAME_LENGTH);
+    assert_eq!(normalize_collection_name(&longest), Some(longest.clone()));
+    assert_eq!(
+        normalize_collection_name(&"a".repeat(MAX_COLLECTION_NAME_LENGTH + 1)),
+        None
+    );
+}
+
+#[test]
+fn test_bookmark_cursor_round_trips() {
+    let cursor = BookmarkCursor {
+        created_at: DateTime::from_timestamp_micros(1_700_000_000_123_456)
+            .unwrap()
+            .naive_utc(),
+        id: 42,
+    };
+
+    assert_eq!(cursor.to_string(), "1700000000123456_42");
+    assert_eq!(cursor.to_string().parse::<BookmarkCursor>(), Ok(cursor));
+    assert_eq!("42".parse::<BookmarkCursor>(), Err(InvalidBookmarkCursor));
+    assert_eq!(
+        "abc_42".parse::<BookmarkCursor>(),
+        Err(InvalidBookmarkCursor)
+    );
+}
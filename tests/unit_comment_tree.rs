@@ -0,0 +1,98 @@
+#[path = "common/fixtures.rs"]
+mod fixtures;
+
+use std::collections::HashSet;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use fixtures::SocialFixture;
+use todo_api::db::models::{Comment, CommentNode};
+use todo_api::service::comment_tree::build_comment_tree;
+
+fn base_time() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 9, 1)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap()
+}
+
+fn comment(id: i32, reply_to_comment_id: Option<i32>, depth: i32) -> (Comment, i32) {
+    let created_at = base_time() + Duration::minutes(id as i64);
+    let comment = Comment {
+        created_at,
+        updated_at: created_at,
+        reply_to_comment_id,
+        ..SocialFixture::comment(id, 1, 1)
+    };
+    (comment, depth)
+}
+
+fn ids(nodes: &[CommentNode]) -> Vec<i32> {
+    nodes.iter().map(|node| node.comment.id).collect()
+}
+
+#[test]
+fn test_nests_replies_under_parents() {
+    let rows = vec![
+        comment(1, None, 1),
+        comment(2, None, 1),
+        comment(3, Some(1), 2),
+        comment(4, Some(1), 2),
+        comment(5, Some(3), 3),
+    ];
+
+    let (tree, has_more) = build_comment_tree(rows, &HashSet::new(), 10);
+
+    assert!(!has_more);
+    assert_eq!(ids(&tree), vec![1, 2]);
+    assert_eq!(ids(&tree[0].replies), vec![3, 4]);
+    assert_eq!(ids(&tree[0].replies[0].replies), vec![5]);
+    assert_eq!(tree[0].replies[0].replies[0].depth, 3);
+    assert!(tree[1].replies.is_empty());
+}
+
+#[test]
+fn test_truncates_top_level_and_reports_more() {
+    let rows = vec![
+        comment(1, None, 1),
+        comment(2, None, 1),
+        comment(3, None, 1),
+        comment(4, Some(3), 2),
+    ];
+
+    let (tree, has_more) = build_comment_tree(rows, &HashSet::new(), 2);
+
+    assert!(has_more);
+    assert_eq!(ids(&tree), vec![1, 2]);
+}
+
+#[test]
+fn test_marks_liked_comments() {
+    let rows = vec![comment(1, None, 1), comment(2, Some(1), 2)];
+    let liked = HashSet::from([2]);
+
+    let (tree, _) = build_comment_tree(rows, &liked, 10);
+
+    assert!(!tree[0].is_liked);
+    assert!(tree[0].replies[0].is_liked);
+}
+
+#[test]
+fn test_drops_orphaned_replies() {
+    let rows = vec![comment(1, None, 1), comment(2, Some(99), 2)];
+
+    let (tree, _) = build_comment_tree(rows, &HashSet::new(), 10);
+
+    assert_eq!(ids(&tree), vec![1]);
+    assert!(tree[0].replies.is_empty());
+}
+
+#[test]
+fn test_node_serializes_comment_fields_flat() {
+    let (tree, _) = build_comment_tree(vec![comment(1, None, 1)], &HashSet::new(), 10);
+    let json = serde_json::to_value(&tree[0]).unwrap();
+
+    assert_eq!(json["id"], 1);
+    assert_eq!(json["depth"], 1);
+    assert_eq!(json["is_liked"], false);
+    assert!(json["replies"].as_array().unwrap().is_empty());
+}
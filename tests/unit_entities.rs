@@ -0,0 +1,76 @@
+use todo_api::db::models::{EntityKind, PostEntity};
+use todo_api::service::entities::{distinct, normalize_tag, parse, MAX_HASHTAG_LENGTH};
+
+fn spans(entities: &[PostEntity]) -> Vec<(EntityKind, usize, usize, &str)> {
+    entities
+        .iter()
+        .map(|entity| (entity.kind, entity.start, entity.end, entity.text.as_str()))
+        .collect()
+}
+
+#[test]
+fn test_parses_mentions_and_hashtags() {
+    let entities = parse("@alice meet @bob_2 at #RustConf!");
+
+    assert_eq!(
+        spans(&entities),
+        vec![
+            (EntityKind::Mention, 0, 6, "alice"),
+            (EntityKind::Mention, 12, 18, "bob_2"),
+            (EntityKind::Hashtag, 22, 31, "rustconf"),
+        ]
+    );
+}
+
+#[test]
+fn test_ignores_sigils_inside_words() {
+    assert!(parse("mail me@example.com about C# or a#b").is_empty());
+}
+
+#[test]
+fn test_ignores_empty_and_numeric_tags() {
+    assert!(parse("# @ #123 @!").is_empty());
+    assert_eq!(
+        spans(&parse("#2024goals")),
+        vec![(EntityKind::Hashtag, 0, 10, "2024goals")]
+    );
+}
+
+#[test]
+fn test_offsets_count_characters() {
+    let entities = parse("héllo ✨ #café @zoe");
+
+    assert_eq!(
+        spans(&entities),
+        vec![
+            (EntityKind::Hashtag, 8, 13, "café"),
+            (EntityKind::Mention, 14, 18, "zoe"),
+        ]
+    );
+}
+
+#[test]
+fn test_mentions_stop_at_non_ascii_letters() {
+    // Usernames are ASCII, so this cannot be a mention of @zo
+    assert!(parse("@zoë").is_empty());
+}
+
+#[test]
+fn test_skips_overlong_tags() {
+    let content = format!("#{}", "a".repeat(MAX_HASHTAG_LENGTH + 1));
+    assert!(parse(&content).is_empty());
+}
+
+#[test]
+fn test_distinct_keeps_first_appearance() {
+    let entities = parse("#b #A @x #a @x #b");
+
+    assert_eq!(distinct(&entities, EntityKind::Hashtag), vec!["b", "a"]);
+    assert_eq!(distinct(&entities, EntityKind::Mention), vec!["x"]);
+}
+
+#[test]
+fn test_normalize_tag() {
+    assert_eq!(normalize_tag("#Rust"), "rust");
+    assert_eq!(normalize_tag("rust"), "rust");
+}
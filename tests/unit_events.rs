@@ -0,0 +1,51 @@
+use chrono::NaiveDate;
+use serde_json::json;
+use todo_api::db::models::StreamEvent;
+use todo_api::service::events::{is_deliverable, resume_after, InvalidLastEventId};
+
+fn event(id: i64, user_id: i32) -> StreamEvent {
+    StreamEvent {
+        id,
+        user_id,
+        kind: "todo".to_string(),
+        payload: json!({ "todo_id": 1, "op": "created" }),
+        created_at: NaiveDate::from_ymd_opt(2025, 9, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap(),
+    }
+}
+
+#[test]
+fn test_decodes_notify_payload() {
+    // Shape produced by notify_stream_event()
+    let payload = r#"{"id" : 7, "user_id" : 3, "kind" : "feed_item", "payload" : {"post_id" : 12, "reposter_id" : null}, "created_at" : "2025-09-01T12:00:00.123456"}"#;
+
+    let event: StreamEvent = serde_json::from_str(payload).unwrap();
+
+    assert_eq!(event.id, 7);
+    assert_eq!(event.user_id, 3);
+    assert_eq!(event.kind, "feed_item");
+    assert_eq!(event.payload["post_id"], 12);
+}
+
+#[test]
+fn test_resume_after_prefers_header() {
+    assert_eq!(resume_after(Some("42"), Some(7)), Ok(Some(42)));
+    assert_eq!(resume_after(None, Some(7)), Ok(Some(7)));
+    assert_eq!(resume_after(None, None), Ok(None));
+}
+
+#[test]
+fn test_resume_after_rejects_malformed_header() {
+    assert_eq!(resume_after(Some("abc"), None), Err(InvalidLastEventId));
+    assert_eq!(resume_after(Some(""), Some(7)), Err(InvalidLastEventId));
+}
+
+#[test]
+fn test_only_delivers_own_unseen_events() {
+    assert!(is_deliverable(&event(5, 1), 1, None));
+    assert!(is_deliverable(&event(5, 1), 1, Some(4)));
+    assert!(!is_deliverable(&event(5, 1), 1, Some(5)));
+    assert!(!is_deliverable(&event(5, 2), 1, None));
+}
@@ -0,0 +1,169 @@
+#[path = "common/fixtures.rs"]
+mod fixtures;
+
+use chrono::Utc;
+use fixtures::SocialFixture;
+use todo_api::db::models::{EntityKind, FeedEntry, Post, PostMedia, RepostAttribution};
+use todo_api::service::hydrate::{assemble, HydrationData};
+
+fn entry(post: Post) -> FeedEntry {
+    FeedEntry {
+        post,
+        reposted_by: None,
+    }
+}
+
+#[test]
+fn test_assemble_keeps_page_order_and_viewer_state() {
+    let mut data = HydrationData::default();
+    data.users.insert(1, SocialFixture::user(1));
+    data.users.insert(2, SocialFixture::user(2));
+    data.liked.insert(20);
+    data.following.insert(2);
+
+    let posts = assemble(
+        vec![
+            entry(SocialFixture::post(20, 2)),
+            entry(SocialFixture::post(10, 1)),
+        ],
+        data,
+    );
+
+    assert_eq!(posts.len(), 2);
+    assert_eq!(posts[0].post.post.id, 20);
+    assert_eq!(posts[0].post.user.username, "user2");
+    assert!(posts[0].post.is_liked);
+    assert!(posts[0].post.is_following_author);
+    assert_eq!(posts[1].post.post.id, 10);
+    assert!(!posts[1].post.is_liked);
+    assert!(!posts[1].post.is_following_author);
+}
+
+#[test]
+fn test_assemble_attaches_comments_to_their_posts() {
+    let mut data = HydrationData::default();
+    data.users.insert(1, SocialFixture::user(1));
+    data.users.insert(3, SocialFixture::user(3));
+    data.comments.insert(
+        10,
+        vec![
+            SocialFixture::comment(100, 10, 3),
+            SocialFixture::comment(101, 10, 1),
+        ],
+    );
+
+    let posts = assemble(
+        vec![
+            entry(SocialFixture::post(10, 1)),
+            entry(SocialFixture::post(11, 1)),
+        ],
+        data,
+    );
+
+    let comment_ids: Vec<i32> = posts[0]
+        .comments
+        .iter()
+        .map(|comment| comment.comment.id)
+        .collect();
+    assert_eq!(comment_ids, vec![100, 101]);
+    assert_eq!(posts[0].comments[0].user.username, "user3");
+    assert!(posts[1].comments.is_empty());
+}
+
+#[test]
+fn test_assemble_drops_rows_without_author() {
+    let mut data = HydrationData::default();
+    data.users.insert(1, SocialFixture::user(1));
+    data.comments
+        .insert(10, vec![SocialFixture::comment(100, 10, 9)]);
+
+    let posts = assemble(
+        vec![
+            entry(SocialFixture::post(10, 1)),
+            entry(SocialFixture::post(11, 9)),
+        ],
+        data,
+    );
+
+    assert_eq!(posts.len(), 1);
+    assert!(posts[0].comments.is_empty());
+}
+
+#[test]
+fn test_assemble_keeps_repost_attribution() {
+    let mut data = HydrationData::default();
+    data.users.insert(1, SocialFixture::user(1));
+
+    let reposted = FeedEntry {
+        post: SocialFixture::post(10, 1),
+        reposted_by: Some(RepostAttribution {
+            user_id: 2,
+            username: "user2".to_string(),
+            reposted_at: Utc::now().naive_utc(),
+        }),
+    };
+
+    let posts = assemble(vec![reposted], data);
+    let body = serde_json::to_value(&posts[0]).unwrap();
+
+    assert_eq!(body["id"], 10);
+    assert_eq!(body["user"]["id"], 1);
+    assert_eq!(body["reposted_by"]["user_id"], 2);
+    assert_eq!(body["comments"], serde_json::json!([]));
+}
+
+#[test]
+fn test_assemble_resolves_mentions_and_keeps_hashtags() {
+    let mut data = HydrationData::default();
+    data.users.insert(1, SocialFixture::user(1));
+    data.mentions
+        .entry(10)
+        .or_default()
+        .insert("user2".to_string(), 2);
+
+    let mut mentioning = SocialFixture::post(10, 1);
+    mentioning.content = "hi @user2 and @nobody #Rust".to_string();
+
+    let posts = assemble(vec![entry(mentioning)], data);
+    let entities = &posts[0].entities;
+
+    assert_eq!(entities.len(), 2);
+    assert_eq!(entities[0].kind, EntityKind::Mention);
+    assert_eq!(entities[0].user_id, Some(2));
+    assert_eq!((entities[0].start, entities[0].end), (3, 9));
+    assert_eq!(entities[1].kind, EntityKind::Hashtag);
+    assert_eq!(entities[1].text, "rust");
+}
+
+fn attachment(media_id: i32) -> PostMedia {
+    PostMedia {
+        media_id,
+        url: format!("/media/files/1/{}.png", media_id),
+        thumbnail_url: format!("/media/files/1/{}_thumb.png", media_id),
+        content_type: "image/png".to_string(),
+        width: 800,
+        height: 600,
+        blurhash: Some("LEHV6nWB2yk8pyo0adR*.7kCMdnj".to_string()),
+        alt_text: None,
+    }
+}
+
+#[test]
+fn test_assemble_attaches_media_in_order() {
+    let mut data = HydrationData::default();
+    data.users.insert(1, SocialFixture::user(1));
+    data.media
+        .insert(10, vec![attachment(7), attachment(5), attachment(6)]);
+
+    let posts = assemble(
+        vec![
+            entry(SocialFixture::post(10, 1)),
+            entry(SocialFixture::post(11, 1)),
+        ],
+        data,
+    );
+
+    let media_ids: Vec<i32> = posts[0].media.iter().map(|m| m.media_id).collect();
+    assert_eq!(media_ids, vec![7, 5, 6]);
+    assert!(posts[1].media.is_empty());
+}
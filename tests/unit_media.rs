@@ -0,0 +1,201 @@
+use std::io::Cursor;
+
+use image::{DynamicImage, GenericImageView, ImageFormat, RgbImage};
+use todo_api::db::models::NewPostMedia;
+use todo_api::service::blob_store::{signing_key, validate_key, InvalidBlobKey};
+use todo_api::service::media::{
+    content_type_for_key, process_image, sniff_image_type, validate_attachments,
+    validate_image_url, ImageType, MediaError, MAX_ALT_TEXT_LENGTH, THUMBNAIL_SIZE,
+};
+
+fn encoded(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+    let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(width, height, [200, 30, 30].into()));
+    let mut buffer = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut buffer), format)
+        .unwrap();
+    buffer
+}
+
+/// JPEG with an APP1 segment saying the camera was rotated 90° clockwise
+fn jpeg_with_exif_orientation(width: u32, height: u32) -> Vec<u8> {
+    let jpeg = encoded(width, height, ImageFormat::Jpeg);
+
+    let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08".to_vec();
+    exif.extend_from_slice(&[0x00, 0x01]); // one IFD entry
+    exif.extend_from_slice(&[0x01, 0x12, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01]); // Orientation, SHORT
+    exif.extend_from_slice(&[0x00, 0x06, 0x00, 0x00]); // value 6
+    exif.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]); // no next IFD
+
+    let mut data = jpeg[..2].to_vec();
+    data.extend_from_slice(&[0xFF, 0xE1]);
+    data.extend_from_slice(&((exif.len() + 2) as u16).to_be_bytes());
+    data.extend_from_slice(&exif);
+    data.extend_from_slice(&jpeg[2..]);
+    data
+}
+
+#[test]
+fn test_sniffs_type_from_magic_bytes() {
+    assert_eq!(
+        sniff_image_type(&encoded(4, 4, ImageFormat::Jpeg)),
+        Some(ImageType::Jpeg)
+    );
+    assert_eq!(
+        sniff_image_type(&encoded(4, 4, ImageFormat::Png)),
+        Some(ImageType::Png)
+    );
+    assert_eq!(
+        sniff_image_type(&encoded(4, 4, ImageFormat::WebP)),
+        Some(ImageType::Webp)
+    );
+    assert_eq!(sniff_image_type(b"GIF89a...."), None);
+    assert_eq!(
+        sniff_image_type(b"<svg xmlns=\"http://www.w3.org/2000/svg\">"),
+        None
+    );
+    assert_eq!(sniff_image_type(b""), None);
+}
+
+#[test]
+fn test_content_type_follows_key_extension() {
+    assert_eq!(content_type_for_key("1/abc.jpg"), Some("image/jpeg"));
+    assert_eq!(content_type_for_key("1/abc_thumb.webp"), Some("image/webp"));
+    assert_eq!(content_type_for_key("1/abc.html"), None);
+    assert_eq!(content_type_for_key("1/abc"), None);
+}
+
+#[test]
+fn test_processes_image_and_builds_thumbnail() {
+    let processed = process_image(&encoded(1000, 500, ImageFormat::Png), 1 << 20).unwrap();
+
+    assert_eq!(processed.image_type, ImageType::Png);
+    assert_eq!((processed.width, processed.height), (1000, 500));
+
+    let thumbnail = image::load_from_memory(&processed.thumbnail).unwrap();
+    assert_eq!(thumbnail.dimensions(), (THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2));
+
+    // 4x3 components: size flag, DC and 11 AC values of 2 characters each
+    assert_eq!(processed.blurhash.len(), 28);
+}
+
+#[test]
+fn test_applies_orientation_and_strips_exif() {
+    let data = jpeg_with_exif_orientation(40, 20);
+    assert!(data.windows(4).any(|window| window == b"Exif"));
+
+    let processed = process_image(&data, 1 << 20).unwrap();
+
+    assert_eq!((processed.width, processed.height), (20, 40));
+    assert!(!processed.data.windows(4).any(|window| window == b"Exif"));
+}
+
+#[test]
+fn test_rejects_unsupported_and_oversized_uploads() {
+    assert!(matches!(
+        process_image(b"GIF89a....", 1 << 20),
+        Err(MediaError::UnsupportedType)
+    ));
+    assert!(matches!(
+        process_image(&encoded(64, 64, ImageFormat::Png), 16),
+        Err(MediaError::TooLarge)
+    ));
+    assert!(matches!(
+        process_image(
+            &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0],
+            1 << 20
+        ),
+        Err(MediaError::InvalidImage(_))
+    ));
+}
+
+fn attachment(media_id: i32, alt_text: Option<String>) -> NewPostMedia {
+    NewPostMedia { media_id, alt_text }
+}
+
+#[test]
+fn test_validates_attachments() {
+    let four: Vec<_> = (1..=4).map(|id| attachment(id, None)).collect();
+    assert!(validate_attachments(&four).is_ok());
+    assert!(validate_attachments(&[]).is_ok());
+
+    let five: Vec<_> = (1..=5).map(|id| attachment(id, None)).collect();
+    assert!(matches!(
+        validate_attachments(&five),
+        Err(MediaError::TooManyAttachments)
+    ));
+    assert!(matches!(
+        validate_attachments(&[attachment(1, None), attachment(1, None)]),
+        Err(MediaError::DuplicateAttachment)
+    ));
+}
+
+#[test]
+fn test_limits_alt_text_by_characters() {
+    let longest = "é".repeat(MAX_ALT_TEXT_LENGTH);
+    assert!(validate_attachments(&[attachment(1, Some(longest))]).is_ok());
+
+    let too_long = "a".repeat(MAX_ALT_TEXT_LENGTH + 1);
+    assert!(matches!(
+        validate_attachments(&[attachment(1, Some(too_long))]),
+        Err(MediaError::AltTextTooLong)
+    ));
+}
+
+#[test]
+fn test_blob_keys_cannot_escape_root() {
+    assert_eq!(validate_key("12/abc.jpg"), Ok(()));
+    assert_eq!(validate_key("12/abc_thumb.webp"), Ok(()));
+
+    for key in [
+        "",
+        "../etc/passwd",
+        "12/../../x",
+        "/abs.jpg",
+        "12//a.jpg",
+        "12/./a.jpg",
+    ] {
+        assert_eq!(validate_key(key), Err(InvalidBlobKey), "{}", key);
+    }
+    assert_eq!(validate_key("12/a b.jpg"), Err(InvalidBlobKey));
+    assert_eq!(validate_key("12\\a.jpg"), Err(InvalidBlobKey));
+}
+
+#[test]
+fn test_sigv4_signing_key() {
+    // Example from the AWS Signature Version 4 documentation
+    let key = signing_key(
+        "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+        "20120215",
+        "us-east-1",
+        "iam",
+    );
+
+    assert_eq!(
+        hex::encode(key),
+        "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+    );
+}
+
+#[test]
+fn test_image_urls_must_point_at_uploaded_media() {
+    let base = "https://cdn.example.com/media/";
+
+    assert!(validate_image_url(base, None).is_ok());
+    assert!(validate_image_url(base, Some("https://cdn.example.com/media/3/abc.png")).is_ok());
+    for url in [
+        "https://evil.example.com/3/abc.png",
+        "https://cdn.example.com/media-evil/3/abc.png",
+        "https://cdn.example.com/media/",
+        "https://cdn.example.com/media/../admin",
+        "",
+    ] {
+        assert!(
+            matches!(
+                validate_image_url(base, Some(url)),
+                Err(MediaError::ExternalUrl)
+            ),
+            "{url}"
+        );
+    }
+}
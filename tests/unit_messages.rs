@@ -0,0 +1,46 @@
+use todo_api::service::messages::{
+    direct_key, normalize_members, normalize_message, MAX_CONVERSATION_MEMBERS, MAX_MESSAGE_LENGTH,
+};
+use todo_api::service::social::SocialError;
+
+#[test]
+fn test_normalize_members_drops_creator_and_duplicates() {
+    assert_eq!(normalize_members(1, &[2, 1, 3, 2]).unwrap(), vec![2, 3]);
+}
+
+#[test]
+fn test_normalize_members_rejects_empty_and_oversized_conversations() {
+    assert!(matches!(
+        normalize_members(1, &[1]),
+        Err(SocialError::InvalidConversationMembers)
+    ));
+
+    let others: Vec<i32> = (2..=MAX_CONVERSATION_MEMBERS as i32).collect();
+    assert_eq!(normalize_members(1, &others).unwrap().len(), others.len());
+
+    let too_many: Vec<i32> = (2..=MAX_CONVERSATION_MEMBERS as i32 + 1).collect();
+    assert!(matches!(
+        normalize_members(1, &too_many),
+        Err(SocialError::InvalidConversationMembers)
+    ));
+}
+
+#[test]
+fn test_direct_key_is_the_same_from_both_sides() {
+    assert_eq!(direct_key(7, 3), "3:7");
+    assert_eq!(direct_key(3, 7), direct_key(7, 3));
+}
+
+#[test]
+fn test_normalize_message() {
+    assert_eq!(normalize_message("  hi there \n").unwrap(), "hi there");
+    assert!(matches!(
+        normalize_message("   "),
+        Err(SocialError::InvalidMessage)
+    ));
+    assert!(normalize_message(&"é".repeat(MAX_MESSAGE_LENGTH)).is_ok());
+    assert!(matches!(
+        normalize_message(&"a".repeat(MAX_MESSAGE_LENGTH + 1)),
+        Err(SocialError::InvalidMessage)
+    ));
+}
@@ -0,0 +1,121 @@
+use todo_api::db::models::{ReportReason, ReportStatus, ReportTarget};
+use todo_api::service::moderation::{
+    extract_domains, ContentFilter, FilterAction, FilterMatch, RuleFilter,
+};
+
+fn filter(json: &str) -> RuleFilter {
+    RuleFilter::from_json(json).unwrap()
+}
+
+fn action(filter: &RuleFilter, text: &str) -> Option<FilterAction> {
+    filter.check(text).map(|found| found.action)
+}
+
+#[test]
+fn test_keywords_match_whole_words_case_insensitively() {
+    let filter = filter(r#"{"rules": [{"action": "reject", "keywords": ["buy now", "scam"]}]}"#);
+
+    assert_eq!(
+        filter.check("BUY NOW while stocks last"),
+        Some(FilterMatch {
+            action: FilterAction::Reject,
+            rule: "keyword \"buy now\"".to_string(),
+        })
+    );
+    assert_eq!(action(&filter, "total scam!"), Some(FilterAction::Reject));
+    assert_eq!(action(&filter, "scampi for dinner"), None);
+    assert_eq!(action(&filter, "nothing to see"), None);
+}
+
+#[test]
+fn test_patterns_are_regular_expressions() {
+    let filter = filter(r#"{"rules": [{"action": "hold", "patterns": ["\\d{3}-\\d{4}"]}]}"#);
+
+    assert_eq!(action(&filter, "call 555-0199"), Some(FilterAction::Hold));
+    assert_eq!(action(&filter, "call me"), None);
+}
+
+#[test]
+fn test_domains_cover_subdomains() {
+    let filter = filter(r#"{"rules": [{"action": "shadow_hide", "domains": ["Spam.example"]}]}"#);
+
+    assert_eq!(
+        action(&filter, "see https://spam.example/offer"),
+        Some(FilterAction::ShadowHide)
+    );
+    assert_eq!(
+        action(&filter, "see http://cdn.spam.example:8080/x"),
+        Some(FilterAction::ShadowHide)
+    );
+    assert_eq!(
+        action(&filter, "www.spam.example."),
+        Some(FilterAction::ShadowHide)
+    );
+    assert_eq!(action(&filter, "https://notspam.example"), None);
+    assert_eq!(action(&filter, "spam.example without a link"), None);
+}
+
+#[test]
+fn test_harshest_matching_rule_wins() {
+    let filter = filter(
+        r#"{"rules": [
+            {"action": "shadow_hide", "keywords": ["crypto"]},
+            {"action": "reject", "keywords": ["giveaway"]},
+            {"action": "hold", "keywords": ["crypto"]}
+        ]}"#,
+    );
+
+    assert_eq!(action(&filter, "crypto news"), Some(FilterAction::Hold));
+    assert_eq!(
+        action(&filter, "crypto giveaway"),
+        Some(FilterAction::Reject)
+    );
+    assert!(FilterAction::ShadowHide < FilterAction::Hold);
+    assert!(FilterAction::Hold < FilterAction::Reject);
+}
+
+#[test]
+fn test_empty_and_invalid_rule_files() {
+    let empty = RuleFilter::default();
+    assert!(empty.is_empty());
+    assert_eq!(empty.check("anything"), None);
+
+    assert!(RuleFilter::from_json(r#"{"rules": [{"action": "ban"}]}"#).is_err());
+    assert!(
+        RuleFilter::from_json(r#"{"rules": [{"action": "hold", "patterns": ["("]}]}"#).is_err()
+    );
+}
+
+#[test]
+fn test_moderation_states() {
+    assert_eq!(FilterAction::ShadowHide.moderation_state(), Some("hidden"));
+    assert_eq!(FilterAction::Hold.moderation_state(), Some("held"));
+    assert_eq!(FilterAction::Reject.moderation_state(), None);
+}
+
+#[test]
+fn test_extract_domains() {
+    assert_eq!(
+        extract_domains("a https://Example.com/x, b www.test.org. c http://example.com"),
+        vec!["example.com".to_string(), "www.test.org".to_string()]
+    );
+    assert!(extract_domains("no links, just me@example.com").is_empty());
+}
+
+#[test]
+fn test_report_enums_round_trip() {
+    for target in ReportTarget::ALL {
+        assert_eq!(ReportTarget::parse(target.as_str()), Some(target));
+    }
+    for reason in ReportReason::ALL {
+        assert_eq!(ReportReason::parse(reason.as_str()), Some(reason));
+        assert_eq!(
+            serde_json::to_value(reason).unwrap(),
+            serde_json::json!(reason.as_str())
+        );
+    }
+    for status in ReportStatus::ALL {
+        assert_eq!(ReportStatus::parse(status.as_str()), Some(status));
+    }
+    assert_eq!(ReportTarget::parse("todo"), None);
+}
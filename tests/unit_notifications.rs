@@ -0,0 +1,91 @@
+use chrono::NaiveDate;
+use todo_api::db::models::{NotificationKind, NotificationPreference};
+use todo_api::service::notifications::{
+    group_key, resolve_preferences, summary, InvalidNotificationCursor, NotificationCursor,
+};
+
+#[test]
+fn test_group_key_separates_targets() {
+    assert_eq!(group_key(NotificationKind::Like, Some(5), None), "like:5:-");
+    assert_eq!(
+        group_key(NotificationKind::Reply, Some(5), Some(9)),
+        "reply:5:9"
+    );
+    assert_ne!(
+        group_key(NotificationKind::Like, Some(5), None),
+        group_key(NotificationKind::Repost, Some(5), None)
+    );
+}
+
+#[test]
+fn test_group_key_ignores_targets_for_follows() {
+    assert_eq!(group_key(NotificationKind::Follow, None, None), "follow");
+    assert_eq!(
+        group_key(NotificationKind::FollowRequest, Some(1), None),
+        "follow_request"
+    );
+}
+
+#[test]
+fn test_summary_counts_other_actors() {
+    assert_eq!(
+        summary(NotificationKind::Like, "alice", 1),
+        "alice liked your post"
+    );
+    assert_eq!(
+        summary(NotificationKind::Like, "alice", 2),
+        "alice and 1 other liked your post"
+    );
+    assert_eq!(
+        summary(NotificationKind::Follow, "alice", 4),
+        "alice and 3 others followed you"
+    );
+}
+
+#[test]
+fn test_resolve_preferences_defaults_to_enabled() {
+    let stored = [NotificationPreference {
+        kind: NotificationKind::Like,
+        enabled: false,
+    }];
+
+    let preferences = resolve_preferences(&stored);
+
+    assert_eq!(preferences.len(), NotificationKind::ALL.len());
+    for preference in preferences {
+        assert_eq!(
+            preference.enabled,
+            preference.kind != NotificationKind::Like
+        );
+    }
+}
+
+#[test]
+fn test_kind_round_trip() {
+    for kind in NotificationKind::ALL {
+        assert_eq!(NotificationKind::parse(kind.as_str()), Some(kind));
+        assert_eq!(
+            serde_json::to_value(kind).unwrap(),
+            serde_json::Value::String(kind.as_str().to_string())
+        );
+    }
+    assert_eq!(NotificationKind::parse("poke"), None);
+}
+
+#[test]
+fn test_cursor_round_trip() {
+    let cursor = NotificationCursor {
+        updated_at: NaiveDate::from_ymd_opt(2025, 9, 1)
+            .unwrap()
+            .and_hms_micro_opt(12, 0, 0, 123_456)
+            .unwrap(),
+        id: 42,
+    };
+
+    let parsed: NotificationCursor = cursor.to_string().parse().unwrap();
+    assert_eq!(parsed, cursor);
+    assert_eq!(
+        "abc_1".parse::<NotificationCursor>(),
+        Err(InvalidNotificationCursor)
+    );
+}
@@ -0,0 +1,96 @@
+use chrono::Utc;
+use todo_api::db::models::{CreatePost, NewPoll, Poll, PollOption};
+use todo_api::service::polls::{
+    redact_results, validate, MAX_POLL_DURATION_MINUTES, MAX_POLL_OPTION_LENGTH,
+    MIN_POLL_DURATION_MINUTES,
+};
+use todo_api::service::social::SocialError;
+
+fn new_poll(options: &[&str], duration_minutes: i32) -> NewPoll {
+    NewPoll {
+        options: options.iter().map(|option| option.to_string()).collect(),
+        duration_minutes,
+    }
+}
+
+fn poll(closed: bool, voted_option_id: Option<i32>) -> Poll {
+    Poll {
+        id: 1,
+        closes_at: Utc::now().naive_utc(),
+        closed,
+        vote_count: 3,
+        options: vec![
+            PollOption {
+                id: 10,
+                label: "yes".to_string(),
+                vote_count: Some(2),
+            },
+            PollOption {
+                id: 11,
+                label: "no".to_string(),
+                vote_count: Some(1),
+            },
+        ],
+        voted_option_id,
+    }
+}
+
+#[test]
+fn test_validate_trims_answers() {
+    assert_eq!(
+        validate(&new_poll(&[" tea ", "coffee"], 60)).unwrap(),
+        vec!["tea".to_string(), "coffee".to_string()]
+    );
+    assert!(validate(&new_poll(&["a", "b", "c", "d"], MAX_POLL_DURATION_MINUTES)).is_ok());
+}
+
+#[test]
+fn test_validate_rejects_bad_polls() {
+    let too_long = "x".repeat(MAX_POLL_OPTION_LENGTH + 1);
+    for poll in [
+        new_poll(&["only one"], 60),
+        new_poll(&["a", "b", "c", "d", "e"], 60),
+        new_poll(&["a", "  "], 60),
+        new_poll(&["Tea", "tea"], 60),
+        new_poll(&["a", &too_long], 60),
+        new_poll(&["a", "b"], MIN_POLL_DURATION_MINUTES - 1),
+        new_poll(&["a", "b"], MAX_POLL_DURATION_MINUTES + 1),
+    ] {
+        assert!(
+            matches!(validate(&poll), Err(SocialError::InvalidPoll)),
+            "{:?}",
+            poll
+        );
+    }
+}
+
+#[test]
+fn test_results_hidden_until_voted_or_closed() {
+    let mut open = poll(false, None);
+    redact_results(&mut open);
+    assert!(open
+        .options
+        .iter()
+        .all(|option| option.vote_count.is_none()));
+    assert_eq!(open.vote_count, 3);
+
+    let mut voted = poll(false, Some(10));
+    redact_results(&mut voted);
+    assert_eq!(voted.options[0].vote_count, Some(2));
+
+    let mut closed = poll(true, None);
+    redact_results(&mut closed);
+    assert_eq!(closed.options[1].vote_count, Some(1));
+}
+
+#[test]
+fn test_create_post_accepts_optional_poll() {
+    let post: CreatePost = serde_json::from_str(
+        r#"{"content": "lunch?", "poll": {"options": ["pizza", "sushi"], "duration_minutes": 30}}"#,
+    )
+    .unwrap();
+    assert_eq!(post.poll, Some(new_poll(&["pizza", "sushi"], 30)));
+
+    let post: CreatePost = serde_json::from_str(r#"{"content": "no poll"}"#).unwrap();
+    assert!(post.poll.is_none());
+}
@@ -0,0 +1,86 @@
+#[path = "common/fixtures.rs"]
+mod fixtures;
+
+use fixtures::SocialFixture;
+use todo_api::db::models::{Post, ReplyMode, ThreadReply};
+use todo_api::service::post_thread::build_reply_tree;
+
+fn post(id: i32, reply_to_post_id: Option<i32>) -> Post {
+    Post {
+        reply_to_post_id,
+        ..SocialFixture::post(id, 1)
+    }
+}
+
+fn ids(replies: &[ThreadReply]) -> Vec<i32> {
+    replies.iter().map(|reply| reply.post.id).collect()
+}
+
+#[test]
+fn test_build_reply_tree_nests_replies() {
+    // 1 <- 2 <- 4 <- 6
+    //   <- 3 <- 5
+    let rows = vec![
+        (post(2, Some(1)), 1),
+        (post(3, Some(1)), 1),
+        (post(4, Some(2)), 2),
+        (post(5, Some(3)), 2),
+        (post(6, Some(4)), 3),
+    ];
+
+    let (replies, has_more) = build_reply_tree(1, rows, 10);
+
+    assert!(!has_more);
+    assert_eq!(ids(&replies), vec![2, 3]);
+    assert_eq!(ids(&replies[0].replies), vec![4]);
+    assert_eq!(ids(&replies[0].replies[0].replies), vec![6]);
+    assert_eq!(replies[0].replies[0].replies[0].depth, 3);
+    assert_eq!(ids(&replies[1].replies), vec![5]);
+}
+
+#[test]
+fn test_build_reply_tree_drops_extra_direct_replies_with_their_subtrees() {
+    let rows = vec![
+        (post(2, Some(1)), 1),
+        (post(3, Some(1)), 1),
+        (post(4, Some(1)), 1),
+        (post(5, Some(4)), 2),
+    ];
+
+    let (replies, has_more) = build_reply_tree(1, rows, 2);
+
+    assert!(has_more);
+    assert_eq!(ids(&replies), vec![2, 3]);
+    assert!(replies.iter().all(|reply| reply.replies.is_empty()));
+}
+
+#[test]
+fn test_build_reply_tree_ignores_orphans() {
+    let rows = vec![(post(2, Some(1)), 1), (post(7, Some(99)), 2)];
+
+    let (replies, _) = build_reply_tree(1, rows, 10);
+
+    assert_eq!(ids(&replies), vec![2]);
+    assert!(replies[0].replies.is_empty());
+}
+
+#[test]
+fn test_build_reply_tree_without_replies() {
+    let (replies, has_more) = build_reply_tree(1, Vec::new(), 10);
+    assert!(replies.is_empty());
+    assert!(!has_more);
+}
+
+#[test]
+fn test_reply_mode_parsing() {
+    assert_eq!(ReplyMode::default(), ReplyMode::Include);
+    assert_eq!(
+        serde_json::from_str::<ReplyMode>(r#""exclude""#).unwrap(),
+        ReplyMode::Exclude
+    );
+    assert_eq!(
+        serde_json::from_str::<ReplyMode>(r#""collapse""#).unwrap(),
+        ReplyMode::Collapse
+    );
+    assert!(serde_json::from_str::<ReplyMode>(r#""hide""#).is_err());
+}
@@ -0,0 +1,50 @@
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use todo_api::handlers::preconditions::{etag, if_match_versions};
+
+fn headers_with_if_match(value: &'static str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::IF_MATCH, HeaderValue::from_static(value));
+    headers
+}
+
+#[test]
+fn test_etag_is_quoted_version() {
+    assert_eq!(etag(7), HeaderValue::from_static("\"7\""));
+}
+
+#[test]
+fn test_missing_if_match() {
+    let headers = HeaderMap::new();
+    assert_eq!(if_match_versions(&headers, false), Ok(None));
+    assert_eq!(
+        if_match_versions(&headers, true),
+        Err(StatusCode::PRECONDITION_REQUIRED)
+    );
+}
+
+#[test]
+fn test_wildcard_if_match_is_unconditional() {
+    let headers = headers_with_if_match("*");
+    assert_eq!(if_match_versions(&headers, true), Ok(None));
+}
+
+#[test]
+fn test_if_match_lists_versions() {
+    let headers = headers_with_if_match("\"3\", \"4\"");
+    assert_eq!(if_match_versions(&headers, false), Ok(Some(vec![3, 4])));
+}
+
+#[test]
+fn test_weak_or_malformed_if_match_never_matches() {
+    let headers = headers_with_if_match("W/\"3\"");
+    assert_eq!(
+        if_match_versions(&headers, false),
+        Err(StatusCode::PRECONDITION_FAILED)
+    );
+
+    let headers = headers_with_if_match("\"abc\"");
+    assert_eq!(
+        if_match_versions(&headers, false),
+        Err(StatusCode::PRECONDITION_FAILED)
+    );
+}
@@ -0,0 +1,132 @@
+#[path = "common/fixtures.rs"]
+mod fixtures;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use fixtures::SocialFixture;
+use serial_test::serial;
+use todo_api::db::models::Post;
+use todo_api::service::ranking::{rank, score, Candidate, CandidateSource, RankingWeights};
+
+fn now() -> NaiveDateTime {
+    Utc::now().naive_utc()
+}
+
+fn candidate(id: i32, age_hours: i64, likes: i32, source: CandidateSource) -> Candidate {
+    let created_at = now() - Duration::hours(age_hours);
+    Candidate {
+        post: Post {
+            like_count: Some(likes),
+            created_at,
+            updated_at: created_at,
+            ..SocialFixture::post(id, id)
+        },
+        source,
+        liked_author_posts: 0,
+        commented_author_posts: 0,
+    }
+}
+
+fn ids(candidates: &[Candidate]) -> Vec<i32> {
+    candidates
+        .iter()
+        .map(|candidate| candidate.post.id)
+        .collect()
+}
+
+#[test]
+fn test_score_halves_after_half_life() {
+    let weights = RankingWeights {
+        follow: 0.0,
+        ..RankingWeights::default()
+    };
+    let now = now();
+    let fresh = candidate(1, 0, 0, CandidateSource::Followed);
+    let mut old = fresh.clone();
+    old.post.created_at = fresh.post.created_at - Duration::hours(12);
+
+    let ratio = score(&old, now, &weights) / score(&fresh, now, &weights);
+    assert!((ratio - 0.5).abs() < 0.01, "ratio was {}", ratio);
+}
+
+#[test]
+fn test_engagement_raises_score() {
+    let weights = RankingWeights::default();
+    let quiet = candidate(1, 1, 0, CandidateSource::Trending);
+    let popular = candidate(2, 1, 50, CandidateSource::Trending);
+
+    assert_eq!(
+        ids(&rank(vec![quiet, popular], now(), &weights)),
+        vec![2, 1]
+    );
+}
+
+#[test]
+fn test_affinity_raises_score() {
+    let weights = RankingWeights::default();
+    let stranger = candidate(1, 1, 0, CandidateSource::SecondDegree);
+    let mut friend = candidate(2, 1, 0, CandidateSource::SecondDegree);
+    friend.liked_author_posts = 5;
+    friend.commented_author_posts = 2;
+
+    assert_eq!(
+        ids(&rank(vec![stranger, friend], now(), &weights)),
+        vec![2, 1]
+    );
+}
+
+#[test]
+fn test_followed_beats_trending_with_equal_engagement() {
+    let weights = RankingWeights::default();
+    let trending = candidate(1, 1, 3, CandidateSource::Trending);
+    let followed = candidate(2, 1, 3, CandidateSource::Followed);
+    let second_degree = candidate(3, 1, 3, CandidateSource::SecondDegree);
+
+    assert_eq!(
+        ids(&rank(
+            vec![trending, followed, second_degree],
+            now(),
+            &weights
+        )),
+        vec![2, 3, 1]
+    );
+}
+
+#[test]
+fn test_recency_beats_stale_engagement() {
+    let weights = RankingWeights::default();
+    let stale = candidate(1, 72, 20, CandidateSource::Followed);
+    let fresh = candidate(2, 0, 1, CandidateSource::Followed);
+
+    assert_eq!(ids(&rank(vec![stale, fresh], now(), &weights)), vec![2, 1]);
+}
+
+#[test]
+fn test_rank_breaks_ties_by_newest_post() {
+    let weights = RankingWeights {
+        half_life_hours: 0.0,
+        ..RankingWeights::default()
+    };
+    let older = candidate(1, 5, 0, CandidateSource::Followed);
+    let newer = candidate(2, 1, 0, CandidateSource::Followed);
+
+    assert_eq!(ids(&rank(vec![older, newer], now(), &weights)), vec![2, 1]);
+}
+
+#[test]
+#[serial]
+fn test_weights_from_env() {
+    std::env::set_var("RANKING_LIKE_WEIGHT", "4.5");
+    std::env::set_var("RANKING_CANDIDATE_WINDOW_HOURS", "24");
+    let weights = RankingWeights::from_env().unwrap();
+    std::env::remove_var("RANKING_LIKE_WEIGHT");
+    std::env::remove_var("RANKING_CANDIDATE_WINDOW_HOURS");
+
+    assert_eq!(weights.like, 4.5);
+    assert_eq!(weights.candidate_window_hours, 24);
+    assert_eq!(weights.comment, RankingWeights::default().comment);
+
+    std::env::set_var("RANKING_REPOST_WEIGHT", "lots");
+    let result = RankingWeights::from_env();
+    std::env::remove_var("RANKING_REPOST_WEIGHT");
+    assert!(result.is_err());
+}
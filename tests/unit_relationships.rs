@@ -0,0 +1,40 @@
+use todo_api::service::relationships::{
+    mutual_followers_summary, parse_user_ids, MAX_RELATIONSHIP_BATCH,
+};
+
+#[test]
+fn test_summary_names_mutual_followers() {
+    assert_eq!(mutual_followers_summary(&[], 0), None);
+    assert_eq!(
+        mutual_followers_summary(&["alice"], 1).as_deref(),
+        Some("Followed by alice")
+    );
+    assert_eq!(
+        mutual_followers_summary(&["alice", "bob"], 2).as_deref(),
+        Some("Followed by alice and bob")
+    );
+}
+
+#[test]
+fn test_summary_counts_the_rest() {
+    assert_eq!(
+        mutual_followers_summary(&["alice", "bob"], 3).as_deref(),
+        Some("Followed by alice, bob and 1 other")
+    );
+    assert_eq!(
+        mutual_followers_summary(&["alice", "bob"], 14).as_deref(),
+        Some("Followed by alice, bob and 12 others")
+    );
+}
+
+#[test]
+fn test_parse_user_ids() {
+    assert_eq!(parse_user_ids("3, 1,3,2"), Some(vec![3, 1, 2]));
+    assert_eq!(parse_user_ids(""), None);
+    assert_eq!(parse_user_ids("1,x"), None);
+
+    let too_many: Vec<String> = (1..=MAX_RELATIONSHIP_BATCH as i32 + 1)
+        .map(|id| id.to_string())
+        .collect();
+    assert_eq!(parse_user_ids(&too_many.join(",")), None);
+}
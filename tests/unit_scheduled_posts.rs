@@ -0,0 +1,58 @@
+use chrono::{Duration, Utc};
+use todo_api::db::models::{SaveScheduledPost, ScheduledPostStatus};
+use todo_api::service::media::MediaError;
+use todo_api::service::scheduled_posts::{is_permanent_failure, status_for};
+use todo_api::service::social::SocialError;
+
+#[test]
+fn test_status_follows_publish_time() {
+    let now = Utc::now().naive_utc();
+
+    assert_eq!(status_for(None, now), Some(ScheduledPostStatus::Draft));
+    assert_eq!(
+        status_for(Some(now + Duration::minutes(5)), now),
+        Some(ScheduledPostStatus::Scheduled)
+    );
+    assert_eq!(status_for(Some(now), now), None);
+    assert_eq!(status_for(Some(now - Duration::minutes(5)), now), None);
+}
+
+#[test]
+fn test_status_round_trips_through_strings() {
+    for status in ScheduledPostStatus::ALL {
+        assert_eq!(ScheduledPostStatus::parse(status.as_str()), Some(status));
+    }
+    assert_eq!(ScheduledPostStatus::parse("published"), None);
+}
+
+#[test]
+fn test_save_body_extends_create_post() {
+    let draft: SaveScheduledPost = serde_json::from_str(
+        r#"{
+            "content": "later",
+            "media": [{"media_id": 4, "alt_text": "a cat"}],
+            "reply_to_post_id": 9,
+            "publish_at": "2030-01-02T03:04:05"
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(draft.post.content, "later");
+    assert_eq!(draft.post.media[0].media_id, 4);
+    assert_eq!(draft.post.reply_to_post_id, Some(9));
+    assert_eq!(draft.publish_at.unwrap().to_string(), "2030-01-02 03:04:05");
+
+    let draft: SaveScheduledPost = serde_json::from_str(r#"{"content": "someday"}"#).unwrap();
+    assert!(draft.publish_at.is_none());
+    assert!(draft.post.media.is_empty());
+}
+
+#[test]
+fn test_only_domain_errors_fail_permanently() {
+    assert!(is_permanent_failure(
+        &SocialError::ReplyTargetNotFound.into()
+    ));
+    assert!(is_permanent_failure(&SocialError::ContentRejected.into()));
+    assert!(is_permanent_failure(&MediaError::NotFound.into()));
+    assert!(!is_permanent_failure(&anyhow::anyhow!("connection reset")));
+}
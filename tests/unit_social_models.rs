@@ -0,0 +1,91 @@
+#[path = "common/fixtures.rs"]
+mod fixtures;
+
+use chrono::Utc;
+use fixtures::SocialFixture;
+use todo_api::db::models::{
+    CreatePost, FeedEntry, Follow, FollowOutcome, FollowRequest, RepostAttribution,
+};
+
+#[test]
+fn test_create_post_quote_is_optional() {
+    let create: CreatePost = serde_json::from_str(r#"{"content": "hello"}"#).unwrap();
+    assert!(create.quote_of_post_id.is_none());
+    assert!(create.reply_to_post_id.is_none());
+
+    let create: CreatePost =
+        serde_json::from_str(r#"{"content": "look at this", "quote_of_post_id": 7}"#).unwrap();
+    assert_eq!(create.quote_of_post_id, Some(7));
+}
+
+#[test]
+fn test_feed_entry_flattens_post() {
+    let entry = FeedEntry {
+        post: SocialFixture::post(3, 1),
+        reposted_by: None,
+    };
+
+    let body = serde_json::to_value(&entry).unwrap();
+    assert_eq!(body["id"], 3);
+    assert!(body.get("reposted_by").is_none());
+}
+
+#[test]
+fn test_post_shows_edits_and_hides_unset_moderation_state() {
+    let mut edited = SocialFixture::post(3, 1);
+    edited.edited_at = Some(edited.created_at);
+    edited.revision_count = 2;
+
+    let body = serde_json::to_value(SocialFixture::post(3, 1)).unwrap();
+    assert!(body["edited_at"].is_null());
+    assert_eq!(body["revision_count"], 0);
+    assert!(body.get("moderation_state").is_none());
+
+    let body = serde_json::to_value(&edited).unwrap();
+    assert!(body["edited_at"].is_string());
+    assert_eq!(body["revision_count"], 2);
+}
+
+#[test]
+fn test_feed_entry_carries_reposter() {
+    let reposted_at = Utc::now().naive_utc();
+    let entry = FeedEntry {
+        post: SocialFixture::post(3, 1),
+        reposted_by: Some(RepostAttribution {
+            user_id: 2,
+            username: "bob".to_string(),
+            reposted_at,
+        }),
+    };
+
+    let body = serde_json::to_value(&entry).unwrap();
+    assert_eq!(body["id"], 3);
+    assert_eq!(body["reposted_by"]["user_id"], 2);
+    assert_eq!(body["reposted_by"]["username"], "bob");
+}
+
+#[test]
+fn test_follow_outcome_serializes_without_tag() {
+    let now = Utc::now().naive_utc();
+    let followed = FollowOutcome::Followed(Follow {
+        id: 1,
+        follower_id: 2,
+        following_id: 3,
+        created_at: now,
+    });
+    let requested = FollowOutcome::Requested(FollowRequest {
+        id: 4,
+        requester_id: 2,
+        target_id: 3,
+        created_at: now,
+    });
+
+    let body = serde_json::to_value(&followed).unwrap();
+    assert_eq!(body["following_id"], 3);
+    assert!(body.get("target_id").is_none());
+
+    let body = serde_json::to_value(&requested).unwrap();
+    assert_eq!(body["id"], 4);
+    assert_eq!(body["target_id"], 3);
+    assert!(body.get("following_id").is_none());
+}
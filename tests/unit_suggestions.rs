@@ -0,0 +1,64 @@
+#[path = "common/fixtures.rs"]
+mod fixtures;
+
+use fixtures::SocialFixture;
+use todo_api::db::models::{FollowSuggestion, UserProfile};
+use todo_api::service::suggestions::{rank, score};
+
+fn suggestion(
+    id: i32,
+    followers: i32,
+    mutual: i64,
+    hashtags: i64,
+    interactions: i64,
+) -> FollowSuggestion {
+    FollowSuggestion {
+        user: UserProfile {
+            follower_count: Some(followers),
+            ..SocialFixture::user(id)
+        },
+        mutual_follow_count: mutual,
+        shared_hashtag_count: hashtags,
+        interaction_count: interactions,
+    }
+}
+
+fn ids(suggestions: &[FollowSuggestion]) -> Vec<i32> {
+    suggestions.iter().map(|s| s.user.id).collect()
+}
+
+#[test]
+fn test_score_without_signals_is_zero() {
+    assert_eq!(score(&suggestion(1, 0, 0, 0, 0)), 0.0);
+}
+
+#[test]
+fn test_mutual_follows_outrank_popularity() {
+    let ranked = rank(vec![
+        suggestion(1, 10_000, 0, 0, 0),
+        suggestion(2, 5, 3, 0, 0),
+    ]);
+
+    assert_eq!(ids(&ranked), vec![2, 1]);
+}
+
+#[test]
+fn test_every_signal_raises_the_score() {
+    let base = score(&suggestion(1, 10, 1, 1, 1));
+
+    assert!(score(&suggestion(1, 100, 1, 1, 1)) > base);
+    assert!(score(&suggestion(1, 10, 2, 1, 1)) > base);
+    assert!(score(&suggestion(1, 10, 1, 2, 1)) > base);
+    assert!(score(&suggestion(1, 10, 1, 1, 2)) > base);
+}
+
+#[test]
+fn test_rank_breaks_ties_by_follower_count_then_id() {
+    let ranked = rank(vec![
+        suggestion(3, 0, 1, 0, 0),
+        suggestion(2, 0, 1, 0, 0),
+        suggestion(1, 0, 0, 0, 0),
+    ]);
+
+    assert_eq!(ids(&ranked), vec![2, 3, 1]);
+}
@@ -0,0 +1,130 @@
+#[path = "common/fixtures.rs"]
+mod fixtures;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use fixtures::SocialFixture;
+use todo_api::db::models::{FeedEntry, Post, RepostAttribution};
+use todo_api::service::timeline::{
+    activity_at, merge, InvalidTimelineCursor, TimelineCursor, TimelineEventKind,
+};
+
+fn base_time() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 9, 1)
+        .unwrap()
+        .and_hms_micro_opt(12, 0, 0, 123_456)
+        .unwrap()
+}
+
+fn entry(id: i32, minutes: i64) -> FeedEntry {
+    let created_at = base_time() + Duration::minutes(minutes);
+    FeedEntry {
+        post: Post {
+            created_at,
+            updated_at: created_at,
+            ..SocialFixture::post(id, id)
+        },
+        reposted_by: None,
+    }
+}
+
+fn reposted(mut entry: FeedEntry, by: i32, minutes: i64) -> FeedEntry {
+    entry.reposted_by = Some(RepostAttribution {
+        user_id: by,
+        username: format!("user{}", by),
+        reposted_at: base_time() + Duration::minutes(minutes),
+    });
+    entry
+}
+
+fn ids(entries: &[FeedEntry]) -> Vec<i32> {
+    entries.iter().map(|entry| entry.post.id).collect()
+}
+
+#[test]
+fn test_cursor_round_trip() {
+    let cursor = TimelineCursor {
+        activity_at: base_time(),
+        post_id: 42,
+    };
+
+    let parsed: TimelineCursor = cursor.to_string().parse().unwrap();
+    assert_eq!(parsed, cursor);
+}
+
+#[test]
+fn test_cursor_rejects_malformed_values() {
+    for value in ["", "abc", "123", "123_", "_42", "x_42", "123_y", "123_42_1"] {
+        assert_eq!(
+            value.parse::<TimelineCursor>(),
+            Err(InvalidTimelineCursor),
+            "{:?} should not parse",
+            value
+        );
+    }
+}
+
+#[test]
+fn test_cursor_uses_repost_time() {
+    let entry = reposted(entry(7, 0), 3, 30);
+    let cursor = TimelineCursor::after(&entry);
+
+    assert_eq!(cursor.post_id, 7);
+    assert_eq!(cursor.activity_at, base_time() + Duration::minutes(30));
+    assert_eq!(activity_at(&entry), cursor.activity_at);
+}
+
+#[test]
+fn test_merge_orders_newest_first() {
+    let merged = merge(
+        vec![entry(1, 10), entry(2, 0)],
+        vec![entry(3, 5), entry(4, 20)],
+        10,
+    );
+
+    assert_eq!(ids(&merged), vec![4, 1, 3, 2]);
+}
+
+#[test]
+fn test_merge_breaks_ties_by_post_id() {
+    let merged = merge(vec![entry(1, 0)], vec![entry(2, 0)], 10);
+    assert_eq!(ids(&merged), vec![2, 1]);
+}
+
+#[test]
+fn test_merge_keeps_latest_activity_for_duplicates() {
+    let merged = merge(
+        vec![entry(1, 0), entry(2, 5)],
+        vec![reposted(entry(1, 0), 9, 15)],
+        10,
+    );
+
+    assert_eq!(ids(&merged), vec![1, 2]);
+    let repost = merged[0].reposted_by.as_ref().unwrap();
+    assert_eq!(repost.user_id, 9);
+}
+
+#[test]
+fn test_merge_truncates_to_limit() {
+    let merged = merge(
+        vec![entry(1, 1), entry(2, 2), entry(3, 3)],
+        vec![entry(4, 4)],
+        2,
+    );
+
+    assert_eq!(ids(&merged), vec![4, 3]);
+}
+
+#[test]
+fn test_event_kind_round_trip() {
+    for kind in [
+        TimelineEventKind::PostCreated,
+        TimelineEventKind::PostDeleted,
+        TimelineEventKind::RepostCreated,
+        TimelineEventKind::RepostDeleted,
+        TimelineEventKind::FollowCreated,
+        TimelineEventKind::FollowDeleted,
+    ] {
+        assert_eq!(TimelineEventKind::parse(kind.as_str()), Some(kind));
+    }
+    assert_eq!(TimelineEventKind::parse("like_created"), None);
+}
@@ -0,0 +1,140 @@
+use chrono::Utc;
+use serde_json::json;
+use todo_api::db::models::{TodoModel, TodoRevisionModel};
+use todo_api::handlers::todo::models::TodoRevision;
+use todo_api::service::todo_history::{diff, replay, ContentSnapshot, RevisionAction};
+
+fn todo(title: &str, description: &str) -> TodoModel {
+    TodoModel {
+        id: 1,
+        title: title.to_string(),
+        description: description.to_string(),
+        created: Utc::now().naive_utc(),
+        updated: Utc::now().naive_utc(),
+        user_id: 1,
+        is_archived: false,
+        archived_at: None,
+        is_deleted: false,
+        deleted_at: None,
+        version: 1,
+    }
+}
+
+fn revision(
+    id: i32,
+    action: RevisionAction,
+    before: Option<&TodoModel>,
+    after: &TodoModel,
+) -> TodoRevisionModel {
+    TodoRevisionModel {
+        id,
+        todo_id: after.id,
+        actor_id: Some(after.user_id),
+        action: action.as_str().to_string(),
+        changes: diff(before, Some(after)),
+        reverted_to_revision_id: None,
+        created_at: Utc::now().naive_utc(),
+    }
+}
+
+#[test]
+fn test_diff_on_create_contains_every_field() {
+    let created = todo("Buy milk", "2 liters");
+    let changes = diff(None, Some(&created));
+
+    assert_eq!(changes["title"], json!({ "from": null, "to": "Buy milk" }));
+    assert_eq!(
+        changes["description"],
+        json!({ "from": null, "to": "2 liters" })
+    );
+    assert_eq!(changes["is_archived"], json!({ "from": null, "to": false }));
+    assert_eq!(changes["is_deleted"], json!({ "from": null, "to": false }));
+}
+
+#[test]
+fn test_diff_only_contains_changed_fields() {
+    let before = todo("Buy milk", "2 liters");
+    let mut after = todo("Buy oat milk", "2 liters");
+    after.is_deleted = true;
+
+    let changes = diff(Some(&before), Some(&after));
+    let changes = changes.as_object().unwrap();
+
+    assert_eq!(changes.len(), 2);
+    assert_eq!(
+        changes["title"],
+        json!({ "from": "Buy milk", "to": "Buy oat milk" })
+    );
+    assert_eq!(changes["is_deleted"], json!({ "from": false, "to": true }));
+}
+
+#[test]
+fn test_diff_of_unchanged_todo_is_empty() {
+    let before = todo("Buy milk", "2 liters");
+    assert_eq!(diff(Some(&before), Some(&before)), json!({}));
+}
+
+#[test]
+fn test_replay_returns_content_after_last_revision() {
+    let v1 = todo("Buy milk", "2 liters");
+    let v2 = todo("Buy oat milk", "2 liters");
+    let mut v3 = v2.clone();
+    v3.is_archived = true;
+    let v4 = todo("Buy oat milk", "1 liter");
+
+    let revisions = vec![
+        revision(1, RevisionAction::Create, None, &v1),
+        revision(2, RevisionAction::Update, Some(&v1), &v2),
+        revision(3, RevisionAction::Archive, Some(&v2), &v3),
+        revision(4, RevisionAction::Update, Some(&v3), &v4),
+    ];
+
+    assert_eq!(
+        replay(&revisions[..1]),
+        Some(ContentSnapshot {
+            title: "Buy milk".to_string(),
+            description: "2 liters".to_string(),
+        })
+    );
+    assert_eq!(
+        replay(&revisions[..3]),
+        Some(ContentSnapshot {
+            title: "Buy oat milk".to_string(),
+            description: "2 liters".to_string(),
+        })
+    );
+    assert_eq!(
+        replay(&revisions),
+        Some(ContentSnapshot {
+            title: "Buy oat milk".to_string(),
+            description: "1 liter".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_replay_without_create_revision_is_none() {
+    let v1 = todo("Buy milk", "2 liters");
+    let v2 = todo("Buy oat milk", "2 liters");
+
+    assert_eq!(replay(&[]), None);
+    assert_eq!(
+        replay(&[revision(2, RevisionAction::Update, Some(&v1), &v2)]),
+        None
+    );
+}
+
+#[test]
+fn test_revision_response() {
+    let created = todo("Buy milk", "2 liters");
+    let response = TodoRevision::from(revision(7, RevisionAction::Create, None, &created));
+
+    assert_eq!(response.id, 7);
+    assert_eq!(response.todo_id, 1);
+    assert_eq!(response.actor_id, Some(1));
+    assert_eq!(response.action, "create");
+    assert!(response.reverted_to_revision_id.is_none());
+
+    let body = serde_json::to_value(&response).unwrap();
+    assert!(body.get("reverted_to_revision_id").is_none());
+}
@@ -4,7 +4,7 @@ use todo_api::db::models::{
     CreateTodo, CreateUser, TodoModel, UpdateTodo, UpdateTodoPartial, User,
 };
 use todo_api::handlers::todo::models::{
-    CreateTodoRequest, PartialUpdateTodoRequest, UpdateTodoRequest,
+    CreateTodoRequest, ListTodosQuery, PartialUpdateTodoRequest, Todo, UpdateTodoRequest,
 };
 
 fn create_test_user() -> User {
@@ -43,6 +43,11 @@ fn create_test_todo_model() -> TodoModel {
         created: Utc::now().naive_utc(),
         updated: Utc::now().naive_utc(),
         user_id: 1,
+        is_archived: false,
+        archived_at: None,
+        is_deleted: false,
+        deleted_at: None,
+        version: 1,
     }
 }
 
@@ -221,3 +226,31 @@ async fn test_update_todo_partial_model() {
     assert_eq!(partial_update.title, Some("Partial Title".to_string()));
     assert!(partial_update.description.is_none());
 }
+
+#[tokio::test]
+#[serial]
+async fn test_trashed_todo_response() {
+    let mut todo = create_test_todo_model();
+    let active = Todo::from(&todo);
+    assert!(!active.is_archived);
+    assert!(active.deleted_at.is_none());
+
+    let deleted_at = Utc::now().naive_utc();
+    todo.is_archived = true;
+    todo.is_deleted = true;
+    todo.deleted_at = Some(deleted_at);
+
+    let trashed = Todo::from(todo);
+    assert!(trashed.is_archived);
+    assert_eq!(trashed.deleted_at, Some(deleted_at));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_list_todos_query_defaults_to_active() {
+    let query: ListTodosQuery = serde_json::from_str("{}").unwrap();
+    assert!(!query.archived);
+
+    let query: ListTodosQuery = serde_json::from_str(r#"{"archived": true}"#).unwrap();
+    assert!(query.archived);
+}
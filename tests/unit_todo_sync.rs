@@ -0,0 +1,147 @@
+use serde_json::json;
+use todo_api::handlers::todo::models::{
+    SyncChange, SyncChangeResult, SyncPushRequest, SyncPushResponse, SyncResponse, SyncedTodo,
+};
+use todo_api::service::todo_sync::SyncToken;
+
+#[test]
+fn test_sync_token_round_trip() {
+    for xid in [0, 1, 1304, i64::MAX] {
+        let token = SyncToken::new(xid);
+        let parsed: SyncToken = token.to_string().parse().unwrap();
+        assert_eq!(parsed, token);
+        assert_eq!(parsed.xid(), xid);
+    }
+}
+
+#[test]
+fn test_sync_token_rejects_garbage() {
+    for value in [
+        "",
+        "1304",
+        "zzzzzzzzzzzzzzzz",
+        "ffffffffffffffff",
+        "00000000000005180",
+    ] {
+        assert!(
+            value.parse::<SyncToken>().is_err(),
+            "{value} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn test_sync_push_request_parsing() {
+    let request: SyncPushRequest = serde_json::from_value(json!({
+        "since": "0000000000000518",
+        "changes": [
+            { "op": "create", "client_id": "tmp-1", "title": "Buy milk", "description": "2 liters" },
+            { "op": "update", "id": 4, "base_version": 2, "title": "Buy oat milk" },
+            { "op": "delete", "id": 5, "base_version": 1 }
+        ]
+    }))
+    .unwrap();
+
+    assert_eq!(request.since.as_deref(), Some("0000000000000518"));
+    assert!(matches!(
+        &request.changes[0],
+        SyncChange::Create { client_id: Some(client_id), .. } if client_id == "tmp-1"
+    ));
+    assert!(matches!(
+        &request.changes[1],
+        SyncChange::Update {
+            id: 4,
+            base_version: 2,
+            title: Some(_),
+            description: None
+        }
+    ));
+    assert!(matches!(
+        &request.changes[2],
+        SyncChange::Delete {
+            id: 5,
+            base_version: 1
+        }
+    ));
+}
+
+#[test]
+fn test_sync_push_request_rejects_unknown_op() {
+    let result = serde_json::from_value::<SyncPushRequest>(json!({
+        "changes": [{ "op": "archive", "id": 1 }]
+    }));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_sync_change_result_serialization() {
+    let conflict = SyncChangeResult::Conflict {
+        id: 4,
+        server: SyncedTodo {
+            id: 4,
+            title: "Buy milk".to_string(),
+            description: "2 liters".to_string(),
+            is_archived: false,
+            version: 3,
+            updated: chrono::Utc::now().naive_utc(),
+        },
+    };
+    let body = serde_json::to_value(&conflict).unwrap();
+    assert_eq!(body["status"], "conflict");
+    assert_eq!(body["server"]["version"], 3);
+
+    let deleted = SyncChangeResult::Applied {
+        client_id: None,
+        id: 5,
+        todo: None,
+    };
+    assert_eq!(
+        serde_json::to_value(&deleted).unwrap(),
+        json!({ "status": "applied", "id": 5 })
+    );
+}
+
+#[test]
+fn test_failed_sync_change_result_serialization() {
+    let failed = SyncChangeResult::Failed {
+        client_id: Some("local-1".to_string()),
+        id: None,
+        message: "The change could not be applied, push it again later".to_string(),
+    };
+    let body = serde_json::to_value(&failed).unwrap();
+    assert_eq!(body["status"], "failed");
+    assert_eq!(body["client_id"], "local-1");
+    assert!(body.get("id").is_none());
+}
+
+#[test]
+fn test_sync_push_response_carries_changes_and_token() {
+    let response = SyncPushResponse {
+        results: vec![SyncChangeResult::NotFound { id: 7 }],
+        changes: Some(SyncResponse {
+            created: Vec::new(),
+            updated: Vec::new(),
+            deleted: Vec::new(),
+            sync_token: SyncToken::new(1304).to_string(),
+        }),
+    };
+    assert_eq!(
+        serde_json::to_value(&response).unwrap(),
+        json!({
+            "results": [{ "status": "not_found", "id": 7 }],
+            "created": [],
+            "updated": [],
+            "deleted": [],
+            "sync_token": "0000000000000518"
+        })
+    );
+
+    let without_changes = SyncPushResponse {
+        results: Vec::new(),
+        changes: None,
+    };
+    assert_eq!(
+        serde_json::to_value(&without_changes).unwrap(),
+        json!({ "results": [] })
+    );
+}